@@ -1,7 +1,9 @@
-use apache_avro::{schema_compatibility::SchemaCompatibility, Schema};
+use crate::format::DegaussSchema;
+use crate::format::Incompatibility;
+use crate::report::{Finding, ReportCase, Severity};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::collections::VecDeque;
+use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString, EnumVariantNames};
 
 ///
@@ -23,14 +25,24 @@ use strum_macros::{Display, EnumIter, EnumString, EnumVariantNames};
     Deserialize,
 )]
 pub enum DegaussCompatMode {
+    /// No compatibility guarantees, every change is accepted.
+    #[strum(serialize = "none")]
+    #[serde(rename(serialize = "NONE", deserialize = "none", deserialize = "NONE"))]
+    None,
+
     /// Can read the data written by the most recent previous schema.
     #[strum(serialize = "backward")]
-    #[serde(rename(deserialize = "backward", deserialize = "BACKWARD"))]
+    #[serde(rename(
+        serialize = "BACKWARD",
+        deserialize = "backward",
+        deserialize = "BACKWARD"
+    ))]
     Backward,
 
     /// Can read the data written by all earlier schemas.
     #[strum(serialize = "backward_transitive")]
     #[serde(rename(
+        serialize = "BACKWARD_TRANSITIVE",
         deserialize = "backward-transitive",
         deserialize = "backward_transitive",
         deserialize = "BACKWARD_TRANSITIVE"
@@ -39,12 +51,17 @@ pub enum DegaussCompatMode {
 
     /// The data written by this schema can be read by the most recent previous schema.  
     #[strum(serialize = "forward")]
-    #[serde(rename(deserialize = "forward", deserialize = "FORWARD"))]
+    #[serde(rename(
+        serialize = "FORWARD",
+        deserialize = "forward",
+        deserialize = "FORWARD"
+    ))]
     Forward,
 
     /// The data written by this schema can be read by all earlier schemas.
     #[strum(serialize = "forward_transitive")]
     #[serde(rename(
+        serialize = "FORWARD_TRANSITIVE",
         deserialize = "forward-transitive",
         deserialize = "forward_transitive",
         deserialize = "FORWARD_TRANSITIVE"
@@ -53,12 +70,13 @@ pub enum DegaussCompatMode {
 
     /// Can read the data written by, a write data readable by the most recent previous schema.
     #[strum(serialize = "full")]
-    #[serde(rename(deserialize = "full", deserialize = "FULL"))]
+    #[serde(rename(serialize = "FULL", deserialize = "full", deserialize = "FULL"))]
     Full,
 
     /// Can read the data written by, a write data readable by all earlier schemas.
     #[strum(serialize = "full_transitive")]
     #[serde(rename(
+        serialize = "FULL_TRANSITIVE",
         deserialize = "full-transitive",
         deserialize = "full_transitive",
         deserialize = "FULL_TRANSITIVE"
@@ -66,6 +84,28 @@ pub enum DegaussCompatMode {
     FullTransitive,
 }
 
+impl DegaussCompatMode {
+    ///
+    /// Whether every history satisfying this compat mode also satisfies the other one.
+    ///
+    /// Full transitive implies every mode, full implies backward and forward, transitive modes imply their
+    /// non-transitive counterparts, and every mode implies none. Backward and forward don't imply each other,
+    /// so compat modes are only partially ordered by their guarantees.
+    pub fn implies(&self, other: DegaussCompatMode) -> bool {
+        use DegaussCompatMode::*;
+        matches!(
+            (*self, other),
+            (_, None)
+                | (FullTransitive, _)
+                | (Full, Full | Backward | Forward)
+                | (BackwardTransitive, BackwardTransitive | Backward)
+                | (ForwardTransitive, ForwardTransitive | Forward)
+                | (Backward, Backward)
+                | (Forward, Forward)
+        )
+    }
+}
+
 // /// Also known as 'backwards'. Can read the data written by the most recent previous schema.
 // CanReadLatest,
 // /// Also known as 'backwards transitive'. Can read the data written by all earlier schemas.
@@ -92,67 +132,208 @@ pub enum DegaussCompatMode {
 // /** Also known as 'full transitive'. Can read the data written by, a write data readable by all earlier schemas. */
 // MUTUAL_READ_WITH_ALL(ChronologyType.ALL, CheckType.MUTUAL_READ);
 
+///
+/// Pairwise compatibility of every version in a history.
+/// `readable[i][j]` tells if version `i` can read the data written by version `j`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CompatMatrix {
+    pub readable: Vec<Vec<bool>>,
+}
+
+impl CompatMatrix {
+    /// Can the `reader` version read the data written by the `writer` version
+    pub fn can_read(&self, reader: usize, writer: usize) -> bool {
+        self.readable[reader][writer]
+    }
+
+    /// Number of versions in the history
+    pub fn len(&self) -> usize {
+        self.readable.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readable.is_empty()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DegaussCheck(pub DegaussCompatMode);
 
 impl DegaussCheck {
     ///
-    /// Validate given list of the schemas with the compat mode
+    /// Validate given list of the schemas with the compat mode.
+    ///
+    /// An empty list is never valid, neither is a single schema for the non-transitive modes which need a previous one.
+    ///
+    /// Avro schemas are checked by the same rules as [`DegaussCheck::explain`], not by apache-avro's `SchemaCompatibility` anymore.
+    /// Which changes the verdict for:
+    /// - references to named types and logical types, a schema using them can read its own data now,
+    /// - unions of the writer, every branch has to be readable instead of any of them.
+    ///
+    /// Aliases aren't supported by either of them.
     // [previous schemas..., old1, old2, old3, newest schema]
-    pub fn validate(&self, schemas: &[Schema]) -> bool {
-        let schemas: Vec<&Schema> = schemas.iter().rev().collect();
-        // [newest schema, old3, old2, old1, previous schemas]
+    pub fn validate<S: DegaussSchema>(&self, schemas: &[S]) -> bool {
+        self.comparable(schemas.len()) && self.explain(schemas).is_empty()
+    }
+
+    /// Whether a history of the given length has enough schemas for the compat mode
+    fn comparable(&self, len: usize) -> bool {
         match self.0 {
-            DegaussCompatMode::Backward => {
-                // Backward compatibility: A new schema is backward compatible if it can be used to read the data
-                // written in the previous schema.
-                SchemaCompatibility::can_read(schemas[1], schemas[0])
-            }
-            DegaussCompatMode::Forward => {
-                // Forward compatibility: A new schema is forward compatible if the previous schema can read data written in this
-                // schema.
-                SchemaCompatibility::can_read(schemas[0], schemas[1])
-            }
-            DegaussCompatMode::Full => {
-                // Both vice versa
-                // Full compatibility: A new schema is fully compatible if it’s both backward and forward compatible.
-                SchemaCompatibility::mutual_read(schemas[0], schemas[1])
-            }
-            DegaussCompatMode::BackwardTransitive => {
-                // Backward transitive compatibility: A new schema is backward compatible if it can be used to read the data
-                // written in all previous schemas.
-                let mut x = VecDeque::from(schemas);
-                // [newest schema, old3, old2, old1, previous schemas]
-                match x.pop_front() {
-                    Some(s) => x.iter().all(|e| SchemaCompatibility::can_read(e, s)),
-                    _ => false,
-                }
-            }
-            DegaussCompatMode::ForwardTransitive => {
-                // Forward transitive compatibility: A new schema is forward compatible if all previous schemas can read data written
-                // in this schema.
-                let mut x = VecDeque::from(schemas);
-                // [newest schema, old3, old2, old1, previous schemas]
-                match x.pop_front() {
-                    Some(s) => x.iter().all(|e| SchemaCompatibility::can_read(s, e)),
-                    _ => false,
-                }
-            }
-            DegaussCompatMode::FullTransitive => {
-                // Full transitive compatibility: A new schema is fully compatible if it’s both transitively backward
-                // and transitively forward compatible with the entire schema history.
-                // [newest schema, old3, old2, old1, previous schemas]
-                let mut x = VecDeque::from(schemas);
-                // [newest schema, old3, old2, old1, previous schemas]
-                match x.pop_front() {
-                    Some(s) => x.iter().all(|e| SchemaCompatibility::mutual_read(s, e)),
-                    _ => false,
-                }
+            DegaussCompatMode::Backward | DegaussCompatMode::Forward | DegaussCompatMode::Full => {
+                len >= 2
             }
+            _ => len >= 1,
         }
     }
 
-    pub fn tabular_validate(&self, schemas: &[Schema]) -> HashMap<DegaussCompatMode, bool> {
+    ///
+    /// Explain why the given list of the schemas doesn't satisfy the compat mode.
+    /// Every violation carries the indexes of the reader and writer schemas in the history,
+    /// so in transitive modes the offending historical schema can be found.
+    ///
+    /// Empty result means the schemas are compatible, as long as there are enough of them, see [`DegaussCheck::validate`].
+    pub fn explain<S: DegaussSchema>(&self, schemas: &[S]) -> Vec<Incompatibility> {
+        self.pairs(schemas.len())
+            .into_iter()
+            .flat_map(|(reader, writer)| {
+                S::explain_pair(reader, &schemas[reader], writer, &schemas[writer])
+            })
+            .collect()
+    }
+
+    ///
+    /// Explain every violation of the compat mode in the whole history, as if the schemas were registered one after the other.
+    /// Every schema is checked against the ones before it, so a violation stays visible after newer schemas are added.
+    pub fn explain_history<S: DegaussSchema>(&self, schemas: &[S]) -> Vec<Incompatibility> {
+        (2..=schemas.len())
+            .flat_map(|len| self.explain(&schemas[..len]))
+            .collect()
+    }
+
+    ///
+    /// Check the given list of the schemas with the compat mode and produce a report case out of it.
+    /// `sources` are the files the schemas are read from, in the same order, used to locate the findings.
+    /// It can be left empty when the schemas are not read from files.
+    pub fn report<S: DegaussSchema>(&self, schemas: &[S], sources: &[String]) -> ReportCase {
+        let mut findings = self
+            .explain(schemas)
+            .iter()
+            .map(|incompat| Finding::from_incompatibility(incompat, sources))
+            .collect::<Vec<Finding>>();
+        if !self.comparable(schemas.len()) {
+            findings.push(Finding {
+                rule: "nothing_to_compare".to_string(),
+                severity: Severity::Error,
+                message: format!("{} compatibility needs a previous schema", self.0),
+                file: sources.last().cloned(),
+                path: None,
+            });
+        }
+        ReportCase {
+            name: self.0.to_string(),
+            compatibility: Some(self.0),
+            passed: findings.is_empty(),
+            findings,
+        }
+    }
+
+    ///
+    /// (reader, writer) index pairs of the history which need to be checked for the compat mode.
+    // [old1, old2, old3, newest schema]
+    pub(crate) fn pairs(&self, len: usize) -> Vec<(usize, usize)> {
+        if len < 2 {
+            return vec![];
+        }
+        let newest = len - 1;
+        // [old3, old2, old1]
+        let previous = (0..newest).rev();
+        match self.0 {
+            // No compatibility: Nothing to check, any schema is accepted.
+            DegaussCompatMode::None => vec![],
+            // Backward compatibility: A new schema is backward compatible if it can be used to read the data
+            // written in the previous schema.
+            DegaussCompatMode::Backward => vec![(newest, newest - 1)],
+            // Forward compatibility: A new schema is forward compatible if the previous schema can read data written in this
+            // schema.
+            DegaussCompatMode::Forward => vec![(newest - 1, newest)],
+            // Full compatibility: A new schema is fully compatible if it’s both backward and forward compatible.
+            DegaussCompatMode::Full => vec![(newest, newest - 1), (newest - 1, newest)],
+            // Backward transitive compatibility: A new schema is backward compatible if it can be used to read the data
+            // written in all previous schemas.
+            DegaussCompatMode::BackwardTransitive => previous.map(|old| (newest, old)).collect(),
+            // Forward transitive compatibility: A new schema is forward compatible if all previous schemas can read data written
+            // in this schema.
+            DegaussCompatMode::ForwardTransitive => previous.map(|old| (old, newest)).collect(),
+            // Full transitive compatibility: A new schema is fully compatible if it’s both transitively backward
+            // and transitively forward compatible with the entire schema history.
+            DegaussCompatMode::FullTransitive => previous
+                .flat_map(|old| [(newest, old), (old, newest)])
+                .collect(),
+        }
+    }
+
+    pub fn tabular_validate<S: DegaussSchema>(
+        &self,
+        schemas: &[S],
+    ) -> HashMap<DegaussCompatMode, bool> {
         [(self.0, self.validate(schemas))].iter().cloned().collect()
     }
+
+    ///
+    /// Validate given list of the schemas with every compat mode
+    pub fn tabular_validate_all<S: DegaussSchema>(
+        schemas: &[S],
+    ) -> HashMap<DegaussCompatMode, bool> {
+        DegaussCompatMode::iter()
+            .map(|mode| (mode, DegaussCheck(mode).validate(schemas)))
+            .collect()
+    }
+
+    ///
+    /// Check every (reader, writer) pair of versions of the given list of the schemas
+    pub fn pairwise<S: DegaussSchema>(schemas: &[S]) -> CompatMatrix {
+        let readable = (0..schemas.len())
+            .map(|reader| {
+                (0..schemas.len())
+                    .map(|writer| {
+                        S::explain_pair(reader, &schemas[reader], writer, &schemas[writer])
+                            .is_empty()
+                    })
+                    .collect()
+            })
+            .collect();
+        CompatMatrix { readable }
+    }
+
+    ///
+    /// Strongest compat modes the given list of the schemas satisfies, see [`strongest_of`](Self::strongest_of).
+    pub fn strongest<S: DegaussSchema>(schemas: &[S]) -> Vec<DegaussCompatMode> {
+        Self::strongest_of(&Self::tabular_validate_all(schemas))
+    }
+
+    ///
+    /// Compat modes which hold in an already evaluated matrix and aren't implied by another one which holds, in order.
+    /// There are two of them when e.g. backward and forward transitive hold but full doesn't.
+    /// Falls back to [`DegaussCompatMode::None`] which always holds.
+    pub fn strongest_of(matrix: &HashMap<DegaussCompatMode, bool>) -> Vec<DegaussCompatMode> {
+        let valid = matrix
+            .iter()
+            .filter(|(_, valid)| **valid)
+            .map(|(mode, _)| *mode)
+            .collect::<Vec<_>>();
+        let mut strongest = valid
+            .iter()
+            .filter(|mode| {
+                !valid
+                    .iter()
+                    .any(|other| other != *mode && other.implies(**mode))
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        strongest.sort();
+        if strongest.is_empty() {
+            strongest.push(DegaussCompatMode::None);
+        }
+        strongest
+    }
 }
@@ -0,0 +1,140 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Verification of existing data against a reader schema.
+//!
+//! Avro object container files carry the schema they were written with in their header.
+//! Every record is decoded with that writer schema and resolved into the reader schema,
+//! which catches what a schema level check can't, e.g. union branches that never occur in the data.
+//!
+//! ```rust,no_run
+//! use apache_avro::Schema;
+//! use degauss::prelude::*;
+//!
+//! let reader = Schema::parse_file("new.avsc").unwrap();
+//! let verification = verify_data_file(&reader, "data/movies.avro").unwrap();
+//! println!("{} records, passed: {}", verification.records, verification.passed());
+//! ```
+//!
+
+use crate::errors::DegaussError;
+use crate::report::{Finding, ReportCase, Severity};
+use apache_avro::{Reader, Schema};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// The first record of a data file which can't be read with the reader schema
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RecordFailure {
+    /// Position of the record in the file, starting from 0.
+    pub index: usize,
+    pub error: String,
+}
+
+/// Outcome of reading a data file with a reader schema
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataVerification {
+    /// Schema the data was written with, taken from the file header.
+    pub writer_schema: Schema,
+    /// Number of records read successfully.
+    pub records: usize,
+    pub failure: Option<RecordFailure>,
+}
+
+impl DataVerification {
+    /// Whether every record of the file could be read
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Create a report case out of the verification of the file `source`
+    pub fn report(&self, source: &str) -> ReportCase {
+        let findings = self
+            .failure
+            .iter()
+            .map(|failure| Finding {
+                rule: "unreadable_record".to_string(),
+                severity: Severity::Error,
+                message: format!(
+                    "record {} of {} can't be read: {}",
+                    failure.index, source, failure.error
+                ),
+                file: Some(source.to_string()),
+                path: None,
+            })
+            .collect::<Vec<Finding>>();
+        ReportCase {
+            name: source.to_string(),
+            compatibility: None,
+            passed: findings.is_empty(),
+            findings,
+        }
+    }
+}
+
+/// Read every record of an object container with the reader schema, stops at the first failing record.
+/// Errors only if the header of the container can't be read.
+pub fn verify_data<R: Read>(
+    reader_schema: &Schema,
+    data: R,
+) -> Result<DataVerification, apache_avro::Error> {
+    let mut reader = Reader::with_schema(reader_schema, data)?;
+    let writer_schema = reader.writer_schema().clone();
+    let mut records = 0;
+    let mut failure = None;
+    for (index, record) in reader.by_ref().enumerate() {
+        match record {
+            Ok(_) => records += 1,
+            Err(error) => {
+                failure = Some(RecordFailure {
+                    index,
+                    error: error.to_string(),
+                });
+                break;
+            }
+        }
+    }
+    Ok(DataVerification {
+        writer_schema,
+        records,
+        failure,
+    })
+}
+
+/// Read every record of the object container file at `path` with the reader schema
+pub fn verify_data_file<P: AsRef<Path>>(
+    reader_schema: &Schema,
+    path: P,
+) -> Result<DataVerification, DegaussError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| DegaussError::FileRead {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    verify_data(reader_schema, BufReader::new(file)).map_err(|source| DegaussError::DataFile {
+        path: path.to_path_buf(),
+        source,
+    })
+}
@@ -0,0 +1,462 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Semantic diff between two schemas.
+//!
+//! Schemas are compared after parsing, so key ordering and whitespace of the
+//! schema files don't show up as changes. Record fields are matched by name,
+//! union branches by their type.
+//!
+//! ```rust,no_run
+//! use apache_avro::Schema;
+//! use degauss::prelude::*;
+//!
+//! let old = Schema::parse_file("old.avsc").unwrap();
+//! let new = Schema::parse_file("new.avsc").unwrap();
+//! for change in diff(&old, &new) {
+//!     println!("{}", change);
+//! }
+//! ```
+//!
+
+use crate::explain::{named_types, render_path, resolve, type_name};
+use apache_avro::schema::{Alias, Name, Namespace, RecordField};
+use apache_avro::Schema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+/// Kind of a change between two schemas
+#[derive(
+    EnumVariantNames,
+    EnumString,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaChangeKind {
+    #[strum(serialize = "field_added")]
+    FieldAdded,
+    #[strum(serialize = "field_removed")]
+    FieldRemoved,
+    #[strum(serialize = "type_changed")]
+    TypeChanged,
+    #[strum(serialize = "default_changed")]
+    DefaultChanged,
+    #[strum(serialize = "doc_changed")]
+    DocChanged,
+    #[strum(serialize = "aliases_changed")]
+    AliasesChanged,
+    #[strum(serialize = "name_changed")]
+    NameChanged,
+    #[strum(serialize = "namespace_changed")]
+    NamespaceChanged,
+    #[strum(serialize = "symbol_added")]
+    SymbolAdded,
+    #[strum(serialize = "symbol_removed")]
+    SymbolRemoved,
+    #[strum(serialize = "fixed_size_changed")]
+    FixedSizeChanged,
+    #[strum(serialize = "union_branch_added")]
+    UnionBranchAdded,
+    #[strum(serialize = "union_branch_removed")]
+    UnionBranchRemoved,
+}
+
+/// A single change between the old and the new schema
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SchemaChange {
+    /// Location of the change, fields are addressed by name, e.g. `/fields/f1/type`.
+    pub path: String,
+    /// Kind of the change.
+    pub kind: SchemaChangeKind,
+    /// Value in the old schema, if there was any.
+    pub old: Option<String>,
+    /// Value in the new schema, if there is any.
+    pub new: Option<String>,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} -> {}",
+            self.kind,
+            self.path,
+            self.old.as_deref().unwrap_or("-"),
+            self.new.as_deref().unwrap_or("-")
+        )
+    }
+}
+
+/// Compute the semantic changes needed to go from the `old` schema to the `new` one.
+pub fn diff(old: &Schema, new: &Schema) -> Vec<SchemaChange> {
+    let mut differ = Differ {
+        old_names: named_types(old),
+        new_names: named_types(new),
+        seen: HashSet::new(),
+        changes: vec![],
+    };
+    differ.diff(old, &None, new, &None, &mut vec![]);
+    differ.changes
+}
+
+fn render_aliases(aliases: &Option<Vec<Alias>>) -> Option<String> {
+    aliases.as_ref().map(|aliases| {
+        let mut names = aliases.iter().map(|a| a.name()).collect::<Vec<String>>();
+        names.sort();
+        names.join(", ")
+    })
+}
+
+/// Key used to pair union branches of the old and the new schema
+fn branch_key(schema: &Schema, namespace: &Namespace) -> String {
+    match schema {
+        Schema::Record { name, .. }
+        | Schema::Enum { name, .. }
+        | Schema::Fixed { name, .. }
+        | Schema::Ref { name } => name.fully_qualified_name(namespace).fullname(None),
+        Schema::Array(_) => "array".to_string(),
+        Schema::Map(_) => "map".to_string(),
+        Schema::Decimal { .. } => "decimal".to_string(),
+        other => type_name(other),
+    }
+}
+
+struct Differ<'s> {
+    old_names: HashMap<String, &'s Schema>,
+    new_names: HashMap<String, &'s Schema>,
+    // Named types already compared, avoids looping on recursive types.
+    seen: HashSet<String>,
+    changes: Vec<SchemaChange>,
+}
+
+impl<'s> Differ<'s> {
+    fn push(
+        &mut self,
+        path: &[String],
+        kind: SchemaChangeKind,
+        old: Option<String>,
+        new: Option<String>,
+    ) {
+        self.changes.push(SchemaChange {
+            path: render_path(path),
+            kind,
+            old,
+            new,
+        });
+    }
+
+    fn diff_name(
+        &mut self,
+        path: &[String],
+        old: (&Name, &Namespace),
+        new: (&Name, &Namespace),
+    ) -> bool {
+        let old_fq = old.0.fully_qualified_name(old.1);
+        let new_fq = new.0.fully_qualified_name(new.1);
+        if old_fq.name != new_fq.name {
+            self.push(
+                path,
+                SchemaChangeKind::NameChanged,
+                Some(old_fq.name.clone()),
+                Some(new_fq.name.clone()),
+            );
+        }
+        if old_fq.namespace != new_fq.namespace {
+            self.push(
+                path,
+                SchemaChangeKind::NamespaceChanged,
+                old_fq.namespace.clone(),
+                new_fq.namespace.clone(),
+            );
+        }
+        // Both sides are compared only once, recursive references stop here.
+        self.seen.insert(format!(
+            "{}|{}",
+            old_fq.fullname(None),
+            new_fq.fullname(None)
+        ))
+    }
+
+    fn diff_meta(
+        &mut self,
+        path: &[String],
+        old: (&Option<String>, &Option<Vec<Alias>>),
+        new: (&Option<String>, &Option<Vec<Alias>>),
+    ) {
+        if old.0 != new.0 {
+            self.push(
+                path,
+                SchemaChangeKind::DocChanged,
+                old.0.clone(),
+                new.0.clone(),
+            );
+        }
+        let (old_aliases, new_aliases) = (render_aliases(old.1), render_aliases(new.1));
+        if old_aliases != new_aliases {
+            self.push(
+                path,
+                SchemaChangeKind::AliasesChanged,
+                old_aliases,
+                new_aliases,
+            );
+        }
+    }
+
+    fn diff_fields(
+        &mut self,
+        old_fields: &'s [RecordField],
+        old_ns: &Namespace,
+        new_fields: &'s [RecordField],
+        new_ns: &Namespace,
+        path: &mut Vec<String>,
+    ) {
+        let mut matched = HashSet::new();
+        for new_field in new_fields {
+            let old_field = old_fields.iter().find(|f| f.name == new_field.name);
+
+            path.push("fields".to_string());
+            path.push(new_field.name.clone());
+            match old_field {
+                None => self.push(
+                    path,
+                    SchemaChangeKind::FieldAdded,
+                    None,
+                    Some(type_name(&new_field.schema)),
+                ),
+                Some(old_field) => {
+                    matched.insert(old_field.name.clone());
+                    if old_field.doc != new_field.doc {
+                        self.push(
+                            path,
+                            SchemaChangeKind::DocChanged,
+                            old_field.doc.clone(),
+                            new_field.doc.clone(),
+                        );
+                    }
+                    if old_field.default != new_field.default {
+                        self.push(
+                            path,
+                            SchemaChangeKind::DefaultChanged,
+                            old_field.default.as_ref().map(|d| d.to_string()),
+                            new_field.default.as_ref().map(|d| d.to_string()),
+                        );
+                    }
+                    path.push("type".to_string());
+                    self.diff(&old_field.schema, old_ns, &new_field.schema, new_ns, path);
+                    path.pop();
+                }
+            }
+            path.pop();
+            path.pop();
+        }
+
+        for old_field in old_fields.iter().filter(|f| !matched.contains(&f.name)) {
+            path.push("fields".to_string());
+            path.push(old_field.name.clone());
+            self.push(
+                path,
+                SchemaChangeKind::FieldRemoved,
+                Some(type_name(&old_field.schema)),
+                None,
+            );
+            path.pop();
+            path.pop();
+        }
+    }
+
+    fn diff(
+        &mut self,
+        old: &'s Schema,
+        old_ns: &Namespace,
+        new: &'s Schema,
+        new_ns: &Namespace,
+        path: &mut Vec<String>,
+    ) {
+        let (old, old_ns) = resolve(&self.old_names, old, old_ns);
+        let (new, new_ns) = resolve(&self.new_names, new, new_ns);
+
+        match (old, new) {
+            (Schema::Union(old_union), Schema::Union(new_union)) => {
+                let old_keys = old_union
+                    .variants()
+                    .iter()
+                    .map(|v| branch_key(v, &old_ns))
+                    .collect::<Vec<String>>();
+                let new_keys = new_union
+                    .variants()
+                    .iter()
+                    .map(|v| branch_key(v, &new_ns))
+                    .collect::<Vec<String>>();
+                for (new_idx, new_variant) in new_union.variants().iter().enumerate() {
+                    path.push(new_idx.to_string());
+                    match old_keys.iter().position(|k| k == &new_keys[new_idx]) {
+                        Some(old_idx) => self.diff(
+                            &old_union.variants()[old_idx],
+                            &old_ns,
+                            new_variant,
+                            &new_ns,
+                            path,
+                        ),
+                        None => self.push(
+                            path,
+                            SchemaChangeKind::UnionBranchAdded,
+                            None,
+                            Some(type_name(new_variant)),
+                        ),
+                    }
+                    path.pop();
+                }
+                for (old_idx, old_variant) in old_union.variants().iter().enumerate() {
+                    if !new_keys.contains(&old_keys[old_idx]) {
+                        path.push(old_idx.to_string());
+                        self.push(
+                            path,
+                            SchemaChangeKind::UnionBranchRemoved,
+                            Some(type_name(old_variant)),
+                            None,
+                        );
+                        path.pop();
+                    }
+                }
+            }
+            (
+                Schema::Record {
+                    name: old_name,
+                    aliases: old_aliases,
+                    doc: old_doc,
+                    fields: old_fields,
+                    ..
+                },
+                Schema::Record {
+                    name: new_name,
+                    aliases: new_aliases,
+                    doc: new_doc,
+                    fields: new_fields,
+                    ..
+                },
+            ) => {
+                if !self.diff_name(path, (old_name, &old_ns), (new_name, &new_ns)) {
+                    return;
+                }
+                self.diff_meta(path, (old_doc, old_aliases), (new_doc, new_aliases));
+                self.diff_fields(old_fields, &old_ns, new_fields, &new_ns, path);
+            }
+            (
+                Schema::Enum {
+                    name: old_name,
+                    aliases: old_aliases,
+                    doc: old_doc,
+                    symbols: old_symbols,
+                },
+                Schema::Enum {
+                    name: new_name,
+                    aliases: new_aliases,
+                    doc: new_doc,
+                    symbols: new_symbols,
+                },
+            ) => {
+                if !self.diff_name(path, (old_name, &old_ns), (new_name, &new_ns)) {
+                    return;
+                }
+                self.diff_meta(path, (old_doc, old_aliases), (new_doc, new_aliases));
+                path.push("symbols".to_string());
+                for symbol in new_symbols.iter().filter(|s| !old_symbols.contains(s)) {
+                    self.push(
+                        path,
+                        SchemaChangeKind::SymbolAdded,
+                        None,
+                        Some(symbol.clone()),
+                    );
+                }
+                for symbol in old_symbols.iter().filter(|s| !new_symbols.contains(s)) {
+                    self.push(
+                        path,
+                        SchemaChangeKind::SymbolRemoved,
+                        Some(symbol.clone()),
+                        None,
+                    );
+                }
+                path.pop();
+            }
+            (
+                Schema::Fixed {
+                    name: old_name,
+                    aliases: old_aliases,
+                    doc: old_doc,
+                    size: old_size,
+                },
+                Schema::Fixed {
+                    name: new_name,
+                    aliases: new_aliases,
+                    doc: new_doc,
+                    size: new_size,
+                },
+            ) => {
+                if !self.diff_name(path, (old_name, &old_ns), (new_name, &new_ns)) {
+                    return;
+                }
+                self.diff_meta(path, (old_doc, old_aliases), (new_doc, new_aliases));
+                if old_size != new_size {
+                    self.push(
+                        path,
+                        SchemaChangeKind::FixedSizeChanged,
+                        Some(old_size.to_string()),
+                        Some(new_size.to_string()),
+                    );
+                }
+            }
+            (Schema::Array(old_items), Schema::Array(new_items)) => {
+                path.push("items".to_string());
+                self.diff(old_items, &old_ns, new_items, &new_ns, path);
+                path.pop();
+            }
+            (Schema::Map(old_values), Schema::Map(new_values)) => {
+                path.push("values".to_string());
+                self.diff(old_values, &old_ns, new_values, &new_ns, path);
+                path.pop();
+            }
+            (old, new) => {
+                let (old_type, new_type) = (type_name(old), type_name(new));
+                if old_type != new_type {
+                    self.push(
+                        path,
+                        SchemaChangeKind::TypeChanged,
+                        Some(old_type),
+                        Some(new_type),
+                    );
+                }
+            }
+        }
+    }
+}
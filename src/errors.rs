@@ -23,6 +23,7 @@
 //! Errors to be used with the library, converts to and from
 //! other dependencies' errors.
 
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[allow(dead_code)]
@@ -34,6 +35,70 @@ pub enum DegaussError {
     #[error("Schema parsing error")]
     Schema(#[from] apache_avro::Error),
 
+    #[error("Failed to read file {path}: {source}")]
+    FileRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write file {path}: {source}")]
+    FileWrite {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse schema file {path}: {source}")]
+    SchemaFile {
+        path: PathBuf,
+        source: apache_avro::Error,
+    },
+
+    #[error("Failed to parse IDL file {path}: {source}")]
+    IdlFile {
+        path: PathBuf,
+        source: crate::idl::IdlError,
+    },
+
+    #[error("Failed to parse Protobuf file {path}: {source}")]
+    ProtoFile {
+        path: PathBuf,
+        source: crate::protobuf::ProtoError,
+    },
+
+    #[error("Failed to parse JSON Schema file {path}: {source}")]
+    JsonSchemaFile {
+        path: PathBuf,
+        source: crate::json_schema::JsonSchemaError,
+    },
+
+    #[error("Failed to select a type from protocol {path}: {message}")]
+    ProtocolType { path: PathBuf, message: String },
+
+    #[error("Failed to parse config file {path}: {source}")]
+    ConfigFile {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("git {command} failed: {message}")]
+    Git { command: String, message: String },
+
+    #[error("Named type {name} is defined in both {first} and {second}")]
+    DuplicateType {
+        name: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+
+    #[error("Failed to read data file {path}: {source}")]
+    DataFile {
+        path: PathBuf,
+        source: apache_avro::Error,
+    },
+
+    #[error("{0}")]
+    Usage(String),
+
     #[error("Serializing/Deserializing error")]
     Serde(#[from] serde_json::Error),
 
@@ -43,6 +108,9 @@ pub enum DegaussError {
     #[error("HTTP Protocol error")]
     Http(#[from] isahc::http::Error),
 
+    #[error("Failed to parse schema registry response: {0}")]
+    SrResponse(serde_json::Error),
+
     #[error("Status Code `{error_code}` Message: {message}")]
     SrHttp { error_code: i32, message: String },
 
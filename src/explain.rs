@@ -0,0 +1,509 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Structured explanations of why two schemas are not compatible.
+//!
+//! The walker here follows the same resolution rules as the Avro specification
+//! but, instead of stopping at the first problem, it collects every violation
+//! together with the location in the reader schema where it happened.
+//!
+//! ```rust,no_run
+//! use apache_avro::Schema;
+//! use degauss::prelude::*;
+//!
+//! let schemas = vec![
+//!     Schema::parse_file("old.avsc").unwrap(),
+//!     Schema::parse_file("new.avsc").unwrap(),
+//! ];
+//! for incompat in DegaussCheck(DegaussCompatMode::Backward).explain(&schemas) {
+//!     println!("{}", incompat);
+//! }
+//! ```
+//!
+
+use crate::format::Incompatibility;
+use apache_avro::schema::{Name, Namespace};
+use apache_avro::Schema;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+/// Reason why a reader Avro schema can't read the data of a writer schema
+#[derive(
+    EnumVariantNames,
+    EnumString,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AvroIncompatibilityKind {
+    /// Types can't be resolved into each other, not even by promotion.
+    #[strum(serialize = "type_mismatch")]
+    TypeMismatch,
+
+    /// Named types (record, enum, fixed) have different full names.
+    #[strum(serialize = "name_mismatch")]
+    NameMismatch,
+
+    /// Reader has a field which the writer doesn't have, and it has no default.
+    #[strum(serialize = "missing_default")]
+    MissingDefault,
+
+    /// Writer has an enum symbol which the reader doesn't know about.
+    #[strum(serialize = "enum_symbol_removed")]
+    EnumSymbolRemoved,
+
+    /// Writer type is not readable by any branch of the reader union.
+    #[strum(serialize = "missing_union_branch")]
+    MissingUnionBranch,
+
+    /// Fixed types have different sizes.
+    #[strum(serialize = "fixed_size_mismatch")]
+    FixedSizeMismatch,
+}
+
+/// Violation found by the walker, before it is attributed to a version pair.
+#[derive(Clone, Debug)]
+struct Violation {
+    path: String,
+    reader_type: String,
+    writer_type: String,
+    kind: AvroIncompatibilityKind,
+    message: String,
+}
+
+/// Explain why the `reader` schema can't read data written with the `writer` schema.
+///
+/// Returns an empty list when the reader can read everything the writer produces.
+pub fn explain_pair(
+    reader_version: usize,
+    reader: &Schema,
+    writer_version: usize,
+    writer: &Schema,
+) -> Vec<Incompatibility> {
+    let mut checker = Checker {
+        writer_names: named_types(writer),
+        reader_names: named_types(reader),
+        seen: HashSet::new(),
+    };
+    let mut violations = vec![];
+    checker.check(writer, &None, reader, &None, &mut vec![], &mut violations);
+    violations
+        .into_iter()
+        .map(|v| Incompatibility {
+            reader_version,
+            writer_version,
+            path: v.path,
+            reader_type: v.reader_type,
+            writer_type: v.writer_type,
+            kind: v.kind.into(),
+            message: v.message,
+        })
+        .collect()
+}
+
+/// Short human readable name of the given schema type
+pub fn type_name(schema: &Schema) -> String {
+    match schema {
+        Schema::Null => "null".to_string(),
+        Schema::Boolean => "boolean".to_string(),
+        Schema::Int => "int".to_string(),
+        Schema::Long => "long".to_string(),
+        Schema::Float => "float".to_string(),
+        Schema::Double => "double".to_string(),
+        Schema::Bytes => "bytes".to_string(),
+        Schema::String => "string".to_string(),
+        Schema::Array(inner) => format!("array<{}>", type_name(inner)),
+        Schema::Map(inner) => format!("map<{}>", type_name(inner)),
+        Schema::Union(union) => format!(
+            "union[{}]",
+            union
+                .variants()
+                .iter()
+                .map(type_name)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        Schema::Record { name, .. } => format!("record {}", name),
+        Schema::Enum { name, .. } => format!("enum {}", name),
+        Schema::Fixed { name, size, .. } => format!("fixed {}({})", name, size),
+        Schema::Decimal {
+            precision, scale, ..
+        } => format!("decimal({}, {})", precision, scale),
+        Schema::Uuid => "uuid".to_string(),
+        Schema::Date => "date".to_string(),
+        Schema::TimeMillis => "time-millis".to_string(),
+        Schema::TimeMicros => "time-micros".to_string(),
+        Schema::TimestampMillis => "timestamp-millis".to_string(),
+        Schema::TimestampMicros => "timestamp-micros".to_string(),
+        Schema::Duration => "duration".to_string(),
+        Schema::Ref { name } => name.to_string(),
+    }
+}
+
+/// Collect every named type of the schema by its fully qualified name
+pub(crate) fn named_types(schema: &Schema) -> HashMap<String, &Schema> {
+    fn collect<'s>(
+        schema: &'s Schema,
+        namespace: &Namespace,
+        names: &mut HashMap<String, &'s Schema>,
+    ) {
+        match schema {
+            Schema::Record { name, fields, .. } => {
+                let fq = name.fully_qualified_name(namespace);
+                names.insert(fq.fullname(None), schema);
+                for field in fields {
+                    collect(&field.schema, &fq.namespace, names);
+                }
+            }
+            Schema::Enum { name, .. } | Schema::Fixed { name, .. } => {
+                names.insert(name.fully_qualified_name(namespace).fullname(None), schema);
+            }
+            Schema::Array(inner) | Schema::Map(inner) => collect(inner, namespace, names),
+            Schema::Union(union) => {
+                for variant in union.variants() {
+                    collect(variant, namespace, names);
+                }
+            }
+            Schema::Decimal { inner, .. } => collect(inner, namespace, names),
+            _ => (),
+        }
+    }
+
+    let mut names = HashMap::new();
+    collect(schema, &None, &mut names);
+    names
+}
+
+/// Replace a reference with the named schema it points to, and give back the
+/// namespace children of the schema are resolved in.
+pub(crate) fn resolve<'s>(
+    names: &HashMap<String, &'s Schema>,
+    schema: &'s Schema,
+    namespace: &Namespace,
+) -> (&'s Schema, Namespace) {
+    let schema = match schema {
+        Schema::Ref { name } => names
+            .get(&name.fully_qualified_name(namespace).fullname(None))
+            .copied()
+            .unwrap_or(schema),
+        _ => schema,
+    };
+    let namespace = match schema {
+        Schema::Record { name, .. } | Schema::Enum { name, .. } | Schema::Fixed { name, .. } => {
+            name.fully_qualified_name(namespace).namespace
+        }
+        _ => namespace.clone(),
+    };
+    (schema, namespace)
+}
+
+pub(crate) fn render_path(path: &[String]) -> String {
+    format!("/{}", path.join("/"))
+}
+
+fn violation(
+    path: &[String],
+    writer: &Schema,
+    reader: &Schema,
+    kind: AvroIncompatibilityKind,
+    message: String,
+) -> Violation {
+    Violation {
+        path: render_path(path),
+        reader_type: type_name(reader),
+        writer_type: type_name(writer),
+        kind,
+        message,
+    }
+}
+
+fn is_promotable(writer: &Schema, reader: &Schema) -> bool {
+    matches!(
+        (writer, reader),
+        (Schema::Int, Schema::Long | Schema::Float | Schema::Double)
+            | (Schema::Long, Schema::Float | Schema::Double)
+            | (Schema::Float, Schema::Double)
+            | (Schema::String, Schema::Bytes)
+            | (Schema::Bytes, Schema::String)
+    )
+}
+
+struct Checker<'s> {
+    writer_names: HashMap<String, &'s Schema>,
+    reader_names: HashMap<String, &'s Schema>,
+    // Pairs of named types currently checked, avoids looping on recursive types.
+    seen: HashSet<(String, String)>,
+}
+
+impl<'s> Checker<'s> {
+    fn full_name(name: &Name, namespace: &Namespace) -> String {
+        // `namespace` is already the one of the named type itself after `resolve`.
+        name.fully_qualified_name(namespace).fullname(None)
+    }
+
+    fn check(
+        &mut self,
+        writer: &'s Schema,
+        w_ns: &Namespace,
+        reader: &'s Schema,
+        r_ns: &Namespace,
+        path: &mut Vec<String>,
+        out: &mut Vec<Violation>,
+    ) {
+        let (writer, w_ns) = resolve(&self.writer_names, writer, w_ns);
+        let (reader, r_ns) = resolve(&self.reader_names, reader, r_ns);
+
+        match (writer, reader) {
+            // Every branch the writer might have used must be readable.
+            (Schema::Union(w_union), _) => {
+                for variant in w_union.variants() {
+                    self.check(variant, &w_ns, reader, &r_ns, path, out);
+                }
+            }
+            // At least one branch of the reader must be able to read the writer.
+            (_, Schema::Union(r_union)) => {
+                let readable = r_union.variants().iter().any(|variant| {
+                    let seen = self.seen.clone();
+                    let mut scratch = vec![];
+                    self.check(writer, &w_ns, variant, &r_ns, path, &mut scratch);
+                    if !scratch.is_empty() {
+                        self.seen = seen;
+                    }
+                    scratch.is_empty()
+                });
+                if !readable {
+                    out.push(violation(
+                        path,
+                        writer,
+                        reader,
+                        AvroIncompatibilityKind::MissingUnionBranch,
+                        format!(
+                            "reader union has no branch to read writer type {}",
+                            type_name(writer)
+                        ),
+                    ));
+                }
+            }
+            (
+                Schema::Record {
+                    name: w_name,
+                    fields: w_fields,
+                    lookup: w_lookup,
+                    ..
+                },
+                Schema::Record {
+                    name: r_name,
+                    fields: r_fields,
+                    ..
+                },
+            ) => {
+                let w_full = Self::full_name(w_name, &w_ns);
+                let r_full = Self::full_name(r_name, &r_ns);
+                if w_full != r_full {
+                    out.push(violation(
+                        path,
+                        writer,
+                        reader,
+                        AvroIncompatibilityKind::NameMismatch,
+                        format!("record name {} doesn't match {}", r_full, w_full),
+                    ));
+                    return;
+                }
+                if !self.seen.insert((w_full, r_full)) {
+                    return;
+                }
+                for (idx, field) in r_fields.iter().enumerate() {
+                    path.push("fields".to_string());
+                    path.push(idx.to_string());
+                    match w_lookup.get(&field.name) {
+                        Some(pos) => {
+                            path.push("type".to_string());
+                            self.check(
+                                &w_fields[*pos].schema,
+                                &w_ns,
+                                &field.schema,
+                                &r_ns,
+                                path,
+                                out,
+                            );
+                            path.pop();
+                        }
+                        None if field.default.is_none() => out.push(Violation {
+                            path: render_path(path),
+                            reader_type: type_name(&field.schema),
+                            writer_type: "absent".to_string(),
+                            kind: AvroIncompatibilityKind::MissingDefault,
+                            message: format!(
+                                "reader field '{}' is missing in the writer and has no default",
+                                field.name
+                            ),
+                        }),
+                        None => (),
+                    }
+                    path.pop();
+                    path.pop();
+                }
+            }
+            (
+                Schema::Enum {
+                    name: w_name,
+                    symbols: w_symbols,
+                    ..
+                },
+                Schema::Enum {
+                    name: r_name,
+                    symbols: r_symbols,
+                    ..
+                },
+            ) => {
+                let w_full = Self::full_name(w_name, &w_ns);
+                let r_full = Self::full_name(r_name, &r_ns);
+                if w_full != r_full {
+                    out.push(violation(
+                        path,
+                        writer,
+                        reader,
+                        AvroIncompatibilityKind::NameMismatch,
+                        format!("enum name {} doesn't match {}", r_full, w_full),
+                    ));
+                    return;
+                }
+                path.push("symbols".to_string());
+                for symbol in w_symbols.iter().filter(|s| !r_symbols.contains(s)) {
+                    out.push(Violation {
+                        path: render_path(path),
+                        reader_type: type_name(reader),
+                        writer_type: type_name(writer),
+                        kind: AvroIncompatibilityKind::EnumSymbolRemoved,
+                        message: format!("writer symbol '{}' is unknown to the reader", symbol),
+                    });
+                }
+                path.pop();
+            }
+            (
+                Schema::Fixed {
+                    name: w_name,
+                    size: w_size,
+                    ..
+                },
+                Schema::Fixed {
+                    name: r_name,
+                    size: r_size,
+                    ..
+                },
+            ) => {
+                let w_full = Self::full_name(w_name, &w_ns);
+                let r_full = Self::full_name(r_name, &r_ns);
+                if w_full != r_full {
+                    out.push(violation(
+                        path,
+                        writer,
+                        reader,
+                        AvroIncompatibilityKind::NameMismatch,
+                        format!("fixed name {} doesn't match {}", r_full, w_full),
+                    ));
+                } else if w_size != r_size {
+                    out.push(violation(
+                        path,
+                        writer,
+                        reader,
+                        AvroIncompatibilityKind::FixedSizeMismatch,
+                        format!("fixed size {} doesn't match {}", r_size, w_size),
+                    ));
+                }
+            }
+            (Schema::Array(w_items), Schema::Array(r_items)) => {
+                path.push("items".to_string());
+                self.check(w_items, &w_ns, r_items, &r_ns, path, out);
+                path.pop();
+            }
+            (Schema::Map(w_values), Schema::Map(r_values)) => {
+                path.push("values".to_string());
+                self.check(w_values, &w_ns, r_values, &r_ns, path, out);
+                path.pop();
+            }
+            (
+                Schema::Decimal {
+                    precision: w_precision,
+                    scale: w_scale,
+                    ..
+                },
+                Schema::Decimal {
+                    precision: r_precision,
+                    scale: r_scale,
+                    ..
+                },
+            ) => {
+                if w_precision != r_precision || w_scale != r_scale {
+                    out.push(violation(
+                        path,
+                        writer,
+                        reader,
+                        AvroIncompatibilityKind::TypeMismatch,
+                        format!(
+                            "reader type {} can't read writer type {}",
+                            type_name(reader),
+                            type_name(writer)
+                        ),
+                    ));
+                }
+            }
+            // Only left unresolved when the named type is defined outside of the schema.
+            (Schema::Ref { name: w_name }, Schema::Ref { name: r_name }) => {
+                let w_full = Self::full_name(w_name, &w_ns);
+                let r_full = Self::full_name(r_name, &r_ns);
+                if w_full != r_full {
+                    out.push(violation(
+                        path,
+                        writer,
+                        reader,
+                        AvroIncompatibilityKind::NameMismatch,
+                        format!("type name {} doesn't match {}", r_full, w_full),
+                    ));
+                }
+            }
+            (w, r) if std::mem::discriminant(w) == std::mem::discriminant(r) => {}
+            (w, r) if is_promotable(w, r) => {}
+            _ => out.push(violation(
+                path,
+                writer,
+                reader,
+                AvroIncompatibilityKind::TypeMismatch,
+                format!(
+                    "reader type {} can't read writer type {}",
+                    type_name(reader),
+                    type_name(writer)
+                ),
+            )),
+        }
+    }
+}
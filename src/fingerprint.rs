@@ -0,0 +1,132 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Fingerprints of the Parsing Canonical Form of schemas.
+//!
+//! Schemas which only differ in formatting, docs or defaults have the same fingerprints.
+//! The CRC-64-AVRO (Rabin) fingerprint is the one single object encoding puts in its header.
+//!
+//! Avro schemas are fingerprinted without their logical types, as the specification strips
+//! `logicalType`, `precision` and `scale`, so the fingerprints match the ones of the Java implementation.
+//!
+//! ```rust,no_run
+//! use apache_avro::Schema;
+//! use degauss::prelude::*;
+//!
+//! let schema = Schema::parse_file("movies.avsc").unwrap();
+//! let fingerprints = Fingerprints::new(&schema);
+//! println!("{} {}", fingerprints.rabin, fingerprints.canonical_form);
+//! ```
+//!
+
+use crate::errors::DegaussError;
+use crate::format::DegaussSchema;
+use apache_avro::rabin::Rabin;
+use apache_avro::schema::SchemaFingerprint;
+use apache_avro::Schema;
+use md5::Md5;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Magic bytes which start a single object encoding header
+const SINGLE_OBJECT_MAGIC: &str = "c301";
+
+/// Attributes of the Parsing Canonical Form, in their order
+const CANONICAL_ATTRIBUTES: &[&str] = &[
+    "name", "type", "fields", "symbols", "items", "values", "size",
+];
+
+/// Parsing Canonical Form of a schema with its fingerprints, in lowercase hex
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Fingerprints {
+    pub canonical_form: String,
+    /// CRC-64-AVRO, in the little endian byte order of single object encoding headers.
+    pub rabin: String,
+    pub md5: String,
+    pub sha256: String,
+}
+
+impl Fingerprints {
+    /// Compute the fingerprints of a schema
+    pub fn new<S: DegaussSchema + ?Sized>(schema: &S) -> Self {
+        schema.fingerprints()
+    }
+
+    /// Compute the fingerprints of the canonical form of a schema
+    pub fn of_canonical_form(canonical_form: String) -> Self {
+        Fingerprints {
+            rabin: digest::<Rabin>(&canonical_form),
+            md5: digest::<Md5>(&canonical_form),
+            sha256: digest::<Sha256>(&canonical_form),
+            canonical_form,
+        }
+    }
+
+    /// Header of single object encoded data written with the schema
+    pub fn single_object_header(&self) -> String {
+        format!("{}{}", SINGLE_OBJECT_MAGIC, self.rabin)
+    }
+}
+
+/// Parsing Canonical Form of an Avro schema as the specification defines it.
+/// Unlike [`Schema::canonical_form`] it strips the logical types, which apache-avro keeps.
+pub fn parsing_canonical_form(schema: &Schema) -> Result<String, DegaussError> {
+    // apache-avro already applies the other rules, the attributes left are in the canonical order.
+    let json = serde_json::from_str(&schema.canonical_form())?;
+    Ok(strip(&json))
+}
+
+fn strip(json: &serde_json::Value) -> String {
+    match json {
+        serde_json::Value::Object(attributes) => {
+            let attributes = CANONICAL_ATTRIBUTES
+                .iter()
+                .filter_map(|key| attributes.get(*key).map(|value| (*key, value)))
+                .collect::<Vec<_>>();
+            match attributes.as_slice() {
+                // A logical type without its attributes is its underlying type ([PRIMITIVES] rule).
+                [("type", inner)] => strip(inner),
+                _ => format!(
+                    "{{{}}}",
+                    attributes
+                        .iter()
+                        .map(|(key, value)| format!("\"{}\":{}", key, strip(value)))
+                        .collect::<Vec<String>>()
+                        .join(",")
+                ),
+            }
+        }
+        serde_json::Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(strip).collect::<Vec<String>>().join(",")
+        ),
+        other => other.to_string(),
+    }
+}
+
+fn digest<D: Digest>(canonical_form: &str) -> String {
+    SchemaFingerprint {
+        bytes: D::digest(canonical_form.as_bytes()).to_vec(),
+    }
+    .to_string()
+}
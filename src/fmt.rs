@@ -0,0 +1,315 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Formatting of schema files into a stable layout.
+//!
+//! Attributes are written in a fixed order, `type`, `name`, `namespace` and `doc` first,
+//! arrays of plain values like unions and symbols stay on one line. Names of named types
+//! can be written fully qualified or relative to their enclosing namespace.
+//! Docs, defaults and custom attributes are kept, so formatting never changes what a schema means.
+//!
+//! ```rust
+//! use degauss::prelude::*;
+//!
+//! let formatted = format_schema(
+//!     r#"{"fields": [{"type": "string", "name": "f1"}], "name": "myrecord", "type": "record"}"#,
+//!     &FormatConfig::default(),
+//! )
+//! .unwrap();
+//! assert!(formatted.starts_with("{\n    \"type\": \"record\",\n    \"name\": \"myrecord\""));
+//! ```
+//!
+
+use crate::schema::{fullname, namespace_of, PRIMITIVES};
+use serde_json::{Map, Value};
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+/// Order of the attributes of types, the others follow in alphabetical order
+const TYPE_ATTRIBUTES: [&str; 14] = [
+    "type",
+    "name",
+    "namespace",
+    "doc",
+    "aliases",
+    "fields",
+    "symbols",
+    "default",
+    "size",
+    "items",
+    "values",
+    "logicalType",
+    "precision",
+    "scale",
+];
+
+/// Order of the attributes of record fields, the others follow in alphabetical order
+const FIELD_ATTRIBUTES: [&str; 6] = ["name", "type", "doc", "default", "order", "aliases"];
+
+/// Values of `type` which aren't references to named types
+const TYPE_KEYWORDS: [&str; 6] = ["record", "error", "enum", "fixed", "array", "map"];
+
+/// How to write the names of named types
+#[derive(EnumVariantNames, EnumString, Display, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum NameStyle {
+    /// Fully qualified names, without namespace attributes
+    #[strum(serialize = "full")]
+    Full,
+    /// Names relative to the enclosing namespace, namespace attributes only where it changes
+    #[strum(serialize = "minimal")]
+    Minimal,
+}
+
+/// Settings of the formatting
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatConfig {
+    /// Names are kept as they are written when not given.
+    pub names: Option<NameStyle>,
+    /// Number of spaces to indent nested values with.
+    pub indent: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig {
+            names: None,
+            indent: 4,
+        }
+    }
+}
+
+/// Format the JSON text of a schema, ends with a newline
+pub fn format_schema(input: &str, config: &FormatConfig) -> Result<String, serde_json::Error> {
+    let schema: Value = serde_json::from_str(input)?;
+    let formatter = Formatter {
+        names: config.names,
+    };
+    let mut out = String::new();
+    write(
+        &formatter.schema(&schema, None),
+        &" ".repeat(config.indent),
+        0,
+        &mut out,
+    );
+    out.push('\n');
+    Ok(out)
+}
+
+/// JSON value with the order of the object attributes decided
+enum Node {
+    Plain(Value),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+struct Formatter {
+    names: Option<NameStyle>,
+}
+
+impl Formatter {
+    fn schema(&self, schema: &Value, namespace: Option<&str>) -> Node {
+        match schema {
+            Value::String(name) => Node::Plain(Value::String(self.reference(name, namespace))),
+            Value::Array(union) => Node::Array(
+                union
+                    .iter()
+                    .map(|branch| self.schema(branch, namespace))
+                    .collect(),
+            ),
+            Value::Object(object) => {
+                let mut object = object.clone();
+                let kind = object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                let mut own = namespace.map(str::to_string);
+                if let (Some("record" | "error" | "enum" | "fixed"), Some(name)) =
+                    (kind.as_deref(), object.get("name").and_then(Value::as_str))
+                {
+                    own = namespace_of(&object, namespace);
+                    let full = fullname(name, own.as_deref());
+                    self.rename(&mut object, &full, own.as_deref(), namespace);
+                }
+                let attributes = ordered(&object, &TYPE_ATTRIBUTES)
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let node = match key.as_str() {
+                            "type" => match value.as_str() {
+                                Some(kind)
+                                    if TYPE_KEYWORDS.contains(&kind)
+                                        || PRIMITIVES.contains(&kind) =>
+                                {
+                                    Node::Plain(value.clone())
+                                }
+                                _ => self.schema(value, namespace),
+                            },
+                            "fields" => match value.as_array() {
+                                Some(fields) => Node::Array(
+                                    fields
+                                        .iter()
+                                        .map(|field| self.field(field, own.as_deref()))
+                                        .collect(),
+                                ),
+                                None => plain(value),
+                            },
+                            "items" | "values" => self.schema(value, namespace),
+                            _ => plain(value),
+                        };
+                        (key.clone(), node)
+                    })
+                    .collect();
+                Node::Object(attributes)
+            }
+            _ => plain(schema),
+        }
+    }
+
+    fn field(&self, field: &Value, namespace: Option<&str>) -> Node {
+        match field.as_object() {
+            Some(object) => Node::Object(
+                ordered(object, &FIELD_ATTRIBUTES)
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let node = match key.as_str() {
+                            "type" => self.schema(value, namespace),
+                            _ => plain(value),
+                        };
+                        (key.clone(), node)
+                    })
+                    .collect(),
+            ),
+            None => plain(field),
+        }
+    }
+
+    /// Write the name of a named type definition in the chosen style
+    fn rename(
+        &self,
+        definition: &mut Map<String, Value>,
+        full: &str,
+        own: Option<&str>,
+        enclosing: Option<&str>,
+    ) {
+        match self.names {
+            None => {}
+            Some(NameStyle::Full) => {
+                definition.insert("name".to_string(), Value::String(full.to_string()));
+                definition.remove("namespace");
+            }
+            Some(NameStyle::Minimal) => {
+                let short = full.rsplit('.').next().unwrap_or(full);
+                definition.insert("name".to_string(), Value::String(short.to_string()));
+                if own == enclosing {
+                    definition.remove("namespace");
+                } else {
+                    let own = own.unwrap_or("").to_string();
+                    definition.insert("namespace".to_string(), Value::String(own));
+                }
+            }
+        }
+    }
+
+    /// Write a reference to a named type in the chosen style
+    fn reference(&self, name: &str, namespace: Option<&str>) -> String {
+        if PRIMITIVES.contains(&name) {
+            return name.to_string();
+        }
+        let full = fullname(name, namespace);
+        match self.names {
+            None => name.to_string(),
+            Some(NameStyle::Full) => full,
+            Some(NameStyle::Minimal) => match full.rsplit_once('.') {
+                Some((own, short)) if Some(own) == namespace => short.to_string(),
+                _ => full,
+            },
+        }
+    }
+}
+
+/// Attributes in the given order, the others after them in alphabetical order
+fn ordered<'a>(object: &'a Map<String, Value>, order: &[&str]) -> Vec<(&'a String, &'a Value)> {
+    let mut attributes = object.iter().collect::<Vec<_>>();
+    attributes.sort_by_key(|(key, _)| {
+        let position = order.iter().position(|attribute| attribute == key);
+        (position.unwrap_or(order.len()), key.to_string())
+    });
+    attributes
+}
+
+fn plain(value: &Value) -> Node {
+    match value {
+        Value::Array(values) => Node::Array(values.iter().map(plain).collect()),
+        Value::Object(object) => Node::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), plain(value)))
+                .collect(),
+        ),
+        _ => Node::Plain(value.clone()),
+    }
+}
+
+fn write(node: &Node, indent: &str, level: usize, out: &mut String) {
+    match node {
+        Node::Plain(value) => out.push_str(&value.to_string()),
+        Node::Array(values) if values.is_empty() => out.push_str("[]"),
+        Node::Array(values) if values.iter().all(|value| matches!(value, Node::Plain(_))) => {
+            out.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+                write(value, indent, level, out);
+            }
+            out.push(']');
+        }
+        Node::Array(values) => {
+            out.push_str("[\n");
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(",\n");
+                }
+                out.push_str(&indent.repeat(level + 1));
+                write(value, indent, level + 1, out);
+            }
+            out.push('\n');
+            out.push_str(&indent.repeat(level));
+            out.push(']');
+        }
+        Node::Object(attributes) if attributes.is_empty() => out.push_str("{}"),
+        Node::Object(attributes) => {
+            out.push_str("{\n");
+            for (index, (key, value)) in attributes.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(",\n");
+                }
+                out.push_str(&indent.repeat(level + 1));
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push_str(": ");
+                write(value, indent, level + 1, out);
+            }
+            out.push('\n');
+            out.push_str(&indent.repeat(level));
+            out.push('}');
+        }
+    }
+}
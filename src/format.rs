@@ -0,0 +1,257 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Schema languages degauss works with.
+//!
+//! Every language implements [`DegaussSchema`], which is all the compatibility checks, reports, fingerprints and
+//! the schema registry client need. Avro, Protobuf and JSON Schema come with degauss, other formats can be plugged
+//! in by implementing the trait.
+//!
+//! ```rust
+//! use degauss::prelude::*;
+//! use std::path::Path;
+//!
+//! /// Schemas which are lists of required column names
+//! struct Columns(Vec<String>);
+//!
+//! impl DegaussSchema for Columns {
+//!     fn parse_str_at(_: &Path, input: &str) -> Result<Self, DegaussError> {
+//!         Ok(Columns(input.split(',').map(str::to_string).collect()))
+//!     }
+//!
+//!     fn canonical_form(&self) -> String {
+//!         self.0.join(",")
+//!     }
+//!
+//!     fn schema_type(&self) -> SchemaType {
+//!         SchemaType::Other("COLUMNS".to_string())
+//!     }
+//!
+//!     fn record_name(&self) -> Option<String> {
+//!         None
+//!     }
+//!
+//!     fn payload(&self) -> SchemaPayload {
+//!         SchemaPayload::typed(self.0.join(","), self.schema_type())
+//!     }
+//!
+//!     fn explain_pair(
+//!         reader_version: usize,
+//!         reader: &Self,
+//!         writer_version: usize,
+//!         writer: &Self,
+//!     ) -> Vec<Incompatibility> {
+//!         reader
+//!             .0
+//!             .iter()
+//!             .filter(|column| !writer.0.contains(column))
+//!             .map(|column| Incompatibility {
+//!                 reader_version,
+//!                 writer_version,
+//!                 path: format!("/{}", column),
+//!                 reader_type: "column".to_string(),
+//!                 writer_type: "absent".to_string(),
+//!                 kind: IncompatibilityKind::Other("missing_column"),
+//!                 message: format!("writer doesn't write {}", column),
+//!             })
+//!             .collect()
+//!     }
+//! }
+//!
+//! let history = [
+//!     Columns::parse_str_at(Path::new("v1"), "id,name").unwrap(),
+//!     Columns::parse_str_at(Path::new("v2"), "id").unwrap(),
+//! ];
+//! assert!(DegaussCheck(DegaussCompatMode::Backward).validate(&history));
+//! assert!(!DegaussCheck(DegaussCompatMode::Forward).validate(&history));
+//! assert_eq!(history[1].payload().schema_type, Some(SchemaType::Other("COLUMNS".to_string())));
+//! ```
+//!
+
+use crate::errors::DegaussError;
+use crate::explain::{self, AvroIncompatibilityKind};
+use crate::fingerprint::{self, Fingerprints};
+use crate::json_schema::JsonSchemaIncompatibilityKind;
+use crate::protobuf::ProtobufIncompatibilityKind;
+use crate::schema_registry::types::{SchemaPayload, SchemaType};
+use apache_avro::Schema;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+/// Reason of an incompatibility, in the terms of the schema language
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize)]
+#[serde(untagged)]
+pub enum IncompatibilityKind {
+    Avro(AvroIncompatibilityKind),
+    Protobuf(ProtobufIncompatibilityKind),
+    JsonSchema(JsonSchemaIncompatibilityKind),
+    /// Reasons of other schema languages, in snake case, e.g. `missing_column`.
+    Other(&'static str),
+}
+
+impl fmt::Display for IncompatibilityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncompatibilityKind::Avro(kind) => kind.fmt(f),
+            IncompatibilityKind::Protobuf(kind) => kind.fmt(f),
+            IncompatibilityKind::JsonSchema(kind) => kind.fmt(f),
+            IncompatibilityKind::Other(kind) => f.write_str(kind),
+        }
+    }
+}
+
+impl From<AvroIncompatibilityKind> for IncompatibilityKind {
+    fn from(kind: AvroIncompatibilityKind) -> Self {
+        IncompatibilityKind::Avro(kind)
+    }
+}
+
+impl From<ProtobufIncompatibilityKind> for IncompatibilityKind {
+    fn from(kind: ProtobufIncompatibilityKind) -> Self {
+        IncompatibilityKind::Protobuf(kind)
+    }
+}
+
+impl From<JsonSchemaIncompatibilityKind> for IncompatibilityKind {
+    fn from(kind: JsonSchemaIncompatibilityKind) -> Self {
+        IncompatibilityKind::JsonSchema(kind)
+    }
+}
+
+/// A single compatibility violation between two schemas of a history
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Incompatibility {
+    /// Index of the reader schema in the given history, oldest is 0.
+    pub reader_version: usize,
+    /// Index of the writer schema in the given history, oldest is 0.
+    pub writer_version: usize,
+    /// JSON pointer into the reader schema, e.g. `/fields/3/type`.
+    pub path: String,
+    /// Type found in the reader schema at `path`.
+    pub reader_type: String,
+    /// Type the writer schema has for the same location.
+    pub writer_type: String,
+    /// Reason of the violation.
+    pub kind: IncompatibilityKind,
+    /// Human readable description of the violation.
+    pub message: String,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "v{} reading v{} at {}: {} ({})",
+            self.reader_version, self.writer_version, self.path, self.message, self.kind
+        )
+    }
+}
+
+/// Schema language which degauss can parse, check for compatibility and register
+pub trait DegaussSchema {
+    /// Parse the text of a schema, errors name the given path
+    fn parse_str_at(path: &Path, input: &str) -> Result<Self, DegaussError>
+    where
+        Self: Sized;
+
+    /// Text of the schema without the parts which don't change its meaning
+    fn canonical_form(&self) -> String;
+
+    /// Type of the schema as schema registry knows it
+    fn schema_type(&self) -> SchemaType;
+
+    /// Name of the schema for the record name strategies
+    fn record_name(&self) -> Option<String>;
+
+    /// Explain why the `reader` schema can't read data written with the `writer` schema.
+    ///
+    /// Returns an empty list when the reader can read everything the writer produces.
+    fn explain_pair(
+        reader_version: usize,
+        reader: &Self,
+        writer_version: usize,
+        writer: &Self,
+    ) -> Vec<Incompatibility>
+    where
+        Self: Sized;
+
+    /// Fingerprints of the canonical form
+    fn fingerprints(&self) -> Fingerprints {
+        Fingerprints::of_canonical_form(self.canonical_form())
+    }
+
+    /// Schema as it is sent to schema registry, which keeps it as it is.
+    /// This is the schema as written: canonical forms which leave out options or keywords lose them in the registry.
+    fn payload(&self) -> SchemaPayload;
+}
+
+/// Avro schemas, without the named types of other files, see [`SchemaDependencies`](crate::schema::SchemaDependencies) for those
+impl DegaussSchema for Schema {
+    fn parse_str_at(path: &Path, input: &str) -> Result<Self, DegaussError> {
+        Schema::parse_str(input).map_err(|source| DegaussError::SchemaFile {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Keeps the logical types, unlike the form which is fingerprinted, so it parses back into the same schema.
+    fn canonical_form(&self) -> String {
+        Schema::canonical_form(self)
+    }
+
+    fn schema_type(&self) -> SchemaType {
+        SchemaType::Avro
+    }
+
+    fn record_name(&self) -> Option<String> {
+        match self {
+            Schema::Record { name, .. }
+            | Schema::Enum { name, .. }
+            | Schema::Fixed { name, .. } => Some(name.fullname(None)),
+            _ => None,
+        }
+    }
+
+    fn explain_pair(
+        reader_version: usize,
+        reader: &Self,
+        writer_version: usize,
+        writer: &Self,
+    ) -> Vec<Incompatibility> {
+        explain::explain_pair(reader_version, reader, writer_version, writer)
+    }
+
+    /// Fingerprints of the Parsing Canonical Form, or of the form apache-avro writes when it isn't JSON.
+    fn fingerprints(&self) -> Fingerprints {
+        Fingerprints::of_canonical_form(
+            fingerprint::parsing_canonical_form(self)
+                .unwrap_or_else(|_| Schema::canonical_form(self)),
+        )
+    }
+
+    /// Avro schemas are sent without a type, which schema registry takes as Avro.
+    fn payload(&self) -> SchemaPayload {
+        SchemaPayload::new(self)
+    }
+}
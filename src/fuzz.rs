@@ -0,0 +1,558 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Round trip fuzzing of the compatibility checks.
+//!
+//! Random datums of the writer schema are encoded and decoded with the reader schema.
+//! The outcome is compared with the verdict of [`DegaussCheck`], a datum which can't be read
+//! although the schemas are compatible is minimized and reported back.
+//!
+//! ```rust,no_run
+//! use apache_avro::Schema;
+//! use degauss::prelude::*;
+//!
+//! let writer = Schema::parse_file("old.avsc").unwrap();
+//! let reader = Schema::parse_file("new.avsc").unwrap();
+//! let outcome = fuzz(&writer, &reader, &FuzzConfig::default()).unwrap();
+//! assert!(outcome.agrees());
+//! ```
+//!
+
+use crate::compat::{DegaussCheck, DegaussCompatMode};
+use crate::errors::DegaussError;
+use crate::explain::{named_types, resolve};
+use crate::report::{Finding, ReportCase, Severity};
+use apache_avro::schema::Namespace;
+use apache_avro::types::Value;
+use apache_avro::{
+    from_avro_datum, to_avro_datum, Days, Decimal, Duration, Millis, Months, Schema,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::mem::{self, Discriminant};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Upper bound of the attempts to shrink a failing datum
+const MAX_SHRINKS: usize = 1000;
+
+/// Settings of a fuzzing run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FuzzConfig {
+    /// Number of datums to generate.
+    pub iterations: usize,
+    /// Seed of the random generator, the same seed generates the same datums.
+    pub seed: u64,
+    /// Depth after which recursive types and collections are kept as small as possible.
+    pub max_depth: usize,
+    /// Maximum length of arrays, maps, strings and bytes.
+    pub max_len: usize,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        FuzzConfig {
+            iterations: 1000,
+            seed: 0,
+            max_depth: 8,
+            max_len: 8,
+        }
+    }
+}
+
+/// A datum of the writer schema which the reader schema can't read
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzFailure {
+    /// Smallest failing datum found.
+    pub datum: Value,
+    pub error: String,
+}
+
+impl FuzzFailure {
+    /// The datum in its JSON form
+    pub fn datum_json(&self) -> String {
+        serde_json::Value::try_from(self.datum.clone())
+            .map(|json| json.to_string())
+            .unwrap_or_else(|_| format!("{:?}", self.datum))
+    }
+}
+
+/// Outcome of a fuzzing run
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzOutcome {
+    /// Compat mode of the static check, the reader is the newer schema reading the data of the writer.
+    pub compatibility: DegaussCompatMode,
+    /// Verdict of the static check, whether the reader can read all the data of the writer.
+    pub compatible: bool,
+    pub seed: u64,
+    /// Number of datums generated.
+    pub datums: usize,
+    /// Number of datums the reader read successfully.
+    pub resolved: usize,
+    /// The run stops at the first datum which can't be read.
+    pub failure: Option<FuzzFailure>,
+}
+
+impl FuzzOutcome {
+    /// Whether the round trips match the verdict of the static check.
+    /// An incompatibility is only confirmed when a datum can't be read.
+    pub fn agrees(&self) -> bool {
+        self.compatible == self.failure.is_none()
+    }
+
+    /// Create a report case out of the fuzzing of the `reader` file
+    pub fn report(&self, reader: &str) -> ReportCase {
+        let message = match (&self.failure, self.compatible) {
+            (Some(failure), true) => Some((
+                Severity::Error,
+                format!(
+                    "schemas are compatible but the reader can't read {}: {} (seed {})",
+                    failure.datum_json(),
+                    failure.error,
+                    self.seed
+                ),
+            )),
+            (None, false) => Some((
+                Severity::Warning,
+                format!(
+                    "schemas are incompatible but the reader read all {} datums (seed {})",
+                    self.datums, self.seed
+                ),
+            )),
+            _ => None,
+        };
+        ReportCase {
+            name: "fuzz".to_string(),
+            compatibility: Some(self.compatibility),
+            passed: self.agrees(),
+            findings: message
+                .into_iter()
+                .map(|(severity, message)| Finding {
+                    rule: "fuzz_disagreement".to_string(),
+                    severity,
+                    message,
+                    file: Some(reader.to_string()),
+                    path: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Generate a random datum of the schema
+pub fn generate<R: Rng>(schema: &Schema, rng: &mut R, config: &FuzzConfig) -> Value {
+    let mut generator = Generator {
+        names: named_types(schema),
+        rng,
+        config,
+    };
+    generator.value(schema, &None, 0)
+}
+
+/// Encode random datums of the writer and read them with the reader, stops at the first failure.
+pub fn fuzz(
+    writer: &Schema,
+    reader: &Schema,
+    config: &FuzzConfig,
+) -> Result<FuzzOutcome, DegaussError> {
+    let compatibility = DegaussCompatMode::Backward;
+    let compatible = DegaussCheck(compatibility).validate(&[writer.clone(), reader.clone()]);
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut outcome = FuzzOutcome {
+        compatibility,
+        compatible,
+        seed: config.seed,
+        datums: 0,
+        resolved: 0,
+        failure: None,
+    };
+    for _ in 0..config.iterations {
+        let datum = generate(writer, &mut rng, config);
+        outcome.datums += 1;
+        match round_trip(writer, reader, &datum)? {
+            Ok(()) => outcome.resolved += 1,
+            Err(error) => {
+                outcome.failure = Some(minimize(writer, reader, datum, error));
+                break;
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+/// A failure to read a datum
+struct ReadError {
+    /// Variant of the apache-avro error, none when the reader panicked.
+    kind: Option<Discriminant<apache_avro::Error>>,
+    message: String,
+}
+
+/// Encode the datum with the writer and decode it with the reader.
+/// The outer error is an encoding failure, the inner one a failure to read.
+fn round_trip(
+    writer: &Schema,
+    reader: &Schema,
+    datum: &Value,
+) -> Result<Result<(), ReadError>, DegaussError> {
+    let encoded = to_avro_datum(writer, datum.clone())?;
+    // Resolution of some recursive schemas panics in apache-avro, it is a failure to read as well.
+    // The default hook would print every panic with its backtrace, keep it quiet meanwhile.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let decoded = panic::catch_unwind(AssertUnwindSafe(|| {
+        from_avro_datum(writer, &mut &encoded[..], Some(reader))
+    }));
+    panic::set_hook(hook);
+    Ok(match decoded {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(error)) => Err(ReadError {
+            kind: Some(mem::discriminant(&error)),
+            message: error.to_string(),
+        }),
+        Err(payload) => Err(ReadError {
+            kind: None,
+            message: format!(
+                "reader panicked: {}",
+                payload
+                    .downcast_ref::<&str>()
+                    .map(|message| message.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default()
+            ),
+        }),
+    })
+}
+
+/// Shrink the failing datum as long as it keeps failing the same way.
+/// A candidate is only kept when it is still a valid datum of the writer and the reader fails with the same kind of error.
+fn minimize(writer: &Schema, reader: &Schema, datum: Value, error: ReadError) -> FuzzFailure {
+    let shrinker = Shrinker {
+        names: named_types(writer),
+    };
+    let kind = error.kind;
+    let mut failure = FuzzFailure {
+        datum,
+        error: error.message,
+    };
+    let mut attempts = 0;
+    'shrinking: while attempts < MAX_SHRINKS {
+        for candidate in shrinker.shrink(&failure.datum, writer, &None) {
+            attempts += 1;
+            if candidate.validate(writer) {
+                if let Ok(Err(error)) = round_trip(writer, reader, &candidate) {
+                    if error.kind == kind {
+                        failure = FuzzFailure {
+                            datum: candidate,
+                            error: error.message,
+                        };
+                        continue 'shrinking;
+                    }
+                }
+            }
+            if attempts >= MAX_SHRINKS {
+                break 'shrinking;
+            }
+        }
+        break;
+    }
+    failure
+}
+
+/// Shrinks datums along their schema, so logical types aren't shrunk into invalid data
+struct Shrinker<'s> {
+    names: HashMap<String, &'s Schema>,
+}
+
+impl<'s> Shrinker<'s> {
+    /// Smaller variants of the value, simplest first
+    fn shrink(&self, value: &Value, schema: &'s Schema, namespace: &Namespace) -> Vec<Value> {
+        let (schema, namespace) = resolve(&self.names, schema, namespace);
+        match (value, schema) {
+            // Any other string isn't a uuid, dates, decimals and durations are left as they are too.
+            (_, Schema::Uuid | Schema::Date | Schema::Decimal { .. } | Schema::Duration) => vec![],
+            (Value::Union(index, inner), Schema::Union(union)) => {
+                match union.variants().get(*index as usize) {
+                    Some(variant) => self
+                        .shrink(inner, variant, &namespace)
+                        .into_iter()
+                        .map(|inner| Value::Union(*index, Box::new(inner)))
+                        .collect(),
+                    None => vec![],
+                }
+            }
+            (Value::Array(items), Schema::Array(schema)) if !items.is_empty() => {
+                let mut candidates = vec![
+                    Value::Array(vec![]),
+                    Value::Array(items[..items.len() / 2].to_vec()),
+                ];
+                for (i, item) in items.iter().enumerate() {
+                    let mut removed = items.clone();
+                    removed.remove(i);
+                    candidates.push(Value::Array(removed));
+                    for smaller in self.shrink(item, schema, &namespace) {
+                        let mut replaced = items.clone();
+                        replaced[i] = smaller;
+                        candidates.push(Value::Array(replaced));
+                    }
+                }
+                candidates
+            }
+            (Value::Map(entries), Schema::Map(schema)) if !entries.is_empty() => {
+                let mut candidates = vec![Value::Map(HashMap::new())];
+                for (key, entry) in entries {
+                    let mut removed = entries.clone();
+                    removed.remove(key);
+                    candidates.push(Value::Map(removed));
+                    for smaller in self.shrink(entry, schema, &namespace) {
+                        let mut replaced = entries.clone();
+                        replaced.insert(key.clone(), smaller);
+                        candidates.push(Value::Map(replaced));
+                    }
+                }
+                candidates
+            }
+            (Value::Record(values), Schema::Record { fields, .. }) => {
+                let mut candidates = vec![];
+                for (i, ((_, value), field)) in values.iter().zip(fields).enumerate() {
+                    for smaller in self.shrink(value, &field.schema, &namespace) {
+                        let mut replaced = values.clone();
+                        replaced[i].1 = smaller;
+                        candidates.push(Value::Record(replaced));
+                    }
+                }
+                candidates
+            }
+            _ => shrink(value),
+        }
+    }
+}
+
+/// Smaller variants of a scalar value, simplest first
+fn shrink(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Boolean(true) => vec![Value::Boolean(false)],
+        Value::Int(i) if *i != 0 => vec![Value::Int(0), Value::Int(i / 2)],
+        Value::Long(i) if *i != 0 => vec![Value::Long(0), Value::Long(i / 2)],
+        Value::Float(f) if *f != 0.0 => vec![Value::Float(0.0)],
+        Value::Double(f) if *f != 0.0 => vec![Value::Double(0.0)],
+        Value::TimeMillis(i) if *i != 0 => vec![Value::TimeMillis(0)],
+        Value::TimeMicros(i) if *i != 0 => vec![Value::TimeMicros(0)],
+        Value::TimestampMillis(i) if *i != 0 => vec![Value::TimestampMillis(0)],
+        Value::TimestampMicros(i) if *i != 0 => vec![Value::TimestampMicros(0)],
+        Value::String(s) if !s.is_empty() => {
+            let half = s.chars().take(s.chars().count() / 2).collect();
+            vec![Value::String(String::new()), Value::String(half)]
+        }
+        Value::Bytes(b) if !b.is_empty() => vec![
+            Value::Bytes(vec![]),
+            Value::Bytes(b[..b.len() / 2].to_vec()),
+        ],
+        Value::Fixed(size, b) if b.iter().any(|byte| *byte != 0) => {
+            vec![Value::Fixed(*size, vec![0; *size])]
+        }
+        _ => vec![],
+    }
+}
+
+struct Generator<'s, 'r, R: Rng> {
+    names: HashMap<String, &'s Schema>,
+    rng: &'r mut R,
+    config: &'r FuzzConfig,
+}
+
+impl<'s, 'r, R: Rng> Generator<'s, 'r, R> {
+    /// Pick one of the edge cases every now and then, a random value otherwise
+    fn edge_or<T: Copy>(&mut self, edges: &[T], random: impl FnOnce(&mut R) -> T) -> T {
+        if self.rng.gen_bool(0.25) {
+            edges[self.rng.gen_range(0..edges.len())]
+        } else {
+            random(self.rng)
+        }
+    }
+
+    fn len(&mut self, depth: usize) -> usize {
+        if depth >= self.config.max_depth {
+            0
+        } else {
+            self.rng.gen_range(0..=self.config.max_len)
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.rng.gen()).collect()
+    }
+
+    fn string(&mut self, depth: usize) -> String {
+        let len = self.len(depth);
+        (0..len).map(|_| self.rng.gen::<char>()).collect()
+    }
+
+    /// How far a schema is from a leaf, used to end recursion at the maximum depth
+    fn weight(&self, schema: &Schema) -> u8 {
+        match schema {
+            Schema::Null => 0,
+            Schema::Array(_) | Schema::Map(_) => 2,
+            Schema::Record { .. } | Schema::Ref { .. } | Schema::Union(_) => 3,
+            _ => 1,
+        }
+    }
+
+    fn value(&mut self, schema: &'s Schema, namespace: &Namespace, depth: usize) -> Value {
+        let (schema, namespace) = resolve(&self.names, schema, namespace);
+        match schema {
+            Schema::Null => Value::Null,
+            Schema::Boolean => Value::Boolean(self.rng.gen()),
+            Schema::Int => Value::Int(self.edge_or(&[0, 1, -1, i32::MIN, i32::MAX], |r| r.gen())),
+            Schema::Long => Value::Long(self.edge_or(
+                &[
+                    0,
+                    1,
+                    -1,
+                    i32::MIN as i64,
+                    i32::MAX as i64,
+                    i64::MIN,
+                    i64::MAX,
+                    (1 << 53) + 1,
+                ],
+                |r| r.gen(),
+            )),
+            Schema::Float => Value::Float(self.edge_or(
+                &[
+                    0.0,
+                    -0.0,
+                    f32::MIN,
+                    f32::MAX,
+                    f32::EPSILON,
+                    f32::INFINITY,
+                    f32::NEG_INFINITY,
+                ],
+                |r| r.gen_range(-1e6..1e6),
+            )),
+            Schema::Double => Value::Double(self.edge_or(
+                &[
+                    0.0,
+                    -0.0,
+                    f64::MIN,
+                    f64::MAX,
+                    f64::EPSILON,
+                    f64::INFINITY,
+                    f64::NEG_INFINITY,
+                ],
+                |r| r.gen_range(-1e12..1e12),
+            )),
+            Schema::Bytes => {
+                let len = self.len(depth);
+                Value::Bytes(self.bytes(len))
+            }
+            Schema::String => Value::String(self.string(depth)),
+            Schema::Array(items) => {
+                let len = self.len(depth);
+                Value::Array(
+                    (0..len)
+                        .map(|_| self.value(items, &namespace, depth + 1))
+                        .collect(),
+                )
+            }
+            Schema::Map(values) => {
+                let len = self.len(depth);
+                Value::Map(
+                    (0..len)
+                        .map(|_| {
+                            (
+                                self.string(depth),
+                                self.value(values, &namespace, depth + 1),
+                            )
+                        })
+                        .collect(),
+                )
+            }
+            Schema::Union(union) => {
+                let variants = union.variants();
+                let index = if depth >= self.config.max_depth {
+                    (0..variants.len())
+                        .min_by_key(|i| self.weight(&variants[*i]))
+                        .unwrap_or(0)
+                } else {
+                    self.rng.gen_range(0..variants.len())
+                };
+                Value::Union(
+                    index as u32,
+                    Box::new(self.value(&variants[index], &namespace, depth + 1)),
+                )
+            }
+            Schema::Record { fields, .. } => Value::Record(
+                fields
+                    .iter()
+                    .map(|field| {
+                        (
+                            field.name.clone(),
+                            self.value(&field.schema, &namespace, depth + 1),
+                        )
+                    })
+                    .collect(),
+            ),
+            Schema::Enum { symbols, .. } => {
+                let index = self.rng.gen_range(0..symbols.len());
+                Value::Enum(index as u32, symbols[index].clone())
+            }
+            Schema::Fixed { size, .. } => Value::Fixed(*size, self.bytes(*size)),
+            Schema::Decimal {
+                precision, inner, ..
+            } => {
+                let len = match inner.as_ref() {
+                    Schema::Fixed { size, .. } => *size,
+                    // Stay below the precision, every byte holds more than two decimal digits.
+                    _ => self.rng.gen_range(1..=(precision / 3).max(1)),
+                };
+                Value::Decimal(Decimal::from(self.bytes(len)))
+            }
+            Schema::Uuid => {
+                let hex = self
+                    .bytes(16)
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect::<String>();
+                Value::String(format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                ))
+            }
+            Schema::Date => Value::Date(self.rng.gen()),
+            Schema::TimeMillis => Value::TimeMillis(self.rng.gen_range(0..86_400_000)),
+            Schema::TimeMicros => Value::TimeMicros(self.rng.gen_range(0..86_400_000_000)),
+            Schema::TimestampMillis => Value::TimestampMillis(self.rng.gen()),
+            Schema::TimestampMicros => Value::TimestampMicros(self.rng.gen()),
+            Schema::Duration => Value::Duration(Duration::new(
+                Months::new(self.rng.gen()),
+                Days::new(self.rng.gen()),
+                Millis::new(self.rng.gen()),
+            )),
+            // Unknown reference, nothing to generate from.
+            Schema::Ref { .. } => Value::Null,
+        }
+    }
+}
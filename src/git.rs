@@ -0,0 +1,190 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Revisions of schema files read from the local git repository.
+//!
+//! Instead of keeping copies of every version, the history of a schema is read from the commits
+//! which changed its file, oldest first. Renames aren't followed, the history starts at the commit
+//! which added the file under its current name. `git` has to be on the `PATH`.
+//!
+//! ```rust,no_run
+//! use degauss::git;
+//! use degauss::prelude::*;
+//!
+//! let dependencies = SchemaDependencies::new();
+//! let mut schemas = git::history("schemas/movie.avsc", None)
+//!     .unwrap()
+//!     .iter()
+//!     .map(|revision| dependencies.parse_str_as("schemas/movie.avsc", &revision.contents, None, None))
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! schemas.push(dependencies.parse_file("schemas/movie.avsc").unwrap());
+//! assert!(DegaussCheck(DegaussCompatMode::Backward).validate(&schemas));
+//! ```
+//!
+
+use crate::compat::DegaussCompatMode;
+use crate::errors::DegaussError;
+use crate::schema::read_file;
+use std::path::Path;
+use std::process::Command;
+
+/// Contents of a file at a commit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Revision {
+    pub commit: String,
+    pub contents: String,
+}
+
+impl Revision {
+    /// Name of the revision of the file in reports, `<path>@<abbreviated commit>`
+    pub fn source<P: AsRef<Path>>(&self, path: P) -> String {
+        format!(
+            "{}@{}",
+            path.as_ref().display(),
+            &self.commit[..self.commit.len().min(7)]
+        )
+    }
+}
+
+/// Committed revisions of a file in commit order, oldest first, commits which deleted the file are skipped.
+/// With `since`, the history starts with the revision of the file at that commit, if it existed then.
+pub fn history<P: AsRef<Path>>(
+    path: P,
+    since: Option<&str>,
+) -> Result<Vec<Revision>, DegaussError> {
+    let path = path.as_ref();
+    let (dir, name) = split(path)?;
+    let mut revisions = vec![];
+    let range = match since {
+        Some(since) => {
+            let commit = commit(dir, since)?;
+            if let Ok(revision) = revision_of(dir, name, &commit) {
+                revisions.push(revision);
+            }
+            format!("{}..HEAD", commit)
+        }
+        None => "HEAD".to_string(),
+    };
+    let commits = git(
+        dir,
+        &[
+            "log",
+            "--format=%H",
+            "--reverse",
+            "--diff-filter=ACMRT",
+            &range,
+            "--",
+            name,
+        ],
+    )?;
+    for commit in commits.lines().filter(|line| !line.is_empty()) {
+        revisions.push(revision_of(dir, name, commit)?);
+    }
+    Ok(revisions)
+}
+
+/// History of a file to check with a compatibility, oldest first: its committed revisions followed by the working copy,
+/// which is `None`. The working copy is left out when it is the same as the newest revision.
+/// Backward, forward and full compatibilities only compare the newest schema to the one before it, so only those are kept.
+pub fn check_history<P: AsRef<Path>>(
+    path: P,
+    since: Option<&str>,
+    compatibility: Option<DegaussCompatMode>,
+) -> Result<Vec<Option<Revision>>, DegaussError> {
+    let path = path.as_ref();
+    let revisions = history(path, since)?;
+    let unchanged = revisions
+        .last()
+        .is_some_and(|newest| read_file(path).is_ok_and(|contents| contents == newest.contents));
+    let mut history = revisions.into_iter().map(Some).collect::<Vec<_>>();
+    if !unchanged {
+        history.push(None);
+    }
+    if let Some(
+        DegaussCompatMode::Backward | DegaussCompatMode::Forward | DegaussCompatMode::Full,
+    ) = compatibility
+    {
+        let older = history.len().saturating_sub(2);
+        history.drain(..older);
+    }
+    Ok(history)
+}
+
+/// Revision of a file at a commit, or any other revision git understands like a branch or `HEAD~1`
+pub fn revision<P: AsRef<Path>>(path: P, rev: &str) -> Result<Revision, DegaussError> {
+    let (dir, name) = split(path.as_ref())?;
+    revision_of(dir, name, &commit(dir, rev)?)
+}
+
+/// Directory to run git in, with the name of the file inside of it
+fn split(path: &Path) -> Result<(&Path, &str), DegaussError> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| DegaussError::Usage(format!("{} is not a file", path.display())))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    Ok((dir, name))
+}
+
+fn commit(dir: &Path, rev: &str) -> Result<String, DegaussError> {
+    let commit = git(
+        dir,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", rev)],
+    )?;
+    Ok(commit.trim().to_string())
+}
+
+fn revision_of(dir: &Path, name: &str, commit: &str) -> Result<Revision, DegaussError> {
+    Ok(Revision {
+        commit: commit.to_string(),
+        contents: git(dir, &["show", &format!("{}:./{}", commit, name)])?,
+    })
+}
+
+/// Run git in a directory and return its output
+fn git(dir: &Path, args: &[&str]) -> Result<String, DegaussError> {
+    let command = args.join(" ");
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| DegaussError::Git {
+            command: command.clone(),
+            message: e.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(DegaussError::Git {
+            command,
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    String::from_utf8(output.stdout).map_err(|e| DegaussError::Git {
+        command,
+        message: e.to_string(),
+    })
+}
@@ -0,0 +1,587 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Avro IDL parsing.
+//!
+//! An IDL file declares a protocol, which is turned into the JSON form of the protocol (`.avpr`).
+//! Imports are collected as they are, loading the imported files is up to the caller,
+//! see [`SchemaDependencies`](crate::schema::SchemaDependencies).
+//!
+//! ```rust
+//! use degauss::idl;
+//!
+//! let idl = idl::parse(r#"
+//!     @namespace("com.example")
+//!     protocol Movies {
+//!         /** A movie */
+//!         record Movie {
+//!             string title;
+//!             int? year = null;
+//!         }
+//!     }
+//! "#).unwrap();
+//! assert_eq!(idl.protocol["types"][0]["name"], "Movie");
+//! ```
+//!
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+const PRIMITIVES: [&str; 8] = [
+    "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+];
+
+/// Syntax error of an IDL file
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("line {line}, column {column}: {message}")]
+pub struct IdlError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Kind of a file imported by an IDL file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportKind {
+    /// Another IDL file, `import idl "file.avdl";`
+    Idl,
+    /// A JSON protocol, `import protocol "file.avpr";`
+    Protocol,
+    /// A JSON schema, `import schema "file.avsc";`
+    Schema,
+}
+
+/// File imported by an IDL file, the path is relative to the importing file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+    pub kind: ImportKind,
+    pub path: String,
+}
+
+/// A parsed IDL file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Idl {
+    /// JSON form of the declared protocol
+    pub protocol: Value,
+    pub imports: Vec<Import>,
+}
+
+/// Parse an IDL file into the protocol it declares
+pub fn parse(input: &str) -> Result<Idl, IdlError> {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        doc: None,
+    };
+    let idl = parser.protocol()?;
+    parser.skip()?;
+    if parser.pos < input.len() {
+        return Err(parser.error("unexpected input after the protocol"));
+    }
+    Ok(idl)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Last documentation comment, until a declaration takes it.
+    doc: Option<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T: Into<String>>(&self, message: T) -> IdlError {
+        let before = &self.input[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+        IdlError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    /// Skip whitespace and comments, keeping the last documentation comment
+    fn skip(&mut self) -> Result<(), IdlError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                let end = match comment.find("*/") {
+                    Some(end) => end + 2,
+                    None => return Err(self.error("unterminated comment")),
+                };
+                if trimmed.starts_with("/**") && end > 2 {
+                    self.doc = Some(doc_text(&trimmed[3..end]));
+                }
+                self.pos += end + 2;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn take_doc(&mut self) -> Option<String> {
+        self.doc.take()
+    }
+
+    fn peek(&mut self) -> Result<Option<char>, IdlError> {
+        self.skip()?;
+        Ok(self.rest().chars().next())
+    }
+
+    fn eat(&mut self, c: char) -> Result<bool, IdlError> {
+        if self.peek()? == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), IdlError> {
+        if self.eat(c)? {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", c)))
+        }
+    }
+
+    /// An identifier, dotted names included, or any name quoted in backticks
+    fn ident(&mut self) -> Result<String, IdlError> {
+        self.skip()?;
+        let rest = self.rest();
+        if let Some(quoted) = rest.strip_prefix('`') {
+            return match quoted.find('`') {
+                Some(end) => {
+                    self.pos += end + 2;
+                    Ok(quoted[..end].to_string())
+                }
+                None => Err(self.error("unterminated identifier")),
+            };
+        }
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.' || c == '-'))
+            .unwrap_or(rest.len());
+        match rest.chars().next() {
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                self.pos += end;
+                Ok(rest[..end].to_string())
+            }
+            _ => Err(self.error("expected an identifier")),
+        }
+    }
+
+    /// The next identifier, without consuming it
+    fn peek_ident(&mut self) -> Result<Option<String>, IdlError> {
+        let (pos, doc) = (self.pos, self.doc.clone());
+        let ident = self.ident().ok();
+        self.pos = pos;
+        self.doc = doc;
+        Ok(ident)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), IdlError> {
+        match self.ident() {
+            Ok(ident) if ident == keyword => Ok(()),
+            _ => Err(self.error(format!("expected `{}`", keyword))),
+        }
+    }
+
+    fn json(&mut self) -> Result<Value, IdlError> {
+        self.skip()?;
+        let rest = self.rest();
+        // Streamed numbers and literals must be followed by whitespace, take them up to the next delimiter.
+        if !rest.starts_with(['"', '[', '{']) {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || ",;)]}>".contains(c))
+                .unwrap_or(rest.len());
+            return match serde_json::from_str(&rest[..end]) {
+                Ok(value) => {
+                    self.pos += end;
+                    Ok(value)
+                }
+                Err(e) => Err(self.error(format!("invalid JSON value: {}", e))),
+            };
+        }
+        let mut values = serde_json::Deserializer::from_str(rest).into_iter::<Value>();
+        match values.next() {
+            Some(Ok(value)) => {
+                self.pos += values.byte_offset();
+                Ok(value)
+            }
+            Some(Err(e)) => Err(self.error(format!("invalid JSON value: {}", e))),
+            None => Err(self.error("expected a JSON value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, IdlError> {
+        match self.json()? {
+            Value::String(s) => Ok(s),
+            _ => Err(self.error("expected a string")),
+        }
+    }
+
+    fn integer(&mut self) -> Result<u64, IdlError> {
+        match self.json()?.as_u64() {
+            Some(n) => Ok(n),
+            None => Err(self.error("expected a non negative integer")),
+        }
+    }
+
+    /// Annotations like `@namespace("com.example")`, in order of appearance
+    fn annotations(&mut self) -> Result<Vec<(String, Value)>, IdlError> {
+        let mut annotations = vec![];
+        while self.eat('@')? {
+            let name = self.ident()?;
+            self.expect('(')?;
+            let value = self.json()?;
+            self.expect(')')?;
+            annotations.push((name, value));
+        }
+        Ok(annotations)
+    }
+
+    fn protocol(&mut self) -> Result<Idl, IdlError> {
+        self.skip()?;
+        let doc = self.take_doc();
+        let annotations = self.annotations()?;
+        self.keyword("protocol")?;
+        let name = self.ident()?;
+        self.expect('{')?;
+
+        let mut protocol = Map::new();
+        protocol.insert("protocol".to_string(), json!(name));
+        add_properties(&mut protocol, doc, annotations);
+        let mut types = vec![];
+        let mut messages = Map::new();
+        let mut imports = vec![];
+        loop {
+            self.skip()?;
+            if self.eat('}')? {
+                break;
+            }
+            if self.rest().is_empty() {
+                return Err(self.error("expected `}` at the end of the protocol"));
+            }
+            let doc = self.take_doc();
+            let annotations = self.annotations()?;
+            match self.peek_ident()?.as_deref() {
+                Some("import") => {
+                    self.keyword("import")?;
+                    let kind = match self.ident()?.as_str() {
+                        "idl" => ImportKind::Idl,
+                        "protocol" => ImportKind::Protocol,
+                        "schema" => ImportKind::Schema,
+                        _ => return Err(self.error("expected `idl`, `protocol` or `schema`")),
+                    };
+                    let path = self.string()?;
+                    self.expect(';')?;
+                    imports.push(Import { kind, path });
+                }
+                Some(kind @ ("record" | "error")) => {
+                    let kind = kind.to_string();
+                    types.push(self.record(&kind, doc, annotations)?)
+                }
+                Some("enum") => types.push(self.enumeration(doc, annotations)?),
+                Some("fixed") => types.push(self.fixed(doc, annotations)?),
+                _ => {
+                    let (name, message) = self.message(doc, annotations)?;
+                    messages.insert(name, message);
+                }
+            }
+        }
+        protocol.insert("types".to_string(), Value::Array(types));
+        if !messages.is_empty() {
+            protocol.insert("messages".to_string(), Value::Object(messages));
+        }
+        Ok(Idl {
+            protocol: Value::Object(protocol),
+            imports,
+        })
+    }
+
+    /// Start of a named type definition
+    fn named(
+        &mut self,
+        kind: &str,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> Result<Map<String, Value>, IdlError> {
+        self.keyword(kind)?;
+        let mut definition = Map::new();
+        definition.insert("type".to_string(), json!(kind));
+        definition.insert("name".to_string(), json!(self.ident()?));
+        add_properties(&mut definition, doc, annotations);
+        Ok(definition)
+    }
+
+    fn record(
+        &mut self,
+        kind: &str,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> Result<Value, IdlError> {
+        let mut record = self.named(kind, doc, annotations)?;
+        self.expect('{')?;
+        let mut fields = vec![];
+        while !self.eat('}')? {
+            fields.extend(self.fields()?);
+        }
+        record.insert("fields".to_string(), Value::Array(fields));
+        Ok(Value::Object(record))
+    }
+
+    /// Fields of a declaration like `string first, last = "";`
+    fn fields(&mut self) -> Result<Vec<Value>, IdlError> {
+        self.skip()?;
+        let doc = self.take_doc();
+        let (field_type, nullable) = self.field_type()?;
+        let mut fields = vec![];
+        loop {
+            let annotations = self.annotations()?;
+            let mut field = Map::new();
+            field.insert("name".to_string(), json!(self.ident()?));
+            let default = if self.eat('=')? {
+                Some(self.json()?)
+            } else {
+                None
+            };
+            // The branch of the default value goes first.
+            let field_type = match (nullable, &default) {
+                (false, _) => field_type.clone(),
+                (true, Some(default)) if !default.is_null() => json!([field_type, "null"]),
+                (true, _) => json!(["null", field_type]),
+            };
+            field.insert("type".to_string(), field_type);
+            if let Some(default) = default {
+                field.insert("default".to_string(), default);
+            }
+            add_properties(&mut field, doc.clone(), annotations);
+            fields.push(Value::Object(field));
+            if !self.eat(',')? {
+                break;
+            }
+        }
+        self.expect(';')?;
+        Ok(fields)
+    }
+
+    fn enumeration(
+        &mut self,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> Result<Value, IdlError> {
+        let mut enumeration = self.named("enum", doc, annotations)?;
+        self.expect('{')?;
+        let mut symbols = vec![];
+        while !self.eat('}')? {
+            symbols.push(json!(self.ident()?));
+            if !self.eat(',')? {
+                self.expect('}')?;
+                break;
+            }
+        }
+        enumeration.insert("symbols".to_string(), Value::Array(symbols));
+        if self.eat('=')? {
+            enumeration.insert("default".to_string(), json!(self.ident()?));
+            self.expect(';')?;
+        } else {
+            self.eat(';')?;
+        }
+        Ok(Value::Object(enumeration))
+    }
+
+    fn fixed(
+        &mut self,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> Result<Value, IdlError> {
+        let mut fixed = self.named("fixed", doc, annotations)?;
+        self.expect('(')?;
+        fixed.insert("size".to_string(), json!(self.integer()?));
+        self.expect(')')?;
+        self.expect(';')?;
+        Ok(Value::Object(fixed))
+    }
+
+    /// A message like `string hello(string greeting) throws Failure;`
+    fn message(
+        &mut self,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> Result<(String, Value), IdlError> {
+        let response = if self.peek_ident()?.as_deref() == Some("void") {
+            self.ident()?;
+            json!("null")
+        } else {
+            self.full_type()?
+        };
+        let name = self.ident()?;
+        self.expect('(')?;
+        let mut request = vec![];
+        while !self.eat(')')? {
+            let parameter_type = self.full_type()?;
+            let annotations = self.annotations()?;
+            let mut parameter = Map::new();
+            parameter.insert("name".to_string(), json!(self.ident()?));
+            parameter.insert("type".to_string(), parameter_type);
+            if self.eat('=')? {
+                parameter.insert("default".to_string(), self.json()?);
+            }
+            add_properties(&mut parameter, None, annotations);
+            request.push(Value::Object(parameter));
+            if !self.eat(',')? {
+                self.expect(')')?;
+                break;
+            }
+        }
+
+        let mut message = Map::new();
+        add_properties(&mut message, doc, annotations);
+        message.insert("request".to_string(), Value::Array(request));
+        message.insert("response".to_string(), response);
+        match self.peek_ident()?.as_deref() {
+            Some("oneway") => {
+                self.ident()?;
+                message.insert("one-way".to_string(), json!(true));
+            }
+            Some("throws") => {
+                self.ident()?;
+                let mut errors = vec![json!(self.ident()?)];
+                while self.eat(',')? {
+                    errors.push(json!(self.ident()?));
+                }
+                message.insert("errors".to_string(), Value::Array(errors));
+            }
+            _ => {}
+        }
+        self.expect(';')?;
+        Ok((name, Value::Object(message)))
+    }
+
+    /// A type, nullable ones as a union with null first
+    fn full_type(&mut self) -> Result<Value, IdlError> {
+        let (base, nullable) = self.field_type()?;
+        Ok(if nullable {
+            json!(["null", base])
+        } else {
+            base
+        })
+    }
+
+    /// A type with its annotations, and whether it is marked nullable with `?`
+    fn field_type(&mut self) -> Result<(Value, bool), IdlError> {
+        let annotations = self.annotations()?;
+        let name = self.ident()?;
+        let base = match name.as_str() {
+            "array" | "map" => {
+                self.expect('<')?;
+                let inner = self.full_type()?;
+                self.expect('>')?;
+                if name == "array" {
+                    json!({"type": "array", "items": inner})
+                } else {
+                    json!({"type": "map", "values": inner})
+                }
+            }
+            "union" => {
+                self.expect('{')?;
+                let mut branches = vec![self.full_type()?];
+                while self.eat(',')? {
+                    branches.push(self.full_type()?);
+                }
+                self.expect('}')?;
+                Value::Array(branches)
+            }
+            "decimal" => {
+                self.expect('(')?;
+                let precision = self.integer()?;
+                self.expect(',')?;
+                let scale = self.integer()?;
+                self.expect(')')?;
+                json!({"type": "bytes", "logicalType": "decimal", "precision": precision, "scale": scale})
+            }
+            "date" => json!({"type": "int", "logicalType": "date"}),
+            "time_ms" => json!({"type": "int", "logicalType": "time-millis"}),
+            "timestamp_ms" => json!({"type": "long", "logicalType": "timestamp-millis"}),
+            "local_timestamp_ms" => {
+                json!({"type": "long", "logicalType": "local-timestamp-millis"})
+            }
+            "uuid" => json!({"type": "string", "logicalType": "uuid"}),
+            _ => json!(name),
+        };
+        let base = if annotations.is_empty() {
+            base
+        } else {
+            match base {
+                Value::String(name) if PRIMITIVES.contains(&name.as_str()) => {
+                    let mut annotated = Map::new();
+                    annotated.insert("type".to_string(), json!(name));
+                    add_properties(&mut annotated, None, annotations);
+                    Value::Object(annotated)
+                }
+                Value::Object(mut annotated) => {
+                    add_properties(&mut annotated, None, annotations);
+                    Value::Object(annotated)
+                }
+                _ => return Err(self.error(format!("`{}` can't be annotated", name))),
+            }
+        };
+        Ok((base, self.eat('?')?))
+    }
+}
+
+/// Add the documentation and the annotations as properties of a definition
+fn add_properties(
+    definition: &mut Map<String, Value>,
+    doc: Option<String>,
+    annotations: Vec<(String, Value)>,
+) {
+    if let Some(doc) = doc {
+        definition.insert("doc".to_string(), json!(doc));
+    }
+    for (name, value) in annotations {
+        definition.insert(name, value);
+    }
+}
+
+/// Text of a documentation comment, without the leading `*` of its lines
+fn doc_text(comment: &str) -> String {
+    comment
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix('*').map_or(line, str::trim_start)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,331 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Compatibility of JSON Schemas.
+//!
+//! A reader can read the data of a writer when every value the writer accepts is accepted by the reader too.
+//! Integers of the writer are accepted as numbers, properties a closed writer never writes aren't constrained.
+//!
+
+use super::{escape, AdditionalProperties, JsonSchema, JsonType};
+use crate::format::Incompatibility;
+use serde::Serialize;
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+/// Reason why a reader JSON Schema can't read the data of a writer schema
+#[derive(
+    EnumVariantNames,
+    EnumString,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonSchemaIncompatibilityKind {
+    /// Reader doesn't accept the types of the writer.
+    #[strum(serialize = "type_mismatch")]
+    TypeMismatch,
+
+    /// Writer accepts an enum value which the reader doesn't.
+    #[strum(serialize = "enum_symbol_removed")]
+    EnumSymbolRemoved,
+
+    /// Writer has a JSON Schema property which the closed content model of the reader doesn't allow.
+    #[strum(serialize = "property_removed")]
+    PropertyRemoved,
+
+    /// Reader constrains a JSON Schema property which the open content model of the writer accepts any value for.
+    #[strum(serialize = "property_added_to_open_content_model")]
+    PropertyAddedToOpenContentModel,
+
+    /// Reader restricts the additional JSON Schema properties which the writer accepts.
+    #[strum(serialize = "content_model_closed")]
+    ContentModelClosed,
+
+    /// Reader requires a JSON Schema property which the older writer doesn't require.
+    #[strum(serialize = "required_property_added")]
+    RequiredPropertyAdded,
+
+    /// Older reader requires a JSON Schema property which the writer doesn't require.
+    #[strum(serialize = "required_property_removed")]
+    RequiredPropertyRemoved,
+}
+
+/// Explain why the `reader` schema can't read data written with the `writer` schema.
+///
+/// Returns an empty list when the reader accepts everything the writer accepts.
+pub fn explain_pair(
+    reader_version: usize,
+    reader: &JsonSchema,
+    writer_version: usize,
+    writer: &JsonSchema,
+) -> Vec<Incompatibility> {
+    let mut walker = Walker {
+        reader_version,
+        writer_version,
+        incompatibilities: vec![],
+    };
+    walker.schema(reader, writer, "");
+    walker.incompatibilities
+}
+
+struct Walker {
+    reader_version: usize,
+    writer_version: usize,
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl Walker {
+    fn add(
+        &mut self,
+        path: &str,
+        reader_type: String,
+        writer_type: String,
+        kind: JsonSchemaIncompatibilityKind,
+        message: String,
+    ) {
+        self.incompatibilities.push(Incompatibility {
+            reader_version: self.reader_version,
+            writer_version: self.writer_version,
+            path: if path.is_empty() {
+                "/".to_string()
+            } else {
+                path.to_string()
+            },
+            reader_type,
+            writer_type,
+            kind: kind.into(),
+            message,
+        });
+    }
+
+    fn schema(&mut self, reader: &JsonSchema, writer: &JsonSchema, path: &str) {
+        if reader.reference.is_some() || writer.reference.is_some() {
+            if reader.reference != writer.reference {
+                self.add(
+                    path,
+                    reader.to_string(),
+                    writer.to_string(),
+                    JsonSchemaIncompatibilityKind::TypeMismatch,
+                    format!(
+                        "reader is {} but writer is {}, references are only compared by their targets",
+                        reader, writer
+                    ),
+                );
+            }
+            return;
+        }
+        if reader.combinators != writer.combinators {
+            let mut keywords = reader
+                .combinators
+                .keys()
+                .chain(writer.combinators.keys())
+                .filter(|keyword| {
+                    reader.combinators.get(*keyword) != writer.combinators.get(*keyword)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            keywords.sort();
+            keywords.dedup();
+            self.add(
+                path,
+                reader.to_string(),
+                writer.to_string(),
+                JsonSchemaIncompatibilityKind::TypeMismatch,
+                format!(
+                    "{} changed, combinators are only compared as they are written",
+                    keywords.join(", ")
+                ),
+            );
+        }
+        self.types(reader, writer, path);
+        self.values(reader, writer, path);
+        if reader.may_be(JsonType::Object) && writer.may_be(JsonType::Object) {
+            self.object(reader, writer, path);
+        }
+        if reader.may_be(JsonType::Array) && writer.may_be(JsonType::Array) {
+            if let Some(items) = &reader.items {
+                let any = JsonSchema::default();
+                let written = writer.items.as_deref().unwrap_or(&any);
+                self.schema(items, written, &format!("{}/items", path));
+            }
+        }
+    }
+
+    fn types(&mut self, reader: &JsonSchema, writer: &JsonSchema, path: &str) {
+        let accepted = match &reader.types {
+            None => return,
+            Some(types) => types,
+        };
+        let narrowed = match &writer.types {
+            None => "values of any type".to_string(),
+            Some(types) => {
+                let missing = types
+                    .iter()
+                    .filter(|written| {
+                        !(accepted.contains(written)
+                            || **written == JsonType::Integer
+                                && accepted.contains(&JsonType::Number))
+                    })
+                    .map(JsonType::to_string)
+                    .collect::<Vec<_>>();
+                if missing.is_empty() {
+                    return;
+                }
+                format!("{} values", missing.join(", "))
+            }
+        };
+        self.add(
+            path,
+            reader.to_string(),
+            writer.to_string(),
+            JsonSchemaIncompatibilityKind::TypeMismatch,
+            format!("writer accepts {} which the reader doesn't", narrowed),
+        );
+    }
+
+    fn values(&mut self, reader: &JsonSchema, writer: &JsonSchema, path: &str) {
+        let accepted = match &reader.values {
+            None => return,
+            Some(values) => values,
+        };
+        match &writer.values {
+            None => self.add(
+                path,
+                format!("enum {}", serde_json::Value::from(accepted.clone())),
+                writer.to_string(),
+                JsonSchemaIncompatibilityKind::TypeMismatch,
+                "reader only accepts the values of its enum but the writer has no enum".to_string(),
+            ),
+            Some(written) => {
+                for value in written.iter().filter(|value| !accepted.contains(value)) {
+                    self.add(
+                        &format!("{}/enum", path),
+                        "absent".to_string(),
+                        value.to_string(),
+                        JsonSchemaIncompatibilityKind::EnumSymbolRemoved,
+                        format!("value {} of the writer isn't accepted by the reader", value),
+                    );
+                }
+            }
+        }
+    }
+
+    fn object(&mut self, reader: &JsonSchema, writer: &JsonSchema, path: &str) {
+        for (name, written) in &writer.properties {
+            let property = format!("{}/properties/{}", path, escape(name));
+            match (reader.properties.get(name), &reader.additional_properties) {
+                (Some(read), _) => self.schema(read, written, &property),
+                (None, AdditionalProperties::Open) => {}
+                (None, AdditionalProperties::Closed) => self.add(
+                    &property,
+                    "absent".to_string(),
+                    written.to_string(),
+                    JsonSchemaIncompatibilityKind::PropertyRemoved,
+                    format!(
+                        "property {} of the writer isn't allowed by the closed content model of the reader",
+                        name
+                    ),
+                ),
+                (None, AdditionalProperties::Schema(read)) => {
+                    self.schema(read, written, &property)
+                }
+            }
+        }
+        for (name, read) in &reader.properties {
+            if writer.properties.contains_key(name) {
+                continue;
+            }
+            let property = format!("{}/properties/{}", path, escape(name));
+            match &writer.additional_properties {
+                AdditionalProperties::Closed => {}
+                AdditionalProperties::Open if read.accepts_anything() => {}
+                AdditionalProperties::Open => self.add(
+                    &property,
+                    read.to_string(),
+                    "absent".to_string(),
+                    JsonSchemaIncompatibilityKind::PropertyAddedToOpenContentModel,
+                    format!(
+                        "property {} of the reader may be written with any value by the open content model of the writer",
+                        name
+                    ),
+                ),
+                AdditionalProperties::Schema(written) => self.schema(read, written, &property),
+            }
+        }
+        let additional = format!("{}/additionalProperties", path);
+        match (&reader.additional_properties, &writer.additional_properties) {
+            (AdditionalProperties::Open, _) | (_, AdditionalProperties::Closed) => {}
+            (AdditionalProperties::Schema(read), AdditionalProperties::Schema(written)) => {
+                self.schema(read, written, &additional)
+            }
+            (read, written) => {
+                let describe = |model: &AdditionalProperties| match model {
+                    AdditionalProperties::Open => "open".to_string(),
+                    AdditionalProperties::Closed => "closed".to_string(),
+                    AdditionalProperties::Schema(schema) => schema.to_string(),
+                };
+                self.add(
+                    &additional,
+                    describe(read),
+                    describe(written),
+                    JsonSchemaIncompatibilityKind::ContentModelClosed,
+                    "reader restricts the other properties which the writer accepts".to_string(),
+                )
+            }
+        }
+        for name in reader.required.difference(&writer.required) {
+            let (kind, message) = if self.reader_version > self.writer_version {
+                (
+                    JsonSchemaIncompatibilityKind::RequiredPropertyAdded,
+                    format!(
+                        "property {} is required by the newer reader but not by the writer",
+                        name
+                    ),
+                )
+            } else {
+                (
+                    JsonSchemaIncompatibilityKind::RequiredPropertyRemoved,
+                    format!(
+                        "property {} is required by the older reader but not by the writer",
+                        name
+                    ),
+                )
+            };
+            self.add(
+                &format!("{}/properties/{}", path, escape(name)),
+                "required".to_string(),
+                "optional".to_string(),
+                kind,
+                message,
+            );
+        }
+    }
+}
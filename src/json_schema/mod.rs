@@ -0,0 +1,566 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! JSON Schemas and their compatibility.
+//!
+//! Schemas are compared by the keywords which decide what data they accept: `type`, `properties`, `required`,
+//! `additionalProperties`, `items`, `enum` and `const`. Local `$ref`s are inlined, remote and recursive ones are
+//! compared by the references they point at. Combinators like `anyOf` are compared as they are written.
+//!
+//! ```rust
+//! use degauss::prelude::*;
+//! use degauss::json_schema;
+//!
+//! let old = json_schema::parse(r#"{
+//!     "type": "object",
+//!     "properties": {"title": {"type": "string"}, "year": {"type": "integer"}},
+//!     "additionalProperties": false
+//! }"#).unwrap();
+//! let new = json_schema::parse(r#"{
+//!     "type": "object",
+//!     "properties": {"title": {"type": "string"}, "year": {"type": "number"}},
+//!     "additionalProperties": false
+//! }"#).unwrap();
+//! assert!(DegaussCheck(DegaussCompatMode::Backward).validate(&[old, new]));
+//! ```
+//!
+
+mod compat;
+
+pub use compat::{explain_pair, JsonSchemaIncompatibilityKind};
+
+use crate::errors::DegaussError;
+use crate::format::DegaussSchema;
+use crate::format::Incompatibility;
+use crate::schema_registry::types::{SchemaPayload, SchemaType};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use strum_macros::{Display, EnumString, EnumVariantNames};
+use thiserror::Error;
+
+/// Keywords which are compared as they are written
+const COMBINATORS: [&str; 9] = [
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "patternProperties",
+    "prefixItems",
+];
+
+/// Invalid JSON Schema
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("{message} at {pointer}")]
+pub struct JsonSchemaError {
+    /// JSON pointer to the invalid keyword.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Types of JSON values
+#[derive(
+    EnumVariantNames, EnumString, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug,
+)]
+pub enum JsonType {
+    #[strum(serialize = "null")]
+    Null,
+    #[strum(serialize = "boolean")]
+    Boolean,
+    /// Numbers without a fractional part, every integer is a number too.
+    #[strum(serialize = "integer")]
+    Integer,
+    #[strum(serialize = "number")]
+    Number,
+    #[strum(serialize = "string")]
+    String,
+    #[strum(serialize = "array")]
+    Array,
+    #[strum(serialize = "object")]
+    Object,
+}
+
+/// Properties an object accepts besides the ones it defines
+#[derive(Clone, Debug, PartialEq, Default)]
+pub enum AdditionalProperties {
+    /// Any property is accepted, the content model is open.
+    #[default]
+    Open,
+    /// No other property is accepted, the content model is closed.
+    Closed,
+    /// Other properties are accepted when they are valid against the schema.
+    Schema(Box<JsonSchema>),
+}
+
+/// Keywords of a schema which decide the data it accepts
+#[derive(Clone, Debug, Default)]
+pub struct JsonSchema {
+    pub title: Option<String>,
+    /// Types of the accepted values, any type when not given.
+    pub types: Option<BTreeSet<JsonType>>,
+    pub properties: BTreeMap<String, JsonSchema>,
+    pub required: BTreeSet<String>,
+    pub additional_properties: AdditionalProperties,
+    /// Schema of every item of arrays.
+    pub items: Option<Box<JsonSchema>>,
+    /// Values of `enum`, or the value of `const`.
+    pub values: Option<Vec<Value>>,
+    /// Combinators and conditionals as they are written.
+    pub combinators: BTreeMap<String, Value>,
+    /// Remote or recursive `$ref` which isn't inlined.
+    pub reference: Option<String>,
+    /// Schemas the recursive `$ref`s point at, by reference, only kept on the root schema.
+    pub definitions: BTreeMap<String, JsonSchema>,
+    /// Text of the document as it is written, only kept on the root schema.
+    /// Schema registry takes it with the keywords which aren't compared, it doesn't decide equality.
+    pub source: Option<String>,
+}
+
+impl PartialEq for JsonSchema {
+    fn eq(&self, other: &Self) -> bool {
+        let JsonSchema {
+            title,
+            types,
+            properties,
+            required,
+            additional_properties,
+            items,
+            values,
+            combinators,
+            reference,
+            definitions,
+            source: _,
+        } = self;
+        *title == other.title
+            && *types == other.types
+            && *properties == other.properties
+            && *required == other.required
+            && *additional_properties == other.additional_properties
+            && *items == other.items
+            && *values == other.values
+            && *combinators == other.combinators
+            && *reference == other.reference
+            && *definitions == other.definitions
+    }
+}
+
+impl JsonSchema {
+    /// Whether the schema accepts every value
+    pub fn accepts_anything(&self) -> bool {
+        *self
+            == JsonSchema {
+                title: self.title.clone(),
+                definitions: self.definitions.clone(),
+                ..JsonSchema::default()
+            }
+    }
+
+    /// JSON of the keywords which are compared, `const` is written as an `enum` of one value
+    pub fn to_value(&self) -> Value {
+        let mut object = Map::new();
+        if let Some(reference) = &self.reference {
+            object.insert("$ref".to_string(), Value::from(reference.as_str()));
+            return Value::Object(object);
+        }
+        if let Some(title) = &self.title {
+            object.insert("title".to_string(), Value::from(title.as_str()));
+        }
+        match &self.types {
+            Some(types) if types.len() == 1 => {
+                object.insert(
+                    "type".to_string(),
+                    Value::from(types.iter().next().unwrap().to_string()),
+                );
+            }
+            Some(types) => {
+                object.insert(
+                    "type".to_string(),
+                    types.iter().map(JsonType::to_string).collect(),
+                );
+            }
+            None => {}
+        }
+        if !self.properties.is_empty() {
+            let properties = self
+                .properties
+                .iter()
+                .map(|(name, property)| (name.clone(), property.to_value()))
+                .collect();
+            object.insert("properties".to_string(), Value::Object(properties));
+        }
+        if !self.required.is_empty() {
+            object.insert(
+                "required".to_string(),
+                self.required.iter().map(String::as_str).collect(),
+            );
+        }
+        match &self.additional_properties {
+            AdditionalProperties::Open => {}
+            AdditionalProperties::Closed => {
+                object.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            AdditionalProperties::Schema(schema) => {
+                object.insert("additionalProperties".to_string(), schema.to_value());
+            }
+        }
+        if let Some(items) = &self.items {
+            object.insert("items".to_string(), items.to_value());
+        }
+        if let Some(values) = &self.values {
+            object.insert("enum".to_string(), Value::from(values.clone()));
+        }
+        for (keyword, value) in &self.combinators {
+            object.insert(keyword.clone(), value.clone());
+        }
+        let mut value = Value::Object(object);
+        let definitions = self
+            .definitions
+            .iter()
+            .map(|(reference, definition)| (reference.as_str(), definition.to_value()))
+            .collect::<Vec<_>>();
+        if let Value::Object(object) = &mut value {
+            object
+                .values_mut()
+                .for_each(|value| fold(value, &definitions));
+        }
+        for (reference, definition) in definitions {
+            if let Some(pointer) = reference.strip_prefix('#') {
+                insert_at(&mut value, pointer, definition);
+            }
+        }
+        value
+    }
+
+    /// Whether the schema may accept values of the type
+    pub fn may_be(&self, json_type: JsonType) -> bool {
+        self.types
+            .as_ref()
+            .is_none_or(|types| types.contains(&json_type))
+    }
+}
+
+impl fmt::Display for JsonSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.reference, &self.types) {
+            (Some(reference), _) => write!(f, "{}", reference),
+            (None, None) => write!(f, "any"),
+            (None, Some(types)) if types.is_empty() => write!(f, "nothing"),
+            (None, Some(types)) => write!(
+                f,
+                "{}",
+                types
+                    .iter()
+                    .map(JsonType::to_string)
+                    .collect::<Vec<_>>()
+                    .join("|")
+            ),
+        }
+    }
+}
+
+/// JSON Schemas, remote references aren't loaded
+impl DegaussSchema for JsonSchema {
+    fn parse_str_at(path: &Path, input: &str) -> Result<Self, DegaussError> {
+        parse_str_at(path, input)
+    }
+
+    /// JSON of the compared keywords, with sorted keys and local references inlined
+    fn canonical_form(&self) -> String {
+        self.to_value().to_string()
+    }
+
+    fn schema_type(&self) -> SchemaType {
+        SchemaType::Json
+    }
+
+    fn record_name(&self) -> Option<String> {
+        self.title.clone()
+    }
+
+    /// The document as it is written, schema registry keeps the keywords which aren't compared.
+    /// Schemas which weren't parsed send their canonical form.
+    fn payload(&self) -> SchemaPayload {
+        SchemaPayload::json(self.source.clone().unwrap_or_else(|| self.canonical_form()))
+    }
+
+    fn explain_pair(
+        reader_version: usize,
+        reader: &Self,
+        writer_version: usize,
+        writer: &Self,
+    ) -> Vec<Incompatibility> {
+        explain_pair(reader_version, reader, writer_version, writer)
+    }
+}
+
+/// Parse a JSON Schema file
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<JsonSchema, DegaussError> {
+    let path = path.as_ref();
+    parse_str_at(path, &crate::schema::read_file(path)?)
+}
+
+/// Parse the contents of a JSON Schema file, errors name the given path
+pub fn parse_str_at(path: &Path, input: &str) -> Result<JsonSchema, DegaussError> {
+    parse(input).map_err(|source| DegaussError::JsonSchemaFile {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Parse a JSON Schema, local references are inlined
+pub fn parse(input: &str) -> Result<JsonSchema, JsonSchemaError> {
+    let root: Value = serde_json::from_str(input).map_err(|e| JsonSchemaError {
+        pointer: "/".to_string(),
+        message: e.to_string(),
+    })?;
+    let mut loader = Loader {
+        root: &root,
+        stack: vec![],
+        recursive: BTreeSet::new(),
+    };
+    let mut schema = loader.schema(&root, "")?;
+    // Definitions may refer to further recursive references themselves.
+    while let Some(reference) = loader
+        .recursive
+        .iter()
+        .find(|reference| !schema.definitions.contains_key(*reference))
+        .cloned()
+    {
+        let definition = loader.reference(&Value::from(reference.as_str()), "")?;
+        schema.definitions.insert(reference, definition);
+    }
+    schema.source = Some(input.to_string());
+    Ok(schema)
+}
+
+/// Write the inlined copies of the definitions as the references they came from again
+fn fold(value: &mut Value, definitions: &[(&str, Value)]) {
+    if let Some((reference, _)) = definitions
+        .iter()
+        .find(|(_, definition)| definition == value)
+    {
+        *value = serde_json::json!({ "$ref": reference });
+        return;
+    }
+    match value {
+        Value::Object(object) => object
+            .values_mut()
+            .for_each(|value| fold(value, definitions)),
+        Value::Array(values) => values.iter_mut().for_each(|value| fold(value, definitions)),
+        _ => {}
+    }
+}
+
+/// Put the value at the JSON pointer, unless something is already there
+fn insert_at(value: &mut Value, pointer: &str, inserted: Value) {
+    let mut target = value;
+    let tokens = pointer.split('/').skip(1).collect::<Vec<_>>();
+    for (index, token) in tokens.iter().enumerate() {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        let object = match target {
+            Value::Object(object) => object,
+            _ => return,
+        };
+        if index + 1 == tokens.len() {
+            object.entry(token).or_insert(inserted);
+            return;
+        }
+        target = object
+            .entry(token)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+/// Escape a property name to be a JSON pointer token
+pub(crate) fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+struct Loader<'a> {
+    root: &'a Value,
+    /// References being inlined, to stop at recursive ones.
+    stack: Vec<String>,
+    /// Local references which were kept because they are recursive.
+    recursive: BTreeSet<String>,
+}
+
+impl<'a> Loader<'a> {
+    fn schema(&mut self, value: &'a Value, pointer: &str) -> Result<JsonSchema, JsonSchemaError> {
+        let object = match value {
+            Value::Bool(true) => return Ok(JsonSchema::default()),
+            Value::Bool(false) => {
+                return Ok(JsonSchema {
+                    types: Some(BTreeSet::new()),
+                    ..JsonSchema::default()
+                })
+            }
+            Value::Object(object) => object,
+            _ => return Err(error(pointer, "schema must be an object or a boolean")),
+        };
+        if let Some(reference) = object.get("$ref") {
+            return self.reference(reference, pointer);
+        }
+        let mut schema = JsonSchema {
+            title: object
+                .get("title")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            ..JsonSchema::default()
+        };
+        if let Some(types) = object.get("type") {
+            schema.types = Some(self.types(types, &format!("{}/type", pointer))?);
+        }
+        if let Some(properties) = object.get("properties") {
+            let pointer = format!("{}/properties", pointer);
+            for (name, property) in as_object(properties, &pointer)? {
+                let property_pointer = format!("{}/{}", pointer, escape(name));
+                schema
+                    .properties
+                    .insert(name.clone(), self.schema(property, &property_pointer)?);
+            }
+        }
+        if let Some(required) = object.get("required") {
+            let pointer = format!("{}/required", pointer);
+            schema.required = required
+                .as_array()
+                .ok_or_else(|| error(&pointer, "required must be an array"))?
+                .iter()
+                .map(|name| {
+                    name.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| error(&pointer, "required must only have strings"))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(additional) = object.get("additionalProperties") {
+            schema.additional_properties = match additional {
+                Value::Bool(true) => AdditionalProperties::Open,
+                Value::Bool(false) => AdditionalProperties::Closed,
+                additional => AdditionalProperties::Schema(Box::new(
+                    self.schema(additional, &format!("{}/additionalProperties", pointer))?,
+                )),
+            };
+        }
+        match object.get("items") {
+            Some(items @ Value::Array(_)) => {
+                schema
+                    .combinators
+                    .insert("items".to_string(), items.clone());
+            }
+            Some(items) => {
+                schema.items = Some(Box::new(self.schema(items, &format!("{}/items", pointer))?))
+            }
+            None => {}
+        }
+        if let Some(values) = object.get("enum") {
+            schema.values = Some(
+                values
+                    .as_array()
+                    .ok_or_else(|| error(&format!("{}/enum", pointer), "enum must be an array"))?
+                    .clone(),
+            );
+        }
+        if let Some(value) = object.get("const") {
+            schema.values = Some(vec![value.clone()]);
+        }
+        for keyword in COMBINATORS.iter() {
+            if let Some(value) = object.get(*keyword) {
+                schema
+                    .combinators
+                    .insert(keyword.to_string(), value.clone());
+            }
+        }
+        Ok(schema)
+    }
+
+    fn reference(
+        &mut self,
+        reference: &Value,
+        pointer: &str,
+    ) -> Result<JsonSchema, JsonSchemaError> {
+        let pointer = format!("{}/$ref", pointer);
+        let reference = reference
+            .as_str()
+            .ok_or_else(|| error(&pointer, "$ref must be a string"))?;
+        let local = match reference.strip_prefix('#') {
+            Some(local) if !self.stack.iter().any(|seen| seen == reference) => local,
+            Some(_) => {
+                self.recursive.insert(reference.to_string());
+                return Ok(JsonSchema {
+                    reference: Some(reference.to_string()),
+                    ..JsonSchema::default()
+                });
+            }
+            None => {
+                return Ok(JsonSchema {
+                    reference: Some(reference.to_string()),
+                    ..JsonSchema::default()
+                })
+            }
+        };
+        let target = self
+            .root
+            .pointer(local)
+            .ok_or_else(|| error(&pointer, &format!("{} can't be resolved", reference)))?;
+        self.stack.push(reference.to_string());
+        let schema = self.schema(target, local);
+        self.stack.pop();
+        schema
+    }
+
+    fn types(&self, types: &Value, pointer: &str) -> Result<BTreeSet<JsonType>, JsonSchemaError> {
+        let parse = |name: &Value| {
+            name.as_str()
+                .and_then(|name| name.parse::<JsonType>().ok())
+                .ok_or_else(|| error(pointer, &format!("{} is not a type", name)))
+        };
+        match types {
+            Value::Array(types) => types.iter().map(parse).collect(),
+            name => Ok([parse(name)?].into_iter().collect()),
+        }
+    }
+}
+
+fn as_object<'a>(
+    value: &'a Value,
+    pointer: &str,
+) -> Result<&'a Map<String, Value>, JsonSchemaError> {
+    value
+        .as_object()
+        .ok_or_else(|| error(pointer, "properties must be an object"))
+}
+
+fn error(pointer: &str, message: &str) -> JsonSchemaError {
+    JsonSchemaError {
+        pointer: if pointer.is_empty() {
+            "/".to_string()
+        } else {
+            pointer.to_string()
+        },
+        message: message.to_string(),
+    }
+}
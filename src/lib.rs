@@ -72,14 +72,40 @@
 //! ```
 
 pub mod compat;
+pub mod data;
+pub mod diff;
 pub mod errors;
+pub mod explain;
+pub mod fingerprint;
+pub mod fmt;
+pub mod format;
+pub mod fuzz;
+pub mod git;
+pub mod idl;
+pub mod json_schema;
+pub mod lint;
+pub mod manifest;
+pub mod plan;
+pub mod protobuf;
+pub mod report;
 pub mod schema;
 pub mod table;
 
 pub mod schema_registry;
 pub mod prelude {
     pub use crate::compat::*;
+    pub use crate::data::*;
+    pub use crate::diff::*;
     pub use crate::errors::*;
+    pub use crate::explain::*;
+    pub use crate::fingerprint::*;
+    pub use crate::fmt::*;
+    pub use crate::format::*;
+    pub use crate::fuzz::*;
+    pub use crate::lint::*;
+    pub use crate::manifest::*;
+    pub use crate::plan::*;
+    pub use crate::report::*;
     pub use crate::schema::*;
     pub use crate::schema_registry::types::*;
     pub use crate::schema_registry::*;
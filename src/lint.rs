@@ -0,0 +1,427 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Linting of schemas against conventions, e.g. documented records and snake_case fields.
+//!
+//! Schemas are checked as they are written, so attributes the parser drops, like the default
+//! symbol of enums or where namespaces are declared, can be checked too.
+//! Every [`LintRule`] can be disabled or given another severity in the `[lint]` table of the manifest:
+//!
+//! ```toml
+//! [lint]
+//! money_words = ["price", "amount"]
+//!
+//! [lint.rules.field_doc]
+//! enabled = false
+//!
+//! [lint.rules.namespace]
+//! severity = "error"
+//! ```
+//!
+//! ```rust
+//! use degauss::prelude::*;
+//!
+//! let schema = serde_json::json!({"type": "record", "name": "movie", "fields": []});
+//! let findings = Linter::new(LintConfig::default()).lint(&schema);
+//! assert!(findings.iter().any(|finding| finding.rule == "type_naming"));
+//! ```
+//!
+
+use crate::errors::DegaussError;
+use crate::manifest::Manifest;
+use crate::report::{Finding, ReportCase, Severity};
+use crate::schema::namespace_of;
+use serde::{de, Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use strum::VariantNames;
+use strum_macros::{Display, EnumIter, EnumString, EnumVariantNames};
+
+/// Words of field names which mark amounts of money, when not configured
+const MONEY_WORDS: [&str; 10] = [
+    "amount", "balance", "cost", "fee", "money", "payment", "price", "salary", "tax", "total",
+];
+
+/// Conventions schemas are checked against
+#[derive(
+    EnumIter, EnumVariantNames, EnumString, Display, Clone, Copy, PartialEq, Eq, Hash, Debug,
+)]
+pub enum LintRule {
+    /// Records have a doc
+    #[strum(serialize = "record_doc")]
+    RecordDoc,
+    /// Fields of records have a doc
+    #[strum(serialize = "field_doc")]
+    FieldDoc,
+    /// Fields are named in snake_case
+    #[strum(serialize = "field_naming")]
+    FieldNaming,
+    /// Records, enums and fixed types are named in PascalCase
+    #[strum(serialize = "type_naming")]
+    TypeNaming,
+    /// Named types have a namespace
+    #[strum(serialize = "namespace")]
+    Namespace,
+    /// Nullable fields have `null` as their first branch and default
+    #[strum(serialize = "nullable_default")]
+    NullableDefault,
+    /// Amounts of money aren't `float` or `double`
+    #[strum(serialize = "float_money")]
+    FloatMoney,
+    /// Enums have a default symbol, so readers can read symbols added later
+    #[strum(serialize = "enum_default")]
+    EnumDefault,
+}
+
+impl LintRule {
+    /// Severity of the findings of the rule, when not configured
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            LintRule::FieldDoc => Severity::Note,
+            LintRule::FloatMoney => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+// Parsed with the names of strum, map keys of TOML can't be deserialized as enums.
+impl<'de> Deserialize<'de> for LintRule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse()
+            .map_err(|_| de::Error::unknown_variant(&name, LintRule::VARIANTS))
+    }
+}
+
+/// Settings of a single rule
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Severity of the findings, the default one of the rule when not given.
+    pub severity: Option<Severity>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Settings of the linting, all rules are enabled with their default severity unless configured
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    #[serde(default)]
+    pub rules: HashMap<LintRule, RuleConfig>,
+    /// Words of field names which mark amounts of money, a default list is used when not given.
+    pub money_words: Option<Vec<String>>,
+}
+
+impl LintConfig {
+    /// Read the settings from the `lint` table of a manifest file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, DegaussError> {
+        Ok(Manifest::from_file(path)?.lint)
+    }
+
+    /// Severity of the findings of a rule, nothing when it is disabled
+    pub fn severity(&self, rule: LintRule) -> Option<Severity> {
+        match self.rules.get(&rule) {
+            Some(config) if !config.enabled => None,
+            Some(config) => Some(config.severity.unwrap_or_else(|| rule.default_severity())),
+            None => Some(rule.default_severity()),
+        }
+    }
+}
+
+/// Checks schemas against the configured rules
+#[derive(Clone, Debug)]
+pub struct Linter {
+    config: LintConfig,
+}
+
+impl Linter {
+    pub fn new(config: LintConfig) -> Self {
+        Linter { config }
+    }
+
+    /// Findings of the enabled rules in the JSON definition of a schema
+    pub fn lint(&self, schema: &Value) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        self.schema(schema, None, "", &mut findings);
+        findings
+    }
+
+    /// Lint a schema into a report case, which fails when there are findings with the error severity
+    pub fn report(&self, schema: &Value, source: &str) -> ReportCase {
+        let findings = self
+            .lint(schema)
+            .into_iter()
+            .map(|finding| Finding {
+                file: Some(source.to_string()),
+                ..finding
+            })
+            .collect::<Vec<_>>();
+        ReportCase {
+            name: source.to_string(),
+            compatibility: None,
+            passed: findings
+                .iter()
+                .all(|finding| finding.severity != Severity::Error),
+            findings,
+        }
+    }
+
+    fn schema(
+        &self,
+        schema: &Value,
+        namespace: Option<&str>,
+        path: &str,
+        findings: &mut Vec<Finding>,
+    ) {
+        match schema {
+            Value::Array(union) => {
+                for branch in union {
+                    self.schema(branch, namespace, path, findings);
+                }
+            }
+            Value::Object(object) => match object.get("type") {
+                Some(Value::String(kind)) => match kind.as_str() {
+                    "record" | "error" | "enum" | "fixed" => {
+                        self.named(object, kind, namespace, path, findings)
+                    }
+                    "array" => {
+                        if let Some(items) = object.get("items") {
+                            self.schema(items, namespace, &format!("{}/items", path), findings);
+                        }
+                    }
+                    "map" => {
+                        if let Some(values) = object.get("values") {
+                            self.schema(values, namespace, &format!("{}/values", path), findings);
+                        }
+                    }
+                    _ => {}
+                },
+                Some(kind) => self.schema(kind, namespace, path, findings),
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn named(
+        &self,
+        definition: &Map<String, Value>,
+        kind: &str,
+        enclosing: Option<&str>,
+        path: &str,
+        findings: &mut Vec<Finding>,
+    ) {
+        let full = definition.get("name").and_then(Value::as_str).unwrap_or("");
+        let name = full.rsplit('.').next().unwrap_or(full);
+        let namespace = namespace_of(definition, enclosing).filter(|ns| !ns.is_empty());
+        let at = if path.is_empty() { "/" } else { path };
+
+        if !is_pascal_case(name) {
+            self.find(
+                LintRule::TypeNaming,
+                format!("{} {} is not named in PascalCase", kind, name),
+                at,
+                findings,
+            );
+        }
+        if namespace.is_none() {
+            self.find(
+                LintRule::Namespace,
+                format!("{} {} has no namespace", kind, name),
+                at,
+                findings,
+            );
+        }
+        match kind {
+            "record" | "error" => {
+                if !has_doc(definition) {
+                    self.find(
+                        LintRule::RecordDoc,
+                        format!("{} {} has no doc", kind, name),
+                        at,
+                        findings,
+                    );
+                }
+                let fields = definition.get("fields").and_then(Value::as_array);
+                for field in fields.into_iter().flatten().filter_map(Value::as_object) {
+                    self.field(field, namespace.as_deref(), path, findings);
+                }
+            }
+            "enum" if !definition.contains_key("default") => self.find(
+                LintRule::EnumDefault,
+                format!("enum {} has no default symbol", name),
+                at,
+                findings,
+            ),
+            _ => {}
+        }
+    }
+
+    fn field(
+        &self,
+        field: &Map<String, Value>,
+        namespace: Option<&str>,
+        path: &str,
+        findings: &mut Vec<Finding>,
+    ) {
+        let name = field.get("name").and_then(Value::as_str).unwrap_or("");
+        let path = format!("{}/fields/{}", path, name);
+        let schema = field.get("type").unwrap_or(&Value::Null);
+
+        if !is_snake_case(name) {
+            self.find(
+                LintRule::FieldNaming,
+                format!("field {} is not named in snake_case", name),
+                &path,
+                findings,
+            );
+        }
+        if !has_doc(field) {
+            self.find(
+                LintRule::FieldDoc,
+                format!("field {} has no doc", name),
+                &path,
+                findings,
+            );
+        }
+        if let Value::Array(union) = schema {
+            if union.iter().any(|branch| type_name(branch) == Some("null")) {
+                if type_name(&union[0]) != Some("null") {
+                    self.find(
+                        LintRule::NullableDefault,
+                        format!(
+                            "nullable field {} doesn't have null as its first branch",
+                            name
+                        ),
+                        &path,
+                        findings,
+                    );
+                } else if field.get("default") != Some(&Value::Null) {
+                    self.find(
+                        LintRule::NullableDefault,
+                        format!("nullable field {} doesn't default to null", name),
+                        &path,
+                        findings,
+                    );
+                }
+            }
+        }
+        let branches = match schema {
+            Value::Array(union) => union.iter().collect::<Vec<_>>(),
+            schema => vec![schema],
+        };
+        if let Some(float) = branches
+            .into_iter()
+            .filter_map(type_name)
+            .find(|kind| *kind == "float" || *kind == "double")
+        {
+            if self.is_money(name) {
+                self.find(
+                    LintRule::FloatMoney,
+                    format!(
+                        "field {} looks like money but is a {}, use a decimal or an integer of the smallest unit",
+                        name, float
+                    ),
+                    &path,
+                    findings,
+                );
+            }
+        }
+        self.schema(schema, namespace, &path, findings);
+    }
+
+    fn is_money(&self, field: &str) -> bool {
+        let words = words(field);
+        match &self.config.money_words {
+            Some(money) => money
+                .iter()
+                .any(|word| words.contains(&word.to_lowercase())),
+            None => MONEY_WORDS
+                .iter()
+                .any(|word| words.iter().any(|w| w == word)),
+        }
+    }
+
+    fn find(&self, rule: LintRule, message: String, path: &str, findings: &mut Vec<Finding>) {
+        if let Some(severity) = self.config.severity(rule) {
+            findings.push(Finding {
+                rule: rule.to_string(),
+                severity,
+                message,
+                file: None,
+                path: Some(path.to_string()),
+            });
+        }
+    }
+}
+
+fn has_doc(definition: &Map<String, Value>) -> bool {
+    definition
+        .get("doc")
+        .and_then(Value::as_str)
+        .is_some_and(|doc| !doc.trim().is_empty())
+}
+
+/// Name of a type, without looking into unions
+fn type_name(schema: &Value) -> Option<&str> {
+    match schema {
+        Value::String(name) => Some(name),
+        Value::Object(object) => object.get("type").and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+fn is_snake_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !name.contains("__")
+        && !name.ends_with('_')
+}
+
+fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Lowercase words of a snake_case or camelCase name
+fn words(name: &str) -> Vec<String> {
+    let mut words = vec![String::new()];
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() || c.is_ascii_uppercase() {
+            words.push(String::new());
+        }
+        if c.is_ascii_alphanumeric() {
+            words.last_mut().unwrap().push(c.to_ascii_lowercase());
+        }
+    }
+    words.retain(|word| !word.is_empty());
+    words
+}
@@ -22,12 +22,30 @@
 
 mod status;
 use apache_avro::Schema;
-use degauss::compat::{DegaussCheck, DegaussCompatMode};
-use degauss::prelude::{Auth, SchemaRegistryClient, SchemaSubjectType, SerdeExt};
-use degauss::schema::FromFile;
+use degauss::compat::{CompatMatrix, DegaussCheck, DegaussCompatMode};
+use degauss::data::verify_data_file;
+use degauss::diff::diff;
+use degauss::errors::DegaussError;
+use degauss::fmt::{format_schema, FormatConfig, NameStyle};
+use degauss::format::DegaussSchema;
+use degauss::format::Incompatibility;
+use degauss::fuzz::{fuzz, FuzzConfig};
+use degauss::git;
+use degauss::json_schema::JsonSchema;
+use degauss::lint::{LintConfig, Linter};
+use degauss::manifest::{Manifest, MANIFEST_FILE};
+use degauss::plan::Plan;
+use degauss::prelude::{
+    Auth, SchemaCompatibleResponse, SchemaPayload, SchemaRegistryClient, SchemaSubjectType,
+    SchemaType, SchemaVersion, SerdeExt, SubjectName, SubjectNameStrategy, SUBJECT_NOT_FOUND,
+};
+use degauss::protobuf::ProtoFile;
+use degauss::report::{Finding, Report, ReportCase, ReportFormat, Severity};
+use degauss::schema::{find_schema_files, SchemaDependencies, SchemaFileFormat};
 use degauss::table;
 use status::Status;
-use std::{panic, path::PathBuf};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 use strum::VariantNames;
 
@@ -38,24 +56,489 @@ const AUTHORS: &str = env!("CARGO_PKG_AUTHORS");
 #[structopt(name = "degauss",  version = VERSION, author = AUTHORS)]
 /// Kafka schema compatibility checker
 struct Degauss {
-    /// Print the exit status
-    #[structopt(short, long, global = true)]
+    /// Removed, the process always exits with its status
+    #[structopt(short, long, global = true, hidden = true)]
     exit_status: bool,
 
     #[structopt(subcommand)]
     cmd: SubCommand,
 }
 
+#[derive(StructOpt, Debug, Clone)]
+/// Options to read schema files and the named types they refer to
+struct InputOpts {
+    /// Schema files, or directories of them, defining the named types the schemas refer to
+    #[structopt(short, long, parse(from_os_str))]
+    dependencies: Vec<PathBuf>,
+
+    /// Format of the schema files, taken from their extensions when not given
+    #[structopt(long, possible_values = SchemaFileFormat::VARIANTS, case_insensitive = true)]
+    format: Option<SchemaFileFormat>,
+
+    /// Full or short name of the type to take from protocols, the last defined one when not given
+    #[structopt(long)]
+    protocol_type: Option<String>,
+}
+
+impl InputOpts {
+    fn load(&self) -> Result<SchemaDependencies, DegaussError> {
+        SchemaDependencies::load(&self.dependencies)
+    }
+
+    fn parse_file(
+        &self,
+        dependencies: &SchemaDependencies,
+        path: &Path,
+    ) -> Result<Schema, DegaussError> {
+        dependencies.parse_file_as(path, self.format, self.protocol_type.as_deref())
+    }
+
+    fn schema_type(&self, path: &Path, text: &str) -> SchemaType {
+        self.format
+            .unwrap_or_else(|| SchemaFileFormat::detect(path, text))
+            .schema_type()
+    }
+
+    /// Parse a schema file in the schema language of its format
+    fn parse_schema_file(
+        &self,
+        dependencies: &SchemaDependencies,
+        path: &Path,
+    ) -> Result<SchemaFile, DegaussError> {
+        let text = std::fs::read_to_string(path).map_err(|source| DegaussError::FileRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        match self.schema_type(path, &text) {
+            SchemaType::Avro => Ok(SchemaFile::Avro(dependencies.parse_str_as(
+                path,
+                &text,
+                self.format,
+                self.protocol_type.as_deref(),
+            )?)),
+            schema_type => SchemaFile::parse_text(path, schema_type, text),
+        }
+    }
+
+    fn parse_history(
+        &self,
+        dependencies: &SchemaDependencies,
+        paths: &[PathBuf],
+    ) -> Result<Box<dyn History>, DegaussError> {
+        <dyn History>::new(
+            paths
+                .iter()
+                .map(|path| self.parse_schema_file(dependencies, path))
+                .collect::<Result<Vec<_>, DegaussError>>()?,
+        )
+    }
+
+    /// Parse a committed revision of a schema file
+    fn parse_revision(
+        &self,
+        dependencies: &SchemaDependencies,
+        path: &Path,
+        revision: &git::Revision,
+    ) -> Result<SchemaFile, DegaussError> {
+        let schema_type = self.schema_type(path, &revision.contents);
+        if schema_type != SchemaType::Avro {
+            return SchemaFile::parse_text(
+                Path::new(&revision.source(path)),
+                schema_type,
+                revision.contents.clone(),
+            );
+        }
+        dependencies
+            .parse_str_as(
+                path,
+                &revision.contents,
+                self.format,
+                self.protocol_type.as_deref(),
+            )
+            .map_err(|e| match e {
+                DegaussError::SchemaFile { source, .. } => DegaussError::SchemaFile {
+                    path: PathBuf::from(revision.source(path)),
+                    source,
+                },
+                e => e,
+            })
+            .map(SchemaFile::Avro)
+    }
+
+    /// Definition of the schema file as written, with the dependencies it may refer to
+    fn resolve_file(
+        &self,
+        dependencies: &SchemaDependencies,
+        path: &Path,
+    ) -> Result<(serde_json::Value, SchemaDependencies), DegaussError> {
+        dependencies.resolve_file(path, self.format, self.protocol_type.as_deref())
+    }
+}
+
+/// Schema file parsed in the schema language of its format
+enum SchemaFile {
+    Avro(Schema),
+    /// Parsed file with its text, which is what schema registry takes
+    Protobuf(ProtoFile, String),
+    /// Parsed schema with its text, which is what schema registry takes
+    Json(JsonSchema, String),
+}
+
+impl SchemaFile {
+    /// Parse the text of a schema in the given schema language
+    fn parse_text(
+        path: &Path,
+        schema_type: SchemaType,
+        text: String,
+    ) -> Result<Self, DegaussError> {
+        match schema_type {
+            SchemaType::Avro => Ok(SchemaFile::Avro(
+                SchemaDependencies::new().parse_str_as(path, &text, None, None)?,
+            )),
+            SchemaType::Protobuf => Ok(SchemaFile::Protobuf(
+                ProtoFile::parse_str_at(path, &text)?,
+                text,
+            )),
+            SchemaType::Json => Ok(SchemaFile::Json(
+                JsonSchema::parse_str_at(path, &text)?,
+                text,
+            )),
+            SchemaType::Other(other) => Err(DegaussError::Usage(format!(
+                "{} is a {} schema, which isn't supported",
+                path.display(),
+                other
+            ))),
+        }
+    }
+
+    fn schema(&self) -> &dyn DegaussSchema {
+        match self {
+            SchemaFile::Avro(schema) => schema,
+            SchemaFile::Protobuf(proto, _) => proto,
+            SchemaFile::Json(json, _) => json,
+        }
+    }
+
+    /// Schemas of the files parsed in one schema language, the others are left out
+    fn collect<S: DegaussSchema>(files: Vec<Self>, schema: fn(Self) -> Option<S>) -> Vec<S> {
+        files.into_iter().filter_map(schema).collect()
+    }
+
+    /// Payload of the schema, Protobuf and JSON Schema files are sent as they are written
+    fn payload(&self) -> SchemaPayload {
+        match self {
+            SchemaFile::Avro(schema) => schema.payload(),
+            SchemaFile::Protobuf(_, text) | SchemaFile::Json(_, text) => {
+                SchemaPayload::typed(text.as_str(), self.schema().schema_type())
+            }
+        }
+    }
+}
+
+/// Versions of a schema from oldest to newest, all written in the same schema language
+trait History {
+    fn len(&self) -> usize;
+    fn explain(&self, compatibility: DegaussCompatMode) -> Vec<Incompatibility>;
+    fn report(&self, compatibility: DegaussCompatMode, sources: &[String]) -> ReportCase;
+    fn tabular_validate_all(&self) -> HashMap<DegaussCompatMode, bool>;
+    fn pairwise(&self) -> CompatMatrix;
+}
+
+impl<S: DegaussSchema> History for Vec<S> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn explain(&self, compatibility: DegaussCompatMode) -> Vec<Incompatibility> {
+        DegaussCheck(compatibility).explain(self)
+    }
+
+    fn report(&self, compatibility: DegaussCompatMode, sources: &[String]) -> ReportCase {
+        DegaussCheck(compatibility).report(self, sources)
+    }
+
+    fn tabular_validate_all(&self) -> HashMap<DegaussCompatMode, bool> {
+        DegaussCheck::tabular_validate_all(self)
+    }
+
+    fn pairwise(&self) -> CompatMatrix {
+        DegaussCheck::pairwise(self)
+    }
+}
+
+impl dyn History {
+    fn new(files: Vec<SchemaFile>) -> Result<Box<Self>, DegaussError> {
+        let schema_type = files
+            .first()
+            .map_or(SchemaType::Avro, |file| file.schema().schema_type());
+        if let Some(other) = files
+            .iter()
+            .find(|file| file.schema().schema_type() != schema_type)
+        {
+            return Err(DegaussError::Usage(format!(
+                "{} and {} schemas can't be compared with each other",
+                schema_type,
+                other.schema().schema_type()
+            )));
+        }
+        Ok(match schema_type {
+            SchemaType::Protobuf => Box::new(SchemaFile::collect(files, |file| match file {
+                SchemaFile::Protobuf(proto, _) => Some(proto),
+                _ => None,
+            })),
+            SchemaType::Json => Box::new(SchemaFile::collect(files, |file| match file {
+                SchemaFile::Json(json, _) => Some(json),
+                _ => None,
+            })),
+            _ => Box::new(SchemaFile::collect(files, |file| match file {
+                SchemaFile::Avro(schema) => Some(schema),
+                _ => None,
+            })),
+        })
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Options to send the dependencies of a schema as schema registry references
+struct ReferenceOpts {
+    /// Send the schema as written, referencing the subjects its dependencies are registered under,
+    /// instead of inlining the dependencies
+    #[structopt(long)]
+    references: bool,
+
+    /// Strategy to derive the subjects of the referenced dependencies with
+    #[structopt(long, possible_values = SubjectNameStrategy::VARIANTS, default_value = "record_name", case_insensitive = true)]
+    reference_subject_name_strategy: SubjectNameStrategy,
+}
+
+impl ReferenceOpts {
+    /// Payload of the schema file on its own, references are only sent for Avro schemas
+    fn file_payload(&self, file: &SchemaFile) -> Result<SchemaPayload, DegaussError> {
+        if self.references {
+            return Err(DegaussError::Usage(
+                "References are only supported for Avro schemas".to_string(),
+            ));
+        }
+        Ok(file.payload())
+    }
+
+    /// Build the payload of the schema file, looking up the versions of the referenced dependencies
+    fn payload(
+        &self,
+        client: &SchemaRegistryClient,
+        input: &InputOpts,
+        dependencies: &SchemaDependencies,
+        path: &Path,
+        subject: &SubjectOpts,
+        register: bool,
+    ) -> Result<SchemaPayload, DegaussError> {
+        let (schema, dependencies) = input.resolve_file(dependencies, path)?;
+        let schema = schema.to_string();
+        let topic = match self.reference_subject_name_strategy {
+            SubjectNameStrategy::TopicName => {
+                return Err(DegaussError::Usage(
+                    "Referenced dependencies can't share the subject of a topic, use the record_name or topic_record_name reference strategy"
+                        .to_string(),
+                ))
+            }
+            SubjectNameStrategy::RecordName => None,
+            SubjectNameStrategy::TopicRecordName => Some(subject.topic.clone().ok_or_else(|| {
+                DegaussError::Usage(
+                    "Reference strategy topic_record_name needs a topic, use --topic".to_string(),
+                )
+            })?),
+        };
+        let references = client.resolve_references(
+            &dependencies,
+            &schema,
+            |name| match &topic {
+                Some(topic) => SubjectName::TopicRecordName {
+                    topic: topic.clone(),
+                    record_name: Some(name.to_string()),
+                },
+                None => SubjectName::RecordName {
+                    record_name: Some(name.to_string()),
+                },
+            },
+            register,
+        )?;
+        Ok(SchemaPayload {
+            schema,
+            schema_type: None,
+            references,
+        })
+    }
+}
+
 #[derive(StructOpt, Debug)]
 /// Options to set during the interaction with compatibility
 struct ValidateOpts {
     /// All schemas in chronological order. From oldest to newest.
-    #[structopt(short, long, parse(from_os_str))]
+    #[structopt(short, long, parse(from_os_str), required_unless = "git-history")]
     schemas: Vec<PathBuf>,
 
+    /// Schema file whose committed revisions, followed by the working copy when it changed, are the history
+    #[structopt(long, parse(from_os_str), conflicts_with = "schemas")]
+    git_history: Option<PathBuf>,
+
+    /// Start the history with the revision of the file at this git revision
+    #[structopt(long, requires = "git-history")]
+    since: Option<String>,
+
+    /// Only compare the working copy to the revision of the file at this git revision
+    #[structopt(long, requires = "git-history", conflicts_with = "since")]
+    against: Option<String>,
+
     /// Compat Mode to check against
-    #[structopt(short, long, possible_values = DegaussCompatMode::VARIANTS, case_insensitive = true,)]
-    compat: DegaussCompatMode,
+    #[structopt(short, long, possible_values = DegaussCompatMode::VARIANTS, case_insensitive = true, required_unless_one = &["detect", "matrix"])]
+    compat: Option<DegaussCompatMode>,
+
+    /// Check every compat mode and report the strongest ones the schemas satisfy
+    #[structopt(long, conflicts_with = "compat")]
+    detect: bool,
+
+    /// Show whether every version can read the data written by every other version
+    #[structopt(long)]
+    matrix: bool,
+
+    /// Report format, prints tables when not given
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+}
+
+#[derive(StructOpt, Debug)]
+/// Options to set during the diff of two schemas
+struct DiffOpts {
+    /// Older schema
+    #[structopt(parse(from_os_str))]
+    old: PathBuf,
+
+    /// Newer schema
+    #[structopt(parse(from_os_str))]
+    new: PathBuf,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+}
+
+#[derive(StructOpt, Debug)]
+/// Options to verify data files against a reader schema
+struct VerifyDataOpts {
+    /// Reader schema
+    #[structopt(short, long, parse(from_os_str))]
+    reader: PathBuf,
+
+    /// Avro object container files, written with any of the older schemas
+    #[structopt(parse(from_os_str), required = true)]
+    data: Vec<PathBuf>,
+
+    /// Report format, prints a table when not given
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+}
+
+#[derive(StructOpt, Debug)]
+/// Options to fuzz a writer and reader schema pair
+struct FuzzOpts {
+    /// Schema the datums are written with
+    #[structopt(short, long, parse(from_os_str))]
+    writer: PathBuf,
+
+    /// Schema the datums are read with
+    #[structopt(short, long, parse(from_os_str))]
+    reader: PathBuf,
+
+    /// Number of datums to generate
+    #[structopt(short, long, default_value = "1000")]
+    iterations: usize,
+
+    /// Seed of the random datums, a random one is used when not given
+    #[structopt(long)]
+    seed: Option<u64>,
+
+    /// Depth after which recursive types and collections are kept as small as possible
+    #[structopt(long, default_value = "8")]
+    max_depth: usize,
+
+    /// Report format, prints a table when not given
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+}
+
+#[derive(StructOpt, Debug)]
+/// Options to fingerprint schemas
+struct FingerprintOpts {
+    /// Schemas to fingerprint
+    #[structopt(parse(from_os_str), required = true)]
+    schemas: Vec<PathBuf>,
+
+    /// Print the canonical forms and fingerprints as JSON
+    #[structopt(long)]
+    json: bool,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+}
+
+#[derive(StructOpt, Debug)]
+/// Options to format schema files
+struct FmtOpts {
+    /// Schema files, or directories of them, to format in place
+    #[structopt(parse(from_os_str), required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Only list the files which aren't formatted, and set the exit status if there are any
+    #[structopt(long)]
+    check: bool,
+
+    /// Write names fully qualified, or relative to their enclosing namespace. Kept as they are when not given
+    #[structopt(long, possible_values = NameStyle::VARIANTS, case_insensitive = true)]
+    names: Option<NameStyle>,
+
+    /// Number of spaces to indent with
+    #[structopt(long, default_value = "4")]
+    indent: usize,
+}
+
+#[derive(StructOpt, Debug)]
+/// Options to lint schema files
+struct LintOpts {
+    /// Schema files, or directories of them, to lint
+    #[structopt(parse(from_os_str), required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Manifest to configure the rules with, in its `lint` table. The degauss.toml of the working directory
+    /// is used when it exists, otherwise all rules are enabled
+    #[structopt(short, long, parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    /// Report format, prints a table when not given
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+}
+
+#[derive(StructOpt, Debug)]
+/// Options to check every subject of a manifest
+struct CheckAllOpts {
+    /// Manifest describing the subjects and their schema histories
+    #[structopt(short, long, parse(from_os_str), default_value = MANIFEST_FILE)]
+    manifest: PathBuf,
+
+    /// Report format, prints tables when not given
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -65,6 +548,186 @@ enum SRCommand {
 
     /// Set or Get compatibility for a given topic/subject
     Compatibility(Compatibility),
+
+    /// List, get or delete subjects and their versions
+    Subject(Subject),
+
+    /// Get schemas by their global id
+    Schema(SchemaCmd),
+
+    /// Check the registered history of every subject against its compatibility
+    Audit(AuditOpts),
+
+    /// Show what registering the schemas of a manifest and setting their compatibilities would change
+    Plan(PlanOpts),
+
+    /// Register the schemas of a manifest and set their compatibilities, when the plan is compatible
+    Apply(PlanOpts),
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Options to bring the subjects of a manifest to Kafka Schema Registry
+struct PlanOpts {
+    /// Manifest describing the subjects, their schema histories and compatibilities
+    #[structopt(short, long, parse(from_os_str), default_value = MANIFEST_FILE)]
+    manifest: PathBuf,
+
+    /// Report format of the plan, prints tables when not given
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Options to audit the subjects of Kafka Schema Registry
+struct AuditOpts {
+    /// Report format, prints tables when not given. The summary table still goes to stderr.
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Interact with subjects of Kafka Schema Registry
+struct Subject {
+    #[structopt(subcommand)]
+    cmd: SubjectCommand,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Interact with subjects of Kafka Schema Registry
+enum SubjectCommand {
+    /// List all subjects
+    List,
+
+    /// List the versions of a given topic/subject
+    Versions(SubjectOpts),
+
+    /// Get a version of a given topic/subject
+    Get(SubjectVersionOpts),
+
+    /// Delete a given topic/subject, or only one of its versions
+    Delete(SubjectDeleteOpts),
+
+    /// List the subject versions whose schemas reference a version of a given topic/subject
+    ReferencedBy(SubjectVersionOpts),
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Options to select a subject
+struct SubjectOpts {
+    /// Schema registry topic
+    #[structopt(long, env = "DEGAUSS_TOPIC")]
+    topic: Option<String>,
+
+    /// Schema registry subject type, used by the topic_name strategy
+    #[structopt(long, possible_values = SchemaSubjectType::VARIANTS, env = "DEGAUSS_SUBJECT_TYPE", case_insensitive = true)]
+    subject_type: Option<SchemaSubjectType>,
+
+    /// Strategy to derive the subject with
+    #[structopt(long, possible_values = SubjectNameStrategy::VARIANTS, default_value = "topic_name", env = "DEGAUSS_SUBJECT_NAME_STRATEGY", case_insensitive = true)]
+    subject_name_strategy: SubjectNameStrategy,
+
+    /// Fully qualified record name for the record name strategies, taken from the schema when not given
+    #[structopt(long, env = "DEGAUSS_RECORD_NAME")]
+    record_name: Option<String>,
+
+    /// Subject to use as is, overrides the subject name strategy
+    #[structopt(long, env = "DEGAUSS_SUBJECT")]
+    subject: Option<String>,
+}
+
+impl SubjectOpts {
+    fn subject_name(&self) -> Result<SubjectName, DegaussError> {
+        if let Some(subject) = &self.subject {
+            return Ok(SubjectName::Raw(subject.clone()));
+        }
+        let topic = || {
+            self.topic.clone().ok_or_else(|| {
+                DegaussError::Usage(format!(
+                    "Subject name strategy {} needs a topic, use --topic or --subject",
+                    self.subject_name_strategy
+                ))
+            })
+        };
+        Ok(match self.subject_name_strategy {
+            SubjectNameStrategy::TopicName => SubjectName::TopicName {
+                topic: topic()?,
+                subject_type: self.subject_type.ok_or_else(|| {
+                    DegaussError::Usage(
+                        "Subject name strategy topic_name needs a subject type, use --subject-type or --subject"
+                            .to_string(),
+                    )
+                })?,
+            },
+            SubjectNameStrategy::RecordName => SubjectName::RecordName {
+                record_name: self.record_name.clone(),
+            },
+            SubjectNameStrategy::TopicRecordName => SubjectName::TopicRecordName {
+                topic: topic()?,
+                record_name: self.record_name.clone(),
+            },
+        })
+    }
+
+    /// Resolve the subject once, so the schema's record name is used by every following request
+    fn resolve(&self, file: &SchemaFile) -> Result<SubjectName, DegaussError> {
+        Ok(SubjectName::Raw(
+            self.subject_name()?.resolve(Some(file.schema()))?,
+        ))
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Options to select a version of a subject
+struct SubjectVersionOpts {
+    #[structopt(flatten)]
+    subject: SubjectOpts,
+
+    /// Version number or `latest`
+    #[structopt(long, default_value = "latest")]
+    version: SchemaVersion,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Options to delete a subject or a version of it
+struct SubjectDeleteOpts {
+    #[structopt(flatten)]
+    subject: SubjectOpts,
+
+    /// Version number or `latest` to delete, deletes the whole subject when not given
+    #[structopt(long)]
+    version: Option<SchemaVersion>,
+
+    /// Delete permanently, only possible after a soft delete
+    #[structopt(long)]
+    permanent: bool,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Interact with schemas of Kafka Schema Registry
+struct SchemaCmd {
+    #[structopt(subcommand)]
+    cmd: SchemaCommand,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Interact with schemas of Kafka Schema Registry
+enum SchemaCommand {
+    /// Get the schema with the given global id
+    Get(SchemaIdOpts),
+
+    /// List the subjects the schema with the given global id is registered under
+    Subjects(SchemaIdOpts),
+
+    /// List the subject-version pairs the schema with the given global id is registered as
+    Versions(SchemaIdOpts),
+}
+
+#[derive(StructOpt, Debug, Clone)]
+/// Options to select a schema
+struct SchemaIdOpts {
+    /// Global id of the schema
+    #[structopt(long)]
+    id: i32,
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -90,6 +753,27 @@ enum SubCommand {
     /// Validate the compatibility
     Validate(ValidateOpts),
 
+    /// Show the semantic changes between two schemas
+    Diff(DiffOpts),
+
+    /// Read existing Avro data files with a reader schema
+    VerifyData(VerifyDataOpts),
+
+    /// Read random datums of the writer with the reader and compare with the static check
+    Fuzz(FuzzOpts),
+
+    /// Print the Parsing Canonical Form and the fingerprints of schemas
+    Fingerprint(FingerprintOpts),
+
+    /// Rewrite schema files into a stable normalized layout
+    Fmt(FmtOpts),
+
+    /// Check schema files against conventions like docs, naming and namespaces
+    Lint(LintOpts),
+
+    /// Validate the schema history of every subject of a manifest
+    CheckAll(CheckAllOpts),
+
     /// Interact with Schema Registry
     SchemaRegistry(SchemaRegistry),
 }
@@ -117,148 +801,769 @@ enum CompatibilityCommand {
 #[derive(StructOpt, Debug, Clone)]
 /// Interact with Kafka Schema Registry
 struct RegisterOpts {
-    /// Schema registry username
-    #[structopt(long, env = "DEGAUSS_TOPIC")]
-    topic: String,
-
-    /// Schema registry password
-    #[structopt(long, possible_values = SchemaSubjectType::VARIANTS, env = "DEGAUSS_SUBJECT_TYPE", case_insensitive = true)]
-    subject_type: SchemaSubjectType,
+    #[structopt(flatten)]
+    subject: SubjectOpts,
 
     /// Absolute path to the schema file to register
     #[structopt(short, long, parse(from_os_str), env = "DEGAUSS_SCHEMA_PATH")]
     schema_path: PathBuf,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+
+    #[structopt(flatten)]
+    references: ReferenceOpts,
+
+    /// Register the referenced dependencies under their subjects first
+    #[structopt(long, requires = "references")]
+    register_references: bool,
 }
 
+#[derive(StructOpt, Debug, Clone)]
 /// Check Schema Registry schema compatibility
-type CheckOpts = RegisterOpts;
+struct CheckOpts {
+    #[structopt(flatten)]
+    subject: SubjectOpts,
+
+    /// Absolute path to the schema file to check
+    #[structopt(short, long, parse(from_os_str), env = "DEGAUSS_SCHEMA_PATH")]
+    schema_path: PathBuf,
+
+    /// Report format, prints the registry response when not given
+    #[structopt(short, long, possible_values = ReportFormat::VARIANTS, case_insensitive = true)]
+    output: Option<ReportFormat>,
+
+    /// Check locally against all the registered versions with the configured compatibility,
+    /// and compare it with the verdict of the registry
+    #[structopt(short, long)]
+    local: bool,
+
+    #[structopt(flatten)]
+    input: InputOpts,
+
+    #[structopt(flatten)]
+    references: ReferenceOpts,
+}
 
 #[derive(StructOpt, Debug, Clone)]
 /// Options to set during the interaction with compatibility
 struct CompatibilityOpts {
-    /// Schema registry topic
-    #[structopt(long, env = "DEGAUSS_TOPIC")]
-    topic: String,
-
-    /// Schema registry subject type
-    #[structopt(long, possible_values = SchemaSubjectType::VARIANTS, env = "DEGAUSS_SUBJECT_TYPE", case_insensitive = true)]
-    subject_type: SchemaSubjectType,
+    #[structopt(flatten)]
+    subject: SubjectOpts,
 
     /// Compatibility to set, not needed with Get commands
     #[structopt(short, long, possible_values = DegaussCompatMode::VARIANTS,env = "DEGAUSS_COMPATIBILITY", case_insensitive = true,)]
     compatibility: Option<DegaussCompatMode>,
 }
 
-fn process_validate(schemas: Vec<PathBuf>, compatibility: DegaussCompatMode) -> bool {
-    let schemas = schemas
-        .iter()
-        .map(|e| Schema::parse_file(e).unwrap_or_else(|op| panic!("Failed to find file {:#?}", op)))
-        .collect::<Vec<Schema>>();
-
-    match (schemas.len(), compatibility) {
-        (1, _) => panic!("There is nothing to compare against. Exiting."),
-        (2, DegaussCompatMode::Backward | DegaussCompatMode::Forward | DegaussCompatMode::Full) => {
-            let dc = DegaussCheck(compatibility);
-            let compatibility = dc.tabular_validate(&schemas);
+fn process_validate(
+    history: &dyn History,
+    sources: &[String],
+    compatibility: DegaussCompatMode,
+    output: Option<ReportFormat>,
+    matrix: Option<CompatMatrix>,
+) -> Result<Status, DegaussError> {
+    match (history.len(), compatibility) {
+        (0 | 1, _) => return Err(DegaussError::Usage("There is nothing to compare against.".to_string())),
+        (2, DegaussCompatMode::Backward | DegaussCompatMode::Forward | DegaussCompatMode::Full) => (),
+        (sl, DegaussCompatMode::None | DegaussCompatMode::BackwardTransitive | DegaussCompatMode::ForwardTransitive | DegaussCompatMode::FullTransitive) if sl >= 2 => (),
+        (a, e) => return Err(DegaussError::Usage(format!("Schema count and compatibility check failure. {} compatibility and {} schemas are not comparable.", e, a)))
+    }
+
+    let valid = match output {
+        Some(format) => {
+            let mut report = Report::new(
+                "degauss validate",
+                vec![history.report(compatibility, sources)],
+            );
+            report.matrix = matrix;
+            println!("{}", report.render(format));
+            report.passed
+        }
+        None => {
+            let incompatibilities = history.explain(compatibility);
+            let compatibility = [(compatibility, incompatibilities.is_empty())]
+                .iter()
+                .cloned()
+                .collect();
             table::render(&compatibility);
-            return compatibility.values().all(|x| *x) ;
-        },
-        (sl, DegaussCompatMode::BackwardTransitive | DegaussCompatMode::ForwardTransitive | DegaussCompatMode::FullTransitive) if sl >= 2 => {
-            let dc = DegaussCheck(compatibility);
-            let compatibility = dc.tabular_validate(&schemas);
+            if !incompatibilities.is_empty() {
+                table::render_incompatibilities(&incompatibilities);
+            }
+            incompatibilities.is_empty()
+        }
+    };
+    Ok(if valid {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
+}
+
+fn process_detect(
+    history: &dyn History,
+    sources: &[String],
+    output: Option<ReportFormat>,
+    matrix: Option<CompatMatrix>,
+) -> Result<Status, DegaussError> {
+    if history.len() < 2 {
+        return Err(DegaussError::Usage(
+            "There is nothing to compare against.".to_string(),
+        ));
+    }
+
+    let compatibility = history.tabular_validate_all();
+    let strongest = DegaussCheck::strongest_of(&compatibility)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    match output {
+        Some(format) => {
+            let mut modes = compatibility.keys().collect::<Vec<_>>();
+            modes.sort();
+            let cases = modes
+                .into_iter()
+                .map(|mode| history.report(*mode, sources))
+                .collect();
+            let mut report = Report::new(format!("strongest compatibility: {}", strongest), cases);
+            report.matrix = matrix;
+            println!("{}", report.render(format));
+        }
+        None => {
             table::render(&compatibility);
-            return compatibility.values().all(|x| *x) ;
+            println!("Strongest compatibility: {}", strongest);
         }
-        (a, e) => panic!("Schema count and compatibility check failure. {} compatibility and {} schemas are not comparable.", e, a)
     }
+    Ok(Status::Success)
 }
 
-fn process_check(client: SchemaRegistryClient, opts: CheckOpts) -> Status {
-    let schema = Schema::parse_file(&opts.schema_path).expect("Failed to find path");
-    match client.check_compatibility(&schema, &opts.topic, opts.subject_type, true) {
-        Ok(compat) => {
-            println!("{}", compat.pretty_string());
-            if !compat.is_compatible {
-                Status::Failure
-            } else {
-                Status::Success
+/// Compute the matrix of the history for the report, a table of it is printed right away without a report format
+fn process_matrix(
+    history: &dyn History,
+    output: Option<ReportFormat>,
+) -> Result<Option<CompatMatrix>, DegaussError> {
+    let matrix = history.pairwise();
+    match output {
+        Some(ReportFormat::Json | ReportFormat::Markdown) => Ok(Some(matrix)),
+        Some(format) => Err(DegaussError::Usage(format!(
+            "The compatibility matrix can't be reported as {}, use json or markdown.",
+            format
+        ))),
+        None => {
+            table::render_matrix(&matrix);
+            Ok(None)
+        }
+    }
+}
+
+fn process_validate_opts(opts: ValidateOpts) -> Result<Status, DegaussError> {
+    let dependencies = opts.input.load()?;
+    let (schemas, sources) = match &opts.git_history {
+        Some(path) => {
+            let revisions = match &opts.against {
+                Some(rev) => vec![Some(git::revision(path, rev)?), None],
+                None => git::check_history(path, opts.since.as_deref(), opts.compat)?,
+            };
+            let files = revisions
+                .iter()
+                .map(|revision| match revision {
+                    Some(revision) => opts.input.parse_revision(&dependencies, path, revision),
+                    None => opts.input.parse_schema_file(&dependencies, path),
+                })
+                .collect::<Result<Vec<SchemaFile>, DegaussError>>()?;
+            let sources = revisions
+                .iter()
+                .map(|revision| match revision {
+                    Some(revision) => revision.source(path),
+                    None => path.display().to_string(),
+                })
+                .collect::<Vec<String>>();
+            (<dyn History>::new(files)?, sources)
+        }
+        None => (
+            opts.input.parse_history(&dependencies, &opts.schemas)?,
+            opts.schemas
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<String>>(),
+        ),
+    };
+    let matrix = match opts.matrix {
+        true => process_matrix(schemas.as_ref(), opts.output)?,
+        false => None,
+    };
+    match opts.compat {
+        Some(compat) => process_validate(schemas.as_ref(), &sources, compat, opts.output, matrix),
+        None if opts.detect => process_detect(schemas.as_ref(), &sources, opts.output, matrix),
+        None => {
+            if let (Some(format), Some(matrix)) = (opts.output, matrix) {
+                let report = Report::new("degauss validate", vec![]).with_matrix(matrix);
+                println!("{}", report.render(format));
             }
+            Ok(Status::Success)
         }
-        Err(e) => {
-            println!("{}", e);
-            Status::Failure
+    }
+}
+
+fn process_diff(opts: DiffOpts) -> Result<Status, DegaussError> {
+    let dependencies = opts.input.load()?;
+    let old = opts.input.parse_file(&dependencies, &opts.old)?;
+    let new = opts.input.parse_file(&dependencies, &opts.new)?;
+
+    let changes = diff(&old, &new);
+    if changes.is_empty() {
+        println!("No changes");
+        Ok(Status::Success)
+    } else {
+        table::render_changes(&changes);
+        Ok(Status::Incompatible)
+    }
+}
+
+fn process_verify_data(opts: VerifyDataOpts) -> Result<Status, DegaussError> {
+    let reader = opts.input.parse_file(&opts.input.load()?, &opts.reader)?;
+    let verifications = opts
+        .data
+        .iter()
+        .map(|path| Ok((path.display().to_string(), verify_data_file(&reader, path)?)))
+        .collect::<Result<Vec<_>, DegaussError>>()?;
+
+    let passed = match opts.output {
+        Some(format) => {
+            let cases = verifications
+                .iter()
+                .map(|(source, verification)| verification.report(source))
+                .collect();
+            let report = Report::new("degauss verify-data", cases);
+            println!("{}", report.render(format));
+            report.passed
         }
+        None => {
+            table::render_data_verifications(&verifications);
+            verifications
+                .iter()
+                .all(|(_, verification)| verification.passed())
+        }
+    };
+    Ok(if passed {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
+}
+
+fn process_fuzz(opts: FuzzOpts) -> Result<Status, DegaussError> {
+    let dependencies = opts.input.load()?;
+    let writer = opts.input.parse_file(&dependencies, &opts.writer)?;
+    let reader = opts.input.parse_file(&dependencies, &opts.reader)?;
+    let config = FuzzConfig {
+        iterations: opts.iterations,
+        seed: opts.seed.unwrap_or_else(rand::random),
+        max_depth: opts.max_depth,
+        ..FuzzConfig::default()
+    };
+    let outcome = fuzz(&writer, &reader, &config)?;
+    match opts.output {
+        Some(format) => {
+            let case = outcome.report(&opts.reader.display().to_string());
+            let report = Report::new("degauss fuzz", vec![case]);
+            println!("{}", report.render(format));
+        }
+        None => table::render_fuzz(&outcome),
     }
+    Ok(if outcome.agrees() {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
 }
 
-fn process_register(client: SchemaRegistryClient, opts: RegisterOpts) -> Status {
-    let schema = Schema::parse_file(opts.schema_path).expect("Schema file not found");
-    match client.register_schema(&schema, &opts.topic, opts.subject_type) {
-        Ok(resp) => {
-            println!("{}", resp.pretty_string());
-            Status::Success
+fn registry_case(opts: &CheckOpts, compat: &SchemaCompatibleResponse, name: String) -> ReportCase {
+    let source = opts.schema_path.display().to_string();
+    ReportCase {
+        name,
+        compatibility: None,
+        passed: compat.is_compatible,
+        findings: compat
+            .messages
+            .iter()
+            .map(|message| Finding {
+                rule: "registry_incompatibility".to_string(),
+                severity: Severity::Error,
+                message: message.clone(),
+                file: Some(source.clone()),
+                path: None,
+            })
+            .collect(),
+    }
+}
+
+fn process_fingerprint(opts: FingerprintOpts) -> Result<Status, DegaussError> {
+    let dependencies = opts.input.load()?;
+    let fingerprints = opts
+        .schemas
+        .iter()
+        .map(|path| {
+            let file = opts.input.parse_schema_file(&dependencies, path)?;
+            Ok((path.display().to_string(), file.schema().fingerprints()))
+        })
+        .collect::<Result<Vec<_>, DegaussError>>()?;
+    if opts.json {
+        let fingerprints = fingerprints
+            .iter()
+            .map(|(source, fingerprints)| {
+                let mut fingerprints = serde_json::to_value(fingerprints)?;
+                fingerprints["schema"] = serde_json::json!(source);
+                Ok(fingerprints)
+            })
+            .collect::<Result<Vec<_>, DegaussError>>()?;
+        println!("{}", fingerprints.pretty_string());
+    } else {
+        table::render_fingerprints(&fingerprints);
+    }
+    Ok(Status::Success)
+}
+
+fn process_fmt(opts: FmtOpts) -> Result<Status, DegaussError> {
+    let config = FormatConfig {
+        names: opts.names,
+        indent: opts.indent,
+    };
+    let mut unformatted = 0;
+    for path in find_schema_files(&opts.paths)? {
+        let input = std::fs::read_to_string(&path).map_err(|source| DegaussError::FileRead {
+            path: path.clone(),
+            source,
+        })?;
+        let formatted =
+            format_schema(&input, &config).map_err(|source| DegaussError::SchemaFile {
+                path: path.clone(),
+                source: apache_avro::Error::ParseSchemaJson(source),
+            })?;
+        if formatted == input {
+            continue;
         }
-        Err(e) => {
-            println!("{}", e);
-            Status::Failure
+        unformatted += 1;
+        if opts.check {
+            println!("{}", path.display());
+        } else {
+            std::fs::write(&path, formatted).map_err(|source| DegaussError::FileWrite {
+                path: path.clone(),
+                source,
+            })?;
+            println!("Formatted {}", path.display());
         }
     }
+    Ok(if opts.check && unformatted > 0 {
+        Status::Incompatible
+    } else {
+        Status::Success
+    })
 }
 
-fn process_set(client: SchemaRegistryClient, opts: CompatibilityOpts) -> Status {
-    match client.set_compatibility(&opts.topic, opts.subject_type, opts.compatibility.unwrap()) {
-        Ok(compat) => {
-            println!("{}", compat.pretty_string());
-            Status::Success
+fn process_lint(opts: LintOpts) -> Result<Status, DegaussError> {
+    let config = match &opts.config {
+        Some(path) => LintConfig::from_file(path)?,
+        None if Path::new(MANIFEST_FILE).is_file() => LintConfig::from_file(MANIFEST_FILE)?,
+        None => LintConfig::default(),
+    };
+    let linter = Linter::new(config);
+    let dependencies = opts.input.load()?;
+    let cases = find_schema_files(&opts.paths)?
+        .iter()
+        .map(|path| {
+            opts.input.parse_file(&dependencies, path)?;
+            let (schema, _) = opts.input.resolve_file(&dependencies, path)?;
+            Ok(linter.report(&schema, &path.display().to_string()))
+        })
+        .collect::<Result<Vec<_>, DegaussError>>()?;
+    let report = Report::new("degauss lint", cases);
+    match opts.output {
+        Some(format) => println!("{}", report.render(format)),
+        None => table::render_findings(&report.findings().cloned().collect::<Vec<_>>()),
+    }
+    Ok(if report.passed {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
+}
+
+fn process_check_all(opts: CheckAllOpts) -> Result<Status, DegaussError> {
+    let manifest = Manifest::from_file(&opts.manifest)?;
+    let report = Report::new("degauss check-all", manifest.check()?);
+    match opts.output {
+        Some(format) => println!("{}", report.render(format)),
+        None => {
+            table::render_cases(&report.cases);
+            let findings = report.findings().cloned().collect::<Vec<_>>();
+            if !findings.is_empty() {
+                table::render_findings(&findings);
+            }
         }
-        Err(e) => {
-            println!("{}", e);
-            Status::Failure
+    }
+    Ok(if report.passed {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
+}
+
+fn process_audit(client: SchemaRegistryClient, opts: AuditOpts) -> Result<Status, DegaussError> {
+    let report = Report::new("degauss schema-registry audit", client.audit()?);
+    match opts.output {
+        Some(format) => {
+            println!("{}", report.render(format));
+            table::summarize_cases(&report.cases);
+        }
+        None => {
+            table::render_cases(&report.cases);
+            let findings = report.findings().cloned().collect::<Vec<_>>();
+            if !findings.is_empty() {
+                table::render_findings(&findings);
+            }
         }
     }
+    Ok(if report.passed {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
 }
 
-fn process_get(client: SchemaRegistryClient, opts: CompatibilityOpts) -> Status {
-    match client.get_compatibility(&opts.topic, opts.subject_type) {
-        Ok(compat) => {
-            println!("{}", compat.pretty_string());
-            Status::Success
+/// Make the plan of a manifest and print it
+fn print_plan(client: &SchemaRegistryClient, opts: &PlanOpts) -> Result<Plan, DegaussError> {
+    let plan = Plan::new(&Manifest::from_file(&opts.manifest)?, client)?;
+    let report = Report::new("degauss schema-registry plan", plan.cases());
+    match opts.output {
+        Some(format) => println!("{}", report.render(format)),
+        None => {
+            table::render_cases(&report.cases);
+            let findings = report.findings().cloned().collect::<Vec<_>>();
+            if !findings.is_empty() {
+                table::render_findings(&findings);
+            }
         }
-        Err(e) => {
-            println!("{}", e);
-            Status::Failure
+    }
+    Ok(plan)
+}
+
+fn process_plan(client: SchemaRegistryClient, opts: PlanOpts) -> Result<Status, DegaussError> {
+    Ok(if print_plan(&client, &opts)?.passed() {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
+}
+
+fn process_apply(client: SchemaRegistryClient, opts: PlanOpts) -> Result<Status, DegaussError> {
+    let plan = print_plan(&client, &opts)?;
+    if !plan.passed() {
+        eprintln!("Nothing was applied, the plan has incompatible schemas");
+        return Ok(Status::Incompatible);
+    }
+    if plan.is_empty() {
+        eprintln!("Nothing to apply, schema registry matches the manifest");
+    }
+    for subject in &plan.subjects {
+        subject.apply(&client)?;
+        for action in &subject.actions {
+            eprintln!("{}: {}", subject.subject, action);
+        }
+    }
+    Ok(Status::Success)
+}
+
+fn process_check(client: SchemaRegistryClient, opts: CheckOpts) -> Result<Status, DegaussError> {
+    if opts.local {
+        return process_local_check(client, opts);
+    }
+    let dependencies = opts.input.load()?;
+    let file = opts
+        .input
+        .parse_schema_file(&dependencies, &opts.schema_path)?;
+    let subject = opts.subject.resolve(&file)?;
+    let compat = check_compatibility(&client, &opts, &dependencies, &file, &subject)?;
+    match opts.output {
+        Some(format) => {
+            let case = registry_case(&opts, &compat, subject.resolve(None)?);
+            let report = Report::new("degauss schema-registry check", vec![case]);
+            println!("{}", report.render(format));
+        }
+        None => println!("{}", compat.pretty_string()),
+    }
+    Ok(if compat.is_compatible {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
+}
+
+/// Check the schema with the registry, inlining its dependencies unless references are asked for
+fn check_compatibility(
+    client: &SchemaRegistryClient,
+    opts: &CheckOpts,
+    dependencies: &SchemaDependencies,
+    file: &SchemaFile,
+    subject: &SubjectName,
+) -> Result<SchemaCompatibleResponse, DegaussError> {
+    if !opts.references.references || !matches!(file, SchemaFile::Avro(_)) {
+        let payload = opts.references.file_payload(file)?;
+        return client.check_payload_compatibility(&payload, subject, true);
+    }
+    let payload = opts.references.payload(
+        client,
+        &opts.input,
+        dependencies,
+        &opts.schema_path,
+        &opts.subject,
+        false,
+    )?;
+    client.check_payload_compatibility(&payload, subject, true)
+}
+
+fn process_local_check(
+    client: SchemaRegistryClient,
+    opts: CheckOpts,
+) -> Result<Status, DegaussError> {
+    let dependencies = opts.input.load()?;
+    let file = opts
+        .input
+        .parse_schema_file(&dependencies, &opts.schema_path)?;
+    let subject_name = opts.subject.resolve(&file)?;
+    let subject = subject_name.resolve(None)?;
+    let history = client.get_history(&subject_name)?;
+    let compatibility = client
+        .get_effective_compatibility(&subject_name)?
+        .compatibility_level;
+    // A subject without any versions accepts every schema.
+    let compat = match check_compatibility(&client, &opts, &dependencies, &file, &subject_name) {
+        Err(DegaussError::SrHttp { error_code, .. }) if error_code == SUBJECT_NOT_FOUND => {
+            SchemaCompatibleResponse {
+                is_compatible: true,
+                messages: vec![],
+            }
+        }
+        compat => compat?,
+    };
+
+    let mut files = Vec::with_capacity(history.len() + 1);
+    let mut sources = Vec::with_capacity(history.len() + 1);
+    for version in &history {
+        let source = format!("{}/versions/{}", subject, version.version);
+        files.push(match version.kind() {
+            SchemaType::Avro => SchemaFile::Avro(client.resolve_schema(version)?),
+            schema_type => {
+                SchemaFile::parse_text(Path::new(&source), schema_type, version.schema.clone())?
+            }
+        });
+        sources.push(source);
+    }
+    files.push(file);
+    sources.push(opts.schema_path.display().to_string());
+
+    let schemas = <dyn History>::new(files)?;
+    // Without registered versions there is nothing the schema has to be compatible with, like the registry says.
+    let local = if history.is_empty() {
+        ReportCase {
+            name: compatibility.to_string(),
+            compatibility: Some(compatibility),
+            passed: true,
+            findings: vec![],
+        }
+    } else {
+        schemas.report(compatibility, &sources)
+    };
+    let agreed = local.passed == compat.is_compatible;
+    let disagreement = format!(
+        "Local check {} but schema registry {} the schema for {} with {} compatibility",
+        if local.passed { "passed" } else { "failed" },
+        if compat.is_compatible {
+            "accepts"
+        } else {
+            "rejects"
+        },
+        subject,
+        compatibility
+    );
+
+    match opts.output {
+        Some(format) => {
+            let agreement = ReportCase {
+                name: format!("{}-agreement", subject),
+                compatibility: Some(compatibility),
+                passed: agreed,
+                findings: if agreed {
+                    vec![]
+                } else {
+                    vec![Finding {
+                        rule: "registry_disagreement".to_string(),
+                        severity: Severity::Warning,
+                        message: disagreement,
+                        file: Some(opts.schema_path.display().to_string()),
+                        path: None,
+                    }]
+                },
+            };
+            let cases = vec![
+                ReportCase {
+                    name: format!("{}-local", subject),
+                    ..local
+                },
+                registry_case(&opts, &compat, format!("{}-registry", subject)),
+                agreement,
+            ];
+            let report = Report::new("degauss schema-registry check", cases);
+            println!("{}", report.render(format));
+        }
+        None => {
+            let verdicts = [(compatibility, local.passed)].iter().cloned().collect();
+            table::render(&verdicts);
+            let incompatibilities = schemas.explain(compatibility);
+            if !incompatibilities.is_empty() {
+                table::render_incompatibilities(&incompatibilities);
+            }
+            println!("{}", compat.pretty_string());
+            if !agreed {
+                eprintln!("WARNING: {}", disagreement);
+            }
         }
     }
+    Ok(if local.passed && agreed {
+        Status::Success
+    } else {
+        Status::Incompatible
+    })
+}
+
+fn process_register(
+    client: SchemaRegistryClient,
+    opts: RegisterOpts,
+) -> Result<Status, DegaussError> {
+    let dependencies = opts.input.load()?;
+    let file = opts
+        .input
+        .parse_schema_file(&dependencies, &opts.schema_path)?;
+    let subject = opts.subject.resolve(&file)?;
+    let payload = match &file {
+        SchemaFile::Avro(_) if opts.references.references => opts.references.payload(
+            &client,
+            &opts.input,
+            &dependencies,
+            &opts.schema_path,
+            &opts.subject,
+            opts.register_references,
+        )?,
+        file => opts.references.file_payload(file)?,
+    };
+    let resp = client.register_payload(&payload, &subject)?;
+    println!("{}", resp.pretty_string());
+    Ok(Status::Success)
+}
+
+fn process_set(
+    client: SchemaRegistryClient,
+    opts: CompatibilityOpts,
+) -> Result<Status, DegaussError> {
+    let compatibility = opts.compatibility.ok_or_else(|| {
+        DegaussError::Usage("Compatibility to set is missing, use --compatibility".to_string())
+    })?;
+    let compat = client.set_compatibility(&opts.subject.subject_name()?, compatibility)?;
+    println!("{}", compat.pretty_string());
+    Ok(Status::Success)
 }
 
-fn create_schema_registry_client(sr: SchemaRegistry) -> SchemaRegistryClient {
+fn process_get(
+    client: SchemaRegistryClient,
+    opts: CompatibilityOpts,
+) -> Result<Status, DegaussError> {
+    let compat = client.get_compatibility(&opts.subject.subject_name()?)?;
+    println!("{}", compat.pretty_string());
+    Ok(Status::Success)
+}
+
+fn process_subject(
+    client: SchemaRegistryClient,
+    cmd: SubjectCommand,
+) -> Result<Status, DegaussError> {
+    let resp = match cmd {
+        SubjectCommand::List => client.list_subjects()?.pretty_string(),
+        SubjectCommand::Versions(opts) => {
+            client.list_versions(&opts.subject_name()?)?.pretty_string()
+        }
+        SubjectCommand::Get(opts) => client
+            .get_version(&opts.subject.subject_name()?, opts.version)?
+            .pretty_string(),
+        SubjectCommand::Delete(opts) => match opts.version {
+            Some(version) => client
+                .delete_version(&opts.subject.subject_name()?, version, opts.permanent)?
+                .pretty_string(),
+            None => client
+                .delete_subject(&opts.subject.subject_name()?, opts.permanent)?
+                .pretty_string(),
+        },
+        SubjectCommand::ReferencedBy(opts) => {
+            let mut referencing = vec![];
+            for id in client.get_referenced_by(&opts.subject.subject_name()?, opts.version)? {
+                referencing.extend(client.get_versions_by_id(id)?);
+            }
+            referencing.pretty_string()
+        }
+    };
+    println!("{}", resp);
+    Ok(Status::Success)
+}
+
+fn process_schema(
+    client: SchemaRegistryClient,
+    cmd: SchemaCommand,
+) -> Result<Status, DegaussError> {
+    let resp = match cmd {
+        SchemaCommand::Get(opts) => client.get_schema_by_id(opts.id)?.pretty_string(),
+        SchemaCommand::Subjects(opts) => client.get_subjects_by_id(opts.id)?.pretty_string(),
+        SchemaCommand::Versions(opts) => client.get_versions_by_id(opts.id)?.pretty_string(),
+    };
+    println!("{}", resp);
+    Ok(Status::Success)
+}
+
+fn create_schema_registry_client(sr: SchemaRegistry) -> Result<SchemaRegistryClient, DegaussError> {
     let auth = match (sr.schema_registry_user, sr.schema_registry_pass) {
         (Some(user), Some(pass)) => Auth::Basic {
             username: user,
             password: pass,
         },
         (None, None) => Auth::Skip,
-        _ => panic!("Please set both user/pass, not just one"),
+        _ => {
+            return Err(DegaussError::Usage(
+                "Please set both user/pass, not just one".to_string(),
+            ))
+        }
     };
     SchemaRegistryClient::new(sr.schema_registry_url, auth)
-        .expect("Failed to create a Schema Registry client")
 }
 
-fn main() {
-    let degauss_cli: Degauss = Degauss::from_args();
+fn process(cmd: SubCommand) -> Result<Status, DegaussError> {
+    match cmd {
+        SubCommand::Validate(opts) => process_validate_opts(opts),
 
-    let status = match degauss_cli.cmd {
-        SubCommand::Validate(opts) => {
-            let valid = process_validate(opts.schemas, opts.compat);
-            if !valid {
-                Status::Failure
-            } else {
-                Status::Success
-            }
-        }
+        SubCommand::Diff(opts) => process_diff(opts),
+
+        SubCommand::VerifyData(opts) => process_verify_data(opts),
+
+        SubCommand::Fuzz(opts) => process_fuzz(opts),
+
+        SubCommand::Fingerprint(opts) => process_fingerprint(opts),
+
+        SubCommand::Fmt(opts) => process_fmt(opts),
+
+        SubCommand::Lint(opts) => process_lint(opts),
+
+        SubCommand::CheckAll(opts) => process_check_all(opts),
 
         SubCommand::SchemaRegistry(sr) => {
-            let client = create_schema_registry_client(sr.clone());
+            let client = create_schema_registry_client(sr.clone())?;
             match sr.cmd {
                 SRCommand::Compatibility(comp) => match comp.cmd {
                     CompatibilityCommand::Get(opts) => process_get(client, opts),
@@ -266,10 +1571,32 @@ fn main() {
                     CompatibilityCommand::Check(opts) => process_check(client, opts),
                 },
                 SRCommand::Register(opts) => process_register(client, opts),
+                SRCommand::Subject(subject) => process_subject(client, subject.cmd),
+                SRCommand::Schema(schema) => process_schema(client, schema.cmd),
+                SRCommand::Audit(opts) => process_audit(client, opts),
+                SRCommand::Plan(opts) => process_plan(client, opts),
+                SRCommand::Apply(opts) => process_apply(client, opts),
             }
         }
-    };
-    if degauss_cli.exit_status {
-        std::process::exit(status.to_i32())
     }
 }
+
+fn main() {
+    let degauss_cli: Degauss = Degauss::from_args();
+    let processed = if degauss_cli.exit_status {
+        Err(DegaussError::Usage(
+            "--exit-status was removed, degauss always exits with its status. Drop the flag."
+                .to_string(),
+        ))
+    } else {
+        process(degauss_cli.cmd)
+    };
+    let status = match processed {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("{}", e);
+            Status::from(&e)
+        }
+    };
+    std::process::exit(status.to_i32())
+}
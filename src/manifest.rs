@@ -0,0 +1,284 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Manifest of the subjects of a project, read from a `degauss.toml` file.
+//!
+//! Every subject lists the history of its schema, oldest first, either as files or as a directory
+//! with a glob of the version files, and the compatibility the history has to satisfy.
+//! Paths are relative to the directory of the manifest. The schema files of a subject are all
+//! written in one schema language, `.proto` files are Protobuf ones, `.json` files are JSON Schemas when they
+//! look like one, see [`SchemaFileFormat::detect`](crate::schema::SchemaFileFormat::detect).
+//! The manifest is also the state [`Plan`](crate::plan::Plan)s bring schema registry to.
+//!
+//! ```toml
+//! dependencies = ["schemas/shared"]
+//!
+//! [[subjects]]
+//! topic = "movies"
+//! type = "value"
+//! compatibility = "backward"
+//! schemas = ["schemas/movies/v1.avsc", "schemas/movies/v2.avsc"]
+//!
+//! [[subjects]]
+//! subject = "com.example.Payment"
+//! compatibility = "full_transitive"
+//! dir = "schemas/payments"
+//! versions = "payment_v*.avsc"
+//! ```
+//!
+//! ```rust,no_run
+//! use degauss::prelude::*;
+//!
+//! let manifest = Manifest::from_file("degauss.toml").unwrap();
+//! let report = Report::new("degauss check-all", manifest.check().unwrap());
+//! println!("{}", report.render(ReportFormat::Junit));
+//! ```
+//!
+
+use crate::compat::{DegaussCheck, DegaussCompatMode};
+use crate::errors::DegaussError;
+use crate::json_schema::JsonSchema;
+use crate::lint::LintConfig;
+use crate::protobuf::ProtoFile;
+use crate::report::ReportCase;
+use crate::schema::{read_file, FromFile, SchemaDependencies, SchemaFileFormat};
+use crate::schema_registry::types::{SchemaSubjectType, SchemaType, SubjectName};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest file commands look for in the working directory
+pub const MANIFEST_FILE: &str = "degauss.toml";
+
+/// A subject with the history of its schema
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubjectEntry {
+    /// Topic of the subject, registered under `<topic>-<type>`.
+    pub topic: Option<String>,
+    #[serde(rename = "type", default = "value_type")]
+    pub subject_type: SchemaSubjectType,
+    /// Subject as it is registered, instead of deriving it from the topic.
+    pub subject: Option<String>,
+    pub compatibility: DegaussCompatMode,
+    /// Schema files in chronological order, from oldest to newest.
+    #[serde(default)]
+    pub schemas: Vec<PathBuf>,
+    /// Directory of the schema files, instead of listing them.
+    pub dir: Option<PathBuf>,
+    /// Glob of the file names of the versions in `dir`, ordered by the numbers in their names.
+    pub versions: Option<String>,
+}
+
+fn value_type() -> SchemaSubjectType {
+    SchemaSubjectType::Value
+}
+
+/// Subjects of a project, with the settings of the other commands
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Directory the paths of the manifest are relative to.
+    #[serde(skip)]
+    pub root: PathBuf,
+    /// Schema files, or directories of them, defining the named types the subjects refer to.
+    #[serde(default)]
+    pub dependencies: Vec<PathBuf>,
+    #[serde(default)]
+    pub subjects: Vec<SubjectEntry>,
+    /// Settings of `degauss lint`.
+    #[serde(default)]
+    pub lint: LintConfig,
+}
+
+impl SubjectEntry {
+    /// Subject the schemas are registered under
+    pub fn subject(&self) -> Result<String, DegaussError> {
+        match (&self.subject, &self.topic) {
+            (Some(subject), _) => Ok(subject.clone()),
+            (None, Some(topic)) => SubjectName::topic_name(topic, self.subject_type).resolve(None),
+            (None, None) => Err(DegaussError::Usage(
+                "Subjects of the manifest need a topic or a subject".to_string(),
+            )),
+        }
+    }
+
+    /// Schema files of the history, oldest first, with `root` prepended to relative paths
+    pub fn history<P: AsRef<Path>>(&self, root: P) -> Result<Vec<PathBuf>, DegaussError> {
+        let root = root.as_ref();
+        let subject = self.subject()?;
+        let history = match (&self.dir, &self.versions) {
+            (None, None) => self.schemas.iter().map(|path| root.join(path)).collect(),
+            (Some(dir), Some(versions)) if self.schemas.is_empty() => {
+                let pattern = glob::Pattern::new(versions).map_err(|e| {
+                    DegaussError::Usage(format!(
+                        "Invalid version glob {} of subject {}: {}",
+                        versions, subject, e
+                    ))
+                })?;
+                let dir = root.join(dir);
+                let file_error = |source| DegaussError::FileRead {
+                    path: dir.clone(),
+                    source,
+                };
+                let mut files = std::fs::read_dir(&dir)
+                    .map_err(file_error)?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<PathBuf>, _>>()
+                    .map_err(file_error)?
+                    .into_iter()
+                    .filter(|path| {
+                        path.is_file()
+                            && path
+                                .file_name()
+                                .and_then(|name| name.to_str())
+                                .is_some_and(|name| pattern.matches(name))
+                    })
+                    .collect::<Vec<_>>();
+                files.sort_by(|a, b| version_order(&a.to_string_lossy(), &b.to_string_lossy()));
+                files
+            }
+            _ => {
+                return Err(DegaussError::Usage(format!(
+                    "Subject {} needs either schemas, or a dir with a versions glob",
+                    subject
+                )))
+            }
+        };
+        if history.is_empty() {
+            return Err(DegaussError::Usage(format!(
+                "Subject {} has no schema files",
+                subject
+            )));
+        }
+        Ok(history)
+    }
+
+    /// Schema language of the schema files of the history, by their extensions and for `.json` files their contents
+    pub fn schema_type(&self, history: &[PathBuf]) -> Result<SchemaType, DegaussError> {
+        let types = history
+            .iter()
+            .map(|path| Ok(SchemaFileFormat::detect(path, &read_file(path)?).schema_type()))
+            .collect::<Result<Vec<_>, DegaussError>>()?;
+        let mut types = types.into_iter();
+        let schema_type = types.next().unwrap_or(SchemaType::Avro);
+        match types.find(|other| *other != schema_type) {
+            Some(other) => Err(DegaussError::Usage(format!(
+                "Subject {} mixes {} and {} schema files",
+                self.subject()?,
+                schema_type,
+                other
+            ))),
+            None => Ok(schema_type),
+        }
+    }
+}
+
+impl Manifest {
+    /// Read a manifest, its paths are relative to the directory of the file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, DegaussError> {
+        let path = path.as_ref();
+        let mut manifest: Manifest =
+            toml::from_str(&read_file(path)?).map_err(|source| DegaussError::ConfigFile {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        manifest.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(manifest)
+    }
+
+    /// Named types the schemas of the subjects can refer to
+    pub fn load_dependencies(&self) -> Result<SchemaDependencies, DegaussError> {
+        SchemaDependencies::load(
+            &self
+                .dependencies
+                .iter()
+                .map(|path| self.root.join(path))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Check the history of every subject with its compatibility, one report case per subject
+    pub fn check(&self) -> Result<Vec<ReportCase>, DegaussError> {
+        let dependencies = self.load_dependencies()?;
+        self.subjects
+            .iter()
+            .map(|entry| {
+                let history = entry.history(&self.root)?;
+                let sources = history
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>();
+                let check = DegaussCheck(entry.compatibility);
+                let case = match entry.schema_type(&history)? {
+                    SchemaType::Protobuf => {
+                        check.report(&parse_files::<ProtoFile>(&history)?, &sources)
+                    }
+                    SchemaType::Json => {
+                        check.report(&parse_files::<JsonSchema>(&history)?, &sources)
+                    }
+                    _ => check.report(&dependencies.parse_files(&history)?, &sources),
+                };
+                Ok(ReportCase {
+                    name: entry.subject()?,
+                    ..case
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parse schema files which can't refer to the dependencies of the manifest, which are Avro types
+pub(crate) fn parse_files<S: FromFile>(paths: &[PathBuf]) -> Result<Vec<S>, DegaussError> {
+    paths.iter().map(S::parse_file).collect()
+}
+
+/// Segment of a file name, numbers are compared by their value
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Segment {
+    Number(u64),
+    Text(String),
+}
+
+fn segments(name: &str) -> Vec<Segment> {
+    let mut segments = vec![];
+    let mut rest = name;
+    while let Some(first) = rest.chars().next() {
+        let digit = first.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digit)
+            .unwrap_or(rest.len());
+        let (segment, tail) = rest.split_at(end);
+        segments.push(match segment.parse() {
+            Ok(number) if digit => Segment::Number(number),
+            _ => Segment::Text(segment.to_string()),
+        });
+        rest = tail;
+    }
+    segments
+}
+
+/// Order of version file names, `v2` comes before `v10`
+pub fn version_order(a: &str, b: &str) -> Ordering {
+    segments(a).cmp(&segments(b))
+}
@@ -0,0 +1,427 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Plans to bring schema registry to the state a manifest describes.
+//!
+//! Every subject of the [`Manifest`] has the newest schema file of its history registered,
+//! and its compatibility set to the one of the manifest. A [`Plan`] lists the actions which get there, checks the
+//! schemas to register against the registered versions with the compatibility of the manifest, and reports drift:
+//! subjects the manifest doesn't list, and versions registered after the newest schema file the registry knows.
+//!
+//! ```rust,no_run
+//! use degauss::prelude::*;
+//!
+//! let client = SchemaRegistryClient::new("http://localhost:8081", Auth::Skip).unwrap();
+//! let manifest = Manifest::from_file("degauss.toml").unwrap();
+//! let plan = Plan::new(&manifest, &client).unwrap();
+//! println!("{}", Report::new("degauss plan", plan.cases()).render(ReportFormat::Markdown));
+//! if plan.passed() {
+//!     plan.apply(&client).unwrap();
+//! }
+//! ```
+//!
+
+use crate::compat::{DegaussCheck, DegaussCompatMode};
+use crate::errors::DegaussError;
+use crate::format::DegaussSchema;
+use crate::json_schema::JsonSchema;
+use crate::manifest::{parse_files, Manifest};
+use crate::protobuf::ProtoFile;
+use crate::report::{Finding, ReportCase, Severity};
+use crate::schema_registry::types::{SchemaPayload, SchemaType, SubjectName};
+use crate::schema_registry::{
+    SchemaRegistryClient, SCHEMA_NOT_FOUND, SUBJECT_LEVEL_COMPATIBILITY_NOT_CONFIGURED,
+    SUBJECT_NOT_FOUND,
+};
+use apache_avro::Schema;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Change of a subject in schema registry
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Set the compatibility of the subject, `from` is the one the subject has on its own, if any.
+    SetCompatibility {
+        from: Option<DegaussCompatMode>,
+        to: DegaussCompatMode,
+    },
+    /// Register the schema file as a new version of the subject.
+    Register { file: PathBuf },
+}
+
+impl Action {
+    /// Identifier of the action in reports
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Action::SetCompatibility { .. } => "set_compatibility",
+            Action::Register { .. } => "register",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::SetCompatibility {
+                from: Some(from),
+                to,
+            } => write!(f, "set compatibility from {} to {}", from, to),
+            Action::SetCompatibility { from: None, to } => {
+                write!(f, "set compatibility to {}, instead of the global one", to)
+            }
+            Action::Register { file } => write!(f, "register {}", file.display()),
+        }
+    }
+}
+
+/// State of a subject in schema registry, with its schemas in the schema language of the manifest
+#[derive(Clone, Debug)]
+pub struct RegisteredSubject<S = Schema> {
+    /// Compatibility of the subject on its own, without falling back to the global one.
+    pub compatibility: Option<DegaussCompatMode>,
+    /// Registered versions with their schemas, oldest first.
+    pub versions: Vec<(i32, S)>,
+    /// Version each schema file of the history is registered as, if it is.
+    pub file_versions: Vec<Option<i32>>,
+}
+
+impl<S> Default for RegisteredSubject<S> {
+    fn default() -> Self {
+        RegisteredSubject {
+            compatibility: None,
+            versions: vec![],
+            file_versions: vec![],
+        }
+    }
+}
+
+impl<S: DegaussSchema> RegisteredSubject<S> {
+    /// Fetch the state of a subject, `schemas` are the ones of the schema files of its history
+    pub fn fetch(
+        client: &SchemaRegistryClient,
+        subject: &str,
+        schemas: &[S],
+    ) -> Result<Self, DegaussError> {
+        let schema_type = schemas
+            .first()
+            .map_or(SchemaType::Avro, DegaussSchema::schema_type);
+        let name = SubjectName::Raw(subject.to_string());
+        let compatibility = match client.get_compatibility(&name) {
+            Err(DegaussError::SrHttp { error_code, .. })
+                if error_code == SUBJECT_NOT_FOUND
+                    || error_code == SUBJECT_LEVEL_COMPATIBILITY_NOT_CONFIGURED =>
+            {
+                None
+            }
+            compatibility => Some(compatibility?.compatibility_level),
+        };
+        let versions = client
+            .get_history(&name)?
+            .iter()
+            .map(|version| {
+                let source = format!("{}/versions/{}", subject, version.version);
+                let schema = match version.kind() {
+                    kind if kind != schema_type => {
+                        return Err(DegaussError::Usage(format!(
+                            "{} is a {} schema, but the schema files of {} are {} schemas",
+                            source, kind, subject, schema_type
+                        )))
+                    }
+                    // Avro versions may reference other subjects, their canonical form has the referenced types inlined.
+                    SchemaType::Avro => S::parse_str_at(
+                        Path::new(&source),
+                        &client.resolve_schema(version)?.canonical_form(),
+                    )?,
+                    _ => S::parse_str_at(Path::new(&source), &version.schema)?,
+                };
+                Ok((version.version, schema))
+            })
+            .collect::<Result<Vec<_>, DegaussError>>()?;
+        let file_versions = schemas
+            .iter()
+            .map(|schema| {
+                if versions.is_empty() {
+                    return Ok(None);
+                }
+                // Looked up as they are registered, the text of a file only matches the same text.
+                match client.lookup_schema(&schema.payload(), &name) {
+                    Err(DegaussError::SrHttp { error_code, .. })
+                        if error_code == SUBJECT_NOT_FOUND || error_code == SCHEMA_NOT_FOUND =>
+                    {
+                        Ok(None)
+                    }
+                    registered => Ok(Some(registered?.version)),
+                }
+            })
+            .collect::<Result<Vec<_>, DegaussError>>()?;
+        Ok(RegisteredSubject {
+            compatibility,
+            versions,
+            file_versions,
+        })
+    }
+}
+
+/// Actions and findings of a subject of the manifest
+#[derive(Clone, Debug)]
+pub struct SubjectPlan {
+    pub subject: String,
+    /// Compatibility of the manifest.
+    pub compatibility: DegaussCompatMode,
+    /// Actions in the order they are applied in.
+    pub actions: Vec<Action>,
+    /// Violations of the schema to register against the registered versions, and versions registered out of band.
+    pub findings: Vec<Finding>,
+    /// Newest schema file as it is registered: Protobuf and JSON Schema files as they are written, with their
+    /// schema type, and Avro schemas with their dependencies inlined.
+    pub payload: SchemaPayload,
+}
+
+impl SubjectPlan {
+    /// Plan the changes of a subject from the schema files of its history, oldest first, and its registered state
+    pub fn new<S: DegaussSchema + Clone>(
+        subject: String,
+        compatibility: DegaussCompatMode,
+        files: &[PathBuf],
+        mut schemas: Vec<S>,
+        registered: &RegisteredSubject<S>,
+    ) -> Result<Self, DegaussError> {
+        let (file, schema) = match (files.last(), schemas.pop()) {
+            (Some(file), Some(schema)) => (file, schema),
+            _ => {
+                return Err(DegaussError::Usage(format!(
+                    "Subject {} has no schema files",
+                    subject
+                )))
+            }
+        };
+        let mut actions = vec![];
+        let mut findings = vec![];
+        if registered.compatibility != Some(compatibility) {
+            actions.push(Action::SetCompatibility {
+                from: registered.compatibility,
+                to: compatibility,
+            });
+        }
+        if registered.file_versions.last().copied().flatten().is_none() {
+            actions.push(Action::Register { file: file.clone() });
+            let mut history = registered
+                .versions
+                .iter()
+                .map(|(_, schema)| schema.clone())
+                .collect::<Vec<_>>();
+            let mut sources = registered
+                .versions
+                .iter()
+                .map(|(version, _)| format!("{}/versions/{}", subject, version))
+                .collect::<Vec<_>>();
+            history.push(schema.clone());
+            sources.push(file.display().to_string());
+            findings.extend(
+                DegaussCheck(compatibility)
+                    .explain(&history)
+                    .iter()
+                    .map(|incompat| Finding::from_incompatibility(incompat, &sources)),
+            );
+        }
+        // Versions after the newest one of a schema file were registered without the manifest.
+        let known = registered.file_versions.iter().flatten().max();
+        for (version, _) in &registered.versions {
+            if known.is_none_or(|known| version > known) {
+                findings.push(Finding {
+                    rule: "out_of_band_version".to_string(),
+                    severity: Severity::Warning,
+                    message: format!(
+                        "version {} of {} isn't a schema file of the manifest",
+                        version, subject
+                    ),
+                    file: Some(format!("{}/versions/{}", subject, version)),
+                    path: None,
+                });
+            }
+        }
+        Ok(SubjectPlan {
+            subject,
+            compatibility,
+            actions,
+            findings,
+            payload: schema.payload(),
+        })
+    }
+
+    /// Plan the changes of a subject from the schema files of its history, oldest first, and its state in schema registry
+    pub fn fetch<S: DegaussSchema + Clone>(
+        client: &SchemaRegistryClient,
+        subject: String,
+        compatibility: DegaussCompatMode,
+        files: &[PathBuf],
+        schemas: Vec<S>,
+    ) -> Result<Self, DegaussError> {
+        let registered = RegisteredSubject::fetch(client, &subject, &schemas)?;
+        SubjectPlan::new(subject, compatibility, files, schemas, &registered)
+    }
+
+    /// Whether the schema to register is compatible with the registered versions
+    pub fn passed(&self) -> bool {
+        self.findings
+            .iter()
+            .all(|finding| finding.severity != Severity::Error)
+    }
+
+    /// Report case with the actions as notes, followed by the findings
+    pub fn case(&self) -> ReportCase {
+        let actions = self.actions.iter().map(|action| Finding {
+            rule: action.rule().to_string(),
+            severity: Severity::Note,
+            message: action.to_string(),
+            file: Some(match action {
+                Action::Register { file } => file.display().to_string(),
+                Action::SetCompatibility { .. } => self.subject.clone(),
+            }),
+            path: None,
+        });
+        ReportCase {
+            name: self.subject.clone(),
+            compatibility: Some(self.compatibility),
+            passed: self.passed(),
+            findings: actions.chain(self.findings.iter().cloned()).collect(),
+        }
+    }
+
+    /// Apply the actions, the compatibility is set before the schema is registered with it
+    pub fn apply(&self, client: &SchemaRegistryClient) -> Result<(), DegaussError> {
+        let subject = SubjectName::Raw(self.subject.clone());
+        for action in &self.actions {
+            match action {
+                Action::SetCompatibility { to, .. } => {
+                    client.set_compatibility(&subject, *to)?;
+                }
+                Action::Register { .. } => {
+                    client.register_payload(&self.payload, &subject)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Changes of every subject of a manifest, with the subjects of the registry the manifest doesn't list
+#[derive(Clone, Debug)]
+pub struct Plan {
+    pub subjects: Vec<SubjectPlan>,
+    /// Registered subjects which aren't in the manifest, in alphabetical order.
+    pub unmanaged: Vec<String>,
+}
+
+impl Plan {
+    /// Compare the subjects of the manifest with their state in schema registry
+    pub fn new(manifest: &Manifest, client: &SchemaRegistryClient) -> Result<Self, DegaussError> {
+        let dependencies = manifest.load_dependencies()?;
+        let subjects = manifest
+            .subjects
+            .iter()
+            .map(|entry| {
+                let subject = entry.subject()?;
+                let files = entry.history(&manifest.root)?;
+                match entry.schema_type(&files)? {
+                    SchemaType::Protobuf => SubjectPlan::fetch(
+                        client,
+                        subject,
+                        entry.compatibility,
+                        &files,
+                        parse_files::<ProtoFile>(&files)?,
+                    ),
+                    SchemaType::Json => SubjectPlan::fetch(
+                        client,
+                        subject,
+                        entry.compatibility,
+                        &files,
+                        parse_files::<JsonSchema>(&files)?,
+                    ),
+                    _ => SubjectPlan::fetch(
+                        client,
+                        subject,
+                        entry.compatibility,
+                        &files,
+                        dependencies.parse_files(&files)?,
+                    ),
+                }
+            })
+            .collect::<Result<Vec<_>, DegaussError>>()?;
+        let managed = subjects
+            .iter()
+            .map(|plan| plan.subject.as_str())
+            .collect::<HashSet<_>>();
+        let mut unmanaged = client
+            .list_subjects()?
+            .into_iter()
+            .filter(|subject| !managed.contains(subject.as_str()))
+            .collect::<Vec<_>>();
+        unmanaged.sort();
+        Ok(Plan {
+            subjects,
+            unmanaged,
+        })
+    }
+
+    /// Whether every schema to register is compatible with the registered versions of its subject
+    pub fn passed(&self) -> bool {
+        self.subjects.iter().all(SubjectPlan::passed)
+    }
+
+    /// Whether applying the plan doesn't change anything
+    pub fn is_empty(&self) -> bool {
+        self.subjects.iter().all(|plan| plan.actions.is_empty())
+    }
+
+    /// One report case per subject of the manifest, followed by one per unmanaged subject
+    pub fn cases(&self) -> Vec<ReportCase> {
+        let unmanaged = self.unmanaged.iter().map(|subject| ReportCase {
+            name: subject.clone(),
+            compatibility: None,
+            passed: true,
+            findings: vec![Finding {
+                rule: "unmanaged_subject".to_string(),
+                severity: Severity::Warning,
+                message: format!("{} is registered but not listed in the manifest", subject),
+                file: Some(subject.clone()),
+                path: None,
+            }],
+        });
+        self.subjects
+            .iter()
+            .map(SubjectPlan::case)
+            .chain(unmanaged)
+            .collect()
+    }
+
+    /// Apply the actions of every subject, in the order of the manifest.
+    /// Check [`passed`](Plan::passed) first, schema registry rejects incompatible schemas after the
+    /// subjects before them were changed.
+    pub fn apply(&self, client: &SchemaRegistryClient) -> Result<(), DegaussError> {
+        self.subjects.iter().try_for_each(|plan| plan.apply(client))
+    }
+}
@@ -0,0 +1,448 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Compatibility of Protobuf schemas, with the same modes as Avro.
+//!
+//! A reader can read the data of a writer when the fields with the same numbers have wire compatible types,
+//! the fields it requires are written, and the writer's oneofs and enum values mean the same to it.
+//!
+
+use super::{Definition, Enum, Field, FieldType, Label, Message, ProtoFile, Syntax};
+use crate::format::Incompatibility;
+use serde::Serialize;
+use std::collections::HashMap;
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+/// Reason why a reader Protobuf schema can't read the data of a writer schema
+#[derive(
+    EnumVariantNames,
+    EnumString,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtobufIncompatibilityKind {
+    /// Types can't be read as each other on the wire.
+    #[strum(serialize = "type_mismatch")]
+    TypeMismatch,
+
+    /// Protobuf files have different packages.
+    #[strum(serialize = "package_changed")]
+    PackageChanged,
+
+    /// Writer has a Protobuf message which the reader doesn't have.
+    #[strum(serialize = "message_removed")]
+    MessageRemoved,
+
+    /// A Protobuf field number is used by another field, or it is reserved.
+    #[strum(serialize = "field_number_reused")]
+    FieldNumberReused,
+
+    /// Reader requires a Protobuf field which the older writer doesn't always write.
+    #[strum(serialize = "required_field_added")]
+    RequiredFieldAdded,
+
+    /// Older reader requires a Protobuf field which the writer doesn't always write.
+    #[strum(serialize = "required_field_removed")]
+    RequiredFieldRemoved,
+
+    /// Writer has an enum value which the reader doesn't know about.
+    #[strum(serialize = "enum_symbol_removed")]
+    EnumSymbolRemoved,
+
+    /// A Protobuf enum value has another name.
+    #[strum(serialize = "enum_value_changed")]
+    EnumValueChanged,
+
+    /// Writer has a field in a Protobuf oneof which the reader doesn't have.
+    #[strum(serialize = "oneof_field_removed")]
+    OneofFieldRemoved,
+
+    /// Reader has fields in a Protobuf oneof which the writer sets independently.
+    #[strum(serialize = "field_moved_to_oneof")]
+    FieldMovedToOneof,
+}
+
+/// Explain why the `reader` schema can't read data written with the `writer` schema.
+///
+/// Returns an empty list when the reader can read everything the writer produces.
+pub fn explain_pair(
+    reader_version: usize,
+    reader: &ProtoFile,
+    writer_version: usize,
+    writer: &ProtoFile,
+) -> Vec<Incompatibility> {
+    let mut walker = Walker {
+        reader,
+        writer,
+        reader_version,
+        writer_version,
+        reader_definitions: reader.definitions(),
+        writer_definitions: writer.definitions(),
+        incompatibilities: vec![],
+    };
+    walker.file();
+    walker.incompatibilities
+}
+
+struct Walker<'a> {
+    reader: &'a ProtoFile,
+    writer: &'a ProtoFile,
+    reader_version: usize,
+    writer_version: usize,
+    reader_definitions: HashMap<&'a str, Definition<'a>>,
+    writer_definitions: HashMap<&'a str, Definition<'a>>,
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl<'a> Walker<'a> {
+    fn add(
+        &mut self,
+        path: String,
+        reader_type: String,
+        writer_type: String,
+        kind: ProtobufIncompatibilityKind,
+        message: String,
+    ) {
+        self.incompatibilities.push(Incompatibility {
+            reader_version: self.reader_version,
+            writer_version: self.writer_version,
+            path,
+            reader_type,
+            writer_type,
+            kind: kind.into(),
+            message,
+        });
+    }
+
+    fn file(&mut self) {
+        if self.reader.package != self.writer.package {
+            let package =
+                |file: &ProtoFile| file.package.clone().unwrap_or_else(|| "-".to_string());
+            self.add(
+                "/".to_string(),
+                package(self.reader),
+                package(self.writer),
+                ProtobufIncompatibilityKind::PackageChanged,
+                format!(
+                    "package is {} in the reader but {} in the writer",
+                    package(self.reader),
+                    package(self.writer)
+                ),
+            );
+        }
+        let mut names = self.writer_definitions.keys().copied().collect::<Vec<_>>();
+        names.sort_unstable();
+        for name in names {
+            let relative = self.writer.relative_name(name);
+            let path = format!("/{}", relative);
+            let reader_name = match &self.reader.package {
+                Some(package) => format!("{}.{}", package, relative),
+                None => relative.to_string(),
+            };
+            match (
+                self.writer_definitions[name],
+                self.reader_definitions.get(reader_name.as_str()).copied(),
+            ) {
+                (Definition::Message(writer), Some(Definition::Message(reader))) => {
+                    self.message(reader, writer, &path)
+                }
+                (Definition::Enum(writer), Some(Definition::Enum(reader))) => {
+                    self.enumeration(reader, writer, &path)
+                }
+                (Definition::Message(_), None) => self.add(
+                    path,
+                    "absent".to_string(),
+                    relative.to_string(),
+                    ProtobufIncompatibilityKind::MessageRemoved,
+                    format!(
+                        "message {} of the writer is missing in the reader",
+                        relative
+                    ),
+                ),
+                (writer, Some(reader)) => self.add(
+                    path,
+                    kind_of(reader).to_string(),
+                    kind_of(writer).to_string(),
+                    ProtobufIncompatibilityKind::TypeMismatch,
+                    format!(
+                        "{} is a {} in the reader but a {} in the writer",
+                        relative,
+                        kind_of(reader),
+                        kind_of(writer)
+                    ),
+                ),
+                // Enums only matter through the fields which refer to them.
+                (Definition::Enum(_), None) => {}
+            }
+        }
+    }
+
+    /// Fields are matched by their numbers. Numbers reserved by the older schema must not be used by the newer one.
+    fn message(&mut self, reader: &Message, writer: &Message, path: &str) {
+        let reader_fields = reader
+            .fields
+            .iter()
+            .map(|field| (field.number, field))
+            .collect::<HashMap<_, _>>();
+        let writer_fields = writer
+            .fields
+            .iter()
+            .map(|field| (field.number, field))
+            .collect::<HashMap<_, _>>();
+
+        for field in &writer.fields {
+            let field_path = format!("{}/fields/{}", path, field.name);
+            if self.writer_version > self.reader_version && reader.is_reserved(field.number) {
+                self.reused(field, &field_path, "reader");
+            }
+            match reader_fields.get(&field.number) {
+                Some(reader_field) => self.field(reader_field, field, &field_path),
+                None if field.oneof.is_some() => self.add(
+                    field_path,
+                    "absent".to_string(),
+                    field.to_string(),
+                    ProtobufIncompatibilityKind::OneofFieldRemoved,
+                    format!(
+                        "field {} of oneof {} is missing in the reader",
+                        field.name,
+                        field.oneof.as_deref().unwrap_or_default()
+                    ),
+                ),
+                None => {}
+            }
+        }
+        for field in &reader.fields {
+            let field_path = format!("{}/fields/{}", path, field.name);
+            if self.reader_version > self.writer_version && writer.is_reserved(field.number) {
+                self.reused(field, &field_path, "writer");
+            }
+            if field.label != Some(Label::Required) {
+                continue;
+            }
+            let writer_field = writer_fields.get(&field.number);
+            if writer_field.is_some_and(|written| written.label == Some(Label::Required)) {
+                continue;
+            }
+            let kind = if self.reader_version > self.writer_version {
+                ProtobufIncompatibilityKind::RequiredFieldAdded
+            } else {
+                ProtobufIncompatibilityKind::RequiredFieldRemoved
+            };
+            self.add(
+                field_path,
+                field.to_string(),
+                writer_field.map_or_else(|| "absent".to_string(), |written| written.to_string()),
+                kind,
+                format!(
+                    "field {} is required by the reader but the writer doesn't always write it",
+                    field.name
+                ),
+            );
+        }
+        self.oneofs(reader, &writer_fields, path);
+    }
+
+    fn reused(&mut self, field: &Field, path: &str, reserved_by: &str) {
+        self.add(
+            path.to_string(),
+            field.to_string(),
+            field.to_string(),
+            ProtobufIncompatibilityKind::FieldNumberReused,
+            format!(
+                "field number {} of {} is reserved in the {}",
+                field.number, field.name, reserved_by
+            ),
+        );
+    }
+
+    /// Fields which the writer sets independently can't be read as the cases of a single oneof
+    fn oneofs(&mut self, reader: &Message, writer_fields: &HashMap<u32, &Field>, path: &str) {
+        let mut oneofs: Vec<(&str, Vec<&Field>)> = vec![];
+        for field in &reader.fields {
+            if let Some(oneof) = &field.oneof {
+                match oneofs.iter_mut().find(|(name, _)| name == oneof) {
+                    Some((_, fields)) => fields.push(field),
+                    None => oneofs.push((oneof, vec![field])),
+                }
+            }
+        }
+        for (oneof, fields) in oneofs {
+            let written = fields
+                .iter()
+                .filter_map(|field| {
+                    writer_fields
+                        .get(&field.number)
+                        .map(|w| (*field, w.oneof.as_deref()))
+                })
+                .collect::<Vec<_>>();
+            let first = match written.first() {
+                Some((_, first)) => *first,
+                None => continue,
+            };
+            for (field, writer_oneof) in written.iter().skip(1) {
+                if writer_oneof.is_none() || *writer_oneof != first {
+                    self.add(
+                        format!("{}/fields/{}", path, field.name),
+                        format!("oneof {}", oneof),
+                        writer_oneof.map_or_else(|| "-".to_string(), |o| format!("oneof {}", o)),
+                        ProtobufIncompatibilityKind::FieldMovedToOneof,
+                        format!(
+                            "field {} is in oneof {} of the reader with fields the writer sets independently",
+                            field.name, oneof
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn field(&mut self, reader: &Field, writer: &Field, path: &str) {
+        let repeated = |field: &Field| field.label == Some(Label::Repeated);
+        let packed = repeated(reader) != repeated(writer) && self.is_packable(&writer.kind);
+        if !packed && self.is_readable(&reader.kind, &writer.kind) {
+            return;
+        }
+        let kind = if reader.name != writer.name {
+            ProtobufIncompatibilityKind::FieldNumberReused
+        } else {
+            ProtobufIncompatibilityKind::TypeMismatch
+        };
+        let message = if kind == ProtobufIncompatibilityKind::FieldNumberReused {
+            format!(
+                "field number {} is {} {} in the reader but {} {} in the writer",
+                reader.number, reader, reader.name, writer, writer.name
+            )
+        } else {
+            format!(
+                "field {} is {} in the reader but {} in the writer",
+                reader.name, reader, writer
+            )
+        };
+        self.add(
+            path.to_string(),
+            reader.to_string(),
+            writer.to_string(),
+            kind,
+            message,
+        );
+    }
+
+    /// Whether values of the type are packed when repeated
+    fn is_packable(&self, kind: &FieldType) -> bool {
+        match kind {
+            FieldType::Scalar(scalar) => scalar.is_packable(),
+            FieldType::Named(name) => {
+                matches!(
+                    self.writer_definitions.get(name.as_str()),
+                    Some(Definition::Enum(_))
+                )
+            }
+            FieldType::Map(..) => false,
+        }
+    }
+
+    fn is_readable(&self, reader: &FieldType, writer: &FieldType) -> bool {
+        let reader_enum =
+            |name: &str| matches!(self.reader_definitions.get(name), Some(Definition::Enum(_)));
+        let writer_enum =
+            |name: &str| matches!(self.writer_definitions.get(name), Some(Definition::Enum(_)));
+        match (reader, writer) {
+            (FieldType::Scalar(reader), FieldType::Scalar(writer)) => {
+                reader.wire_group() == writer.wire_group()
+            }
+            (FieldType::Named(reader), FieldType::Named(writer)) => {
+                self.reader.relative_name(reader) == self.writer.relative_name(writer)
+            }
+            (FieldType::Scalar(scalar), FieldType::Named(name)) => {
+                scalar.is_enum_compatible() && writer_enum(name)
+            }
+            (FieldType::Named(name), FieldType::Scalar(scalar)) => {
+                scalar.is_enum_compatible() && reader_enum(name)
+            }
+            (
+                FieldType::Map(reader_key, reader_value),
+                FieldType::Map(writer_key, writer_value),
+            ) => {
+                reader_key.wire_group() == writer_key.wire_group()
+                    && self.is_readable(reader_value, writer_value)
+            }
+            _ => false,
+        }
+    }
+
+    fn enumeration(&mut self, reader: &Enum, writer: &Enum, path: &str) {
+        for value in &writer.values {
+            let names = reader
+                .values
+                .iter()
+                .filter(|known| known.number == value.number)
+                .map(|known| known.name.as_str())
+                .collect::<Vec<_>>();
+            let value_path = format!("{}/values/{}", path, value.name);
+            if names.is_empty() {
+                // Open enums of proto3 keep values they don't know.
+                if self.reader.syntax == Syntax::Proto2 {
+                    self.add(
+                        value_path,
+                        "absent".to_string(),
+                        value.number.to_string(),
+                        ProtobufIncompatibilityKind::EnumSymbolRemoved,
+                        format!(
+                            "enum value {} = {} is missing in the reader",
+                            value.name, value.number
+                        ),
+                    );
+                }
+            } else if !names.contains(&value.name.as_str()) {
+                self.add(
+                    value_path,
+                    names.join(", "),
+                    value.name.clone(),
+                    ProtobufIncompatibilityKind::EnumValueChanged,
+                    format!(
+                        "enum value {} is {} in the reader but {} in the writer",
+                        value.number,
+                        names.join(", "),
+                        value.name
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn kind_of(definition: Definition<'_>) -> &'static str {
+    match definition {
+        Definition::Message(_) => "message",
+        Definition::Enum(_) => "enum",
+    }
+}
@@ -0,0 +1,437 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Protobuf schemas, parsed from `.proto` files, and their compatibility.
+//!
+//! Files are compared message by message, by their names relative to the package, and fields by their numbers.
+//! Wire compatible type changes, like `int32` to `int64`, are accepted as the Protobuf language guide describes.
+//! Imported files aren't loaded, types of them are compared by their names.
+//!
+//! ```rust
+//! use degauss::prelude::*;
+//! use degauss::protobuf;
+//!
+//! let old = protobuf::parse(r#"
+//!     syntax = "proto3";
+//!     package com.example;
+//!     message Movie {
+//!         string title = 1;
+//!         int32 year = 2;
+//!     }
+//! "#).unwrap();
+//! let new = protobuf::parse(r#"
+//!     syntax = "proto3";
+//!     package com.example;
+//!     message Movie {
+//!         string title = 1;
+//!         int64 year = 2;
+//!         reserved 3;
+//!     }
+//! "#).unwrap();
+//! assert!(DegaussCheck(DegaussCompatMode::FullTransitive).validate(&[old, new]));
+//! ```
+//!
+
+mod compat;
+mod parser;
+
+pub use compat::{explain_pair, ProtobufIncompatibilityKind};
+pub use parser::{parse, ProtoError};
+
+use crate::errors::DegaussError;
+use crate::format::DegaussSchema;
+use crate::format::Incompatibility;
+use crate::schema_registry::types::{SchemaPayload, SchemaType};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Write as _};
+use std::ops::RangeInclusive;
+use std::path::Path;
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+/// Parse a `.proto` file
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<ProtoFile, DegaussError> {
+    let path = path.as_ref();
+    parse_str_at(path, &crate::schema::read_file(path)?)
+}
+
+/// Parse the contents of a `.proto` file, errors name the given path
+pub fn parse_str_at(path: &Path, input: &str) -> Result<ProtoFile, DegaussError> {
+    parse(input).map_err(|source| DegaussError::ProtoFile {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Syntax a `.proto` file is written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syntax {
+    /// Enums are closed, unknown values aren't kept in their fields.
+    Proto2,
+    Proto3,
+}
+
+/// Label of a field
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Label {
+    Optional,
+    Required,
+    Repeated,
+}
+
+/// Scalar value types
+#[derive(EnumVariantNames, EnumString, Display, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub enum Scalar {
+    Double,
+    Float,
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Fixed32,
+    Fixed64,
+    Sfixed32,
+    Sfixed64,
+    Bool,
+    String,
+    Bytes,
+}
+
+impl Scalar {
+    /// Scalars of the same group can be read as each other, with truncation of numbers
+    pub fn wire_group(&self) -> u8 {
+        match self {
+            Scalar::Int32 | Scalar::Int64 | Scalar::Uint32 | Scalar::Uint64 | Scalar::Bool => 0,
+            Scalar::Sint32 | Scalar::Sint64 => 1,
+            Scalar::Fixed32 | Scalar::Sfixed32 => 2,
+            Scalar::Fixed64 | Scalar::Sfixed64 => 3,
+            Scalar::String | Scalar::Bytes => 4,
+            Scalar::Float => 5,
+            Scalar::Double => 6,
+        }
+    }
+
+    /// Enum values are written like these scalars
+    pub fn is_enum_compatible(&self) -> bool {
+        matches!(
+            self,
+            Scalar::Int32 | Scalar::Int64 | Scalar::Uint32 | Scalar::Uint64
+        )
+    }
+
+    /// Whether repeated fields of the scalar are packed, and can't be read as a single value
+    pub fn is_packable(&self) -> bool {
+        !matches!(self, Scalar::String | Scalar::Bytes)
+    }
+}
+
+/// Type of a field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    Scalar(Scalar),
+    /// Message or enum, by its full name when it is defined in the file.
+    Named(String),
+    Map(Scalar, Box<FieldType>),
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Scalar(scalar) => write!(f, "{}", scalar),
+            FieldType::Named(name) => write!(f, "{}", name),
+            FieldType::Map(key, value) => write!(f, "map<{}, {}>", key, value),
+        }
+    }
+}
+
+/// A field of a message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub number: u32,
+    /// Fields of proto3 without a label, maps and fields of oneofs don't have one.
+    pub label: Option<Label>,
+    pub kind: FieldType,
+    /// Oneof the field belongs to.
+    pub oneof: Option<String>,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.label {
+            Some(Label::Optional) => write!(f, "optional {}", self.kind),
+            Some(Label::Required) => write!(f, "required {}", self.kind),
+            Some(Label::Repeated) => write!(f, "repeated {}", self.kind),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+/// A message, with the messages and enums nested in it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Message {
+    /// Full name, the package included.
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub reserved_numbers: Vec<RangeInclusive<u32>>,
+    pub reserved_names: Vec<String>,
+}
+
+impl Message {
+    /// Whether a field number is reserved
+    pub fn is_reserved(&self, number: u32) -> bool {
+        self.reserved_numbers
+            .iter()
+            .any(|range| range.contains(&number))
+    }
+}
+
+/// A value of an enum
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnumValue {
+    pub name: String,
+    pub number: i32,
+}
+
+/// An enum
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Enum {
+    /// Full name, the package included.
+    pub name: String,
+    pub values: Vec<EnumValue>,
+}
+
+/// A message or enum defined in a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Definition<'a> {
+    Message(&'a Message),
+    Enum(&'a Enum),
+}
+
+/// A parsed `.proto` file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtoFile {
+    pub syntax: Syntax,
+    pub package: Option<String>,
+    pub imports: Vec<String>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    /// Text of the file as it is written, with the options and services the parsed file leaves out.
+    pub source: String,
+}
+
+impl ProtoFile {
+    /// Every message and enum of the file, nested ones included, by their full names
+    pub fn definitions(&self) -> HashMap<&str, Definition<'_>> {
+        fn collect<'a>(message: &'a Message, definitions: &mut HashMap<&'a str, Definition<'a>>) {
+            definitions.insert(&message.name, Definition::Message(message));
+            for enumeration in &message.enums {
+                definitions.insert(&enumeration.name, Definition::Enum(enumeration));
+            }
+            for nested in &message.messages {
+                collect(nested, definitions);
+            }
+        }
+        let mut definitions = HashMap::new();
+        for enumeration in &self.enums {
+            definitions.insert(enumeration.name.as_str(), Definition::Enum(enumeration));
+        }
+        for message in &self.messages {
+            collect(message, &mut definitions);
+        }
+        definitions
+    }
+
+    /// Name of a definition relative to the package of the file
+    pub fn relative_name<'a>(&self, name: &'a str) -> &'a str {
+        match &self.package {
+            Some(package) => name
+                .strip_prefix(package.as_str())
+                .and_then(|name| name.strip_prefix('.'))
+                .unwrap_or(name),
+            None => name,
+        }
+    }
+}
+
+/// Protobuf files, imported files aren't loaded
+impl DegaussSchema for ProtoFile {
+    fn parse_str_at(path: &Path, input: &str) -> Result<Self, DegaussError> {
+        parse_str_at(path, input)
+    }
+
+    /// The file without comments and options, with its definitions ordered by their names and fields by their numbers.
+    /// Types defined in the file are referred to by their full names.
+    fn canonical_form(&self) -> String {
+        let defined = self
+            .definitions()
+            .keys()
+            .map(|name| name.to_string())
+            .collect::<HashSet<_>>();
+        let mut out = String::new();
+        let syntax = match self.syntax {
+            Syntax::Proto2 => "proto2",
+            Syntax::Proto3 => "proto3",
+        };
+        let _ = writeln!(out, "syntax = \"{}\";", syntax);
+        if let Some(package) = &self.package {
+            let _ = writeln!(out, "package {};", package);
+        }
+        let mut imports = self.imports.iter().collect::<Vec<_>>();
+        imports.sort();
+        for import in imports {
+            let _ = writeln!(out, "import \"{}\";", import);
+        }
+        write_definitions(&self.messages, &self.enums, 0, &defined, &mut out);
+        out
+    }
+
+    fn schema_type(&self) -> SchemaType {
+        SchemaType::Protobuf
+    }
+
+    fn record_name(&self) -> Option<String> {
+        self.messages.first().map(|message| message.name.clone())
+    }
+
+    /// The file as it is written, schema registry keeps its options and services.
+    fn payload(&self) -> SchemaPayload {
+        SchemaPayload::protobuf(self.source.as_str())
+    }
+
+    fn explain_pair(
+        reader_version: usize,
+        reader: &Self,
+        writer_version: usize,
+        writer: &Self,
+    ) -> Vec<Incompatibility> {
+        explain_pair(reader_version, reader, writer_version, writer)
+    }
+}
+
+fn write_definitions(
+    messages: &[Message],
+    enums: &[Enum],
+    level: usize,
+    defined: &HashSet<String>,
+    out: &mut String,
+) {
+    let indent = "  ".repeat(level);
+    let mut enums = enums.iter().collect::<Vec<_>>();
+    enums.sort_by(|a, b| a.name.cmp(&b.name));
+    for enumeration in enums {
+        let _ = writeln!(out, "{}enum {} {{", indent, short_name(&enumeration.name));
+        for value in &enumeration.values {
+            let _ = writeln!(out, "{}  {} = {};", indent, value.name, value.number);
+        }
+        let _ = writeln!(out, "{}}}", indent);
+    }
+    let mut messages = messages.iter().collect::<Vec<_>>();
+    messages.sort_by(|a, b| a.name.cmp(&b.name));
+    for message in messages {
+        let _ = writeln!(out, "{}message {} {{", indent, short_name(&message.name));
+        let mut fields = message.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|field| field.number);
+        let mut oneofs = BTreeMap::<&str, Vec<&Field>>::new();
+        for field in fields {
+            match &field.oneof {
+                Some(oneof) => oneofs.entry(oneof).or_default().push(field),
+                None => {
+                    let label = match field.label {
+                        Some(Label::Optional) => "optional ",
+                        Some(Label::Required) => "required ",
+                        Some(Label::Repeated) => "repeated ",
+                        None => "",
+                    };
+                    let _ = writeln!(
+                        out,
+                        "{}  {}{} {} = {};",
+                        indent,
+                        label,
+                        type_name(&field.kind, defined),
+                        field.name,
+                        field.number
+                    );
+                }
+            }
+        }
+        for (oneof, fields) in oneofs {
+            let _ = writeln!(out, "{}  oneof {} {{", indent, oneof);
+            for field in fields {
+                let _ = writeln!(
+                    out,
+                    "{}    {} {} = {};",
+                    indent,
+                    type_name(&field.kind, defined),
+                    field.name,
+                    field.number
+                );
+            }
+            let _ = writeln!(out, "{}  }}", indent);
+        }
+        let mut numbers = message.reserved_numbers.clone();
+        numbers.sort_by_key(|range| *range.start());
+        if !numbers.is_empty() {
+            let numbers = numbers
+                .iter()
+                .map(|range| {
+                    if range.start() == range.end() {
+                        range.start().to_string()
+                    } else {
+                        format!("{} to {}", range.start(), range.end())
+                    }
+                })
+                .collect::<Vec<_>>();
+            let _ = writeln!(out, "{}  reserved {};", indent, numbers.join(", "));
+        }
+        let mut names = message.reserved_names.iter().collect::<Vec<_>>();
+        names.sort();
+        if !names.is_empty() {
+            let names = names
+                .iter()
+                .map(|name| format!("\"{}\"", name))
+                .collect::<Vec<_>>();
+            let _ = writeln!(out, "{}  reserved {};", indent, names.join(", "));
+        }
+        write_definitions(&message.messages, &message.enums, level + 1, defined, out);
+        let _ = writeln!(out, "{}}}", indent);
+    }
+}
+
+fn short_name(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+/// Type of a field, with the types defined in the file fully qualified
+fn type_name(kind: &FieldType, defined: &HashSet<String>) -> String {
+    match kind {
+        FieldType::Scalar(scalar) => scalar.to_string(),
+        FieldType::Named(name) if defined.contains(name) => format!(".{}", name),
+        FieldType::Named(name) => name.clone(),
+        FieldType::Map(key, value) => format!("map<{}, {}>", key, type_name(value, defined)),
+    }
+}
@@ -0,0 +1,536 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Parsing of `.proto` files.
+//!
+//! Options, services and extensions are skipped, they don't change what the messages look like on the wire.
+//!
+
+use super::{Enum, EnumValue, Field, FieldType, Label, Message, ProtoFile, Scalar, Syntax};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Syntax error of a `.proto` file
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("line {line}, column {column}: {message}")]
+pub struct ProtoError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Largest field number
+const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+/// Parse a `.proto` file, type names of fields are resolved to the full names of the definitions of the file
+pub fn parse(input: &str) -> Result<ProtoFile, ProtoError> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut file = parser.file()?;
+    let defined = file
+        .definitions()
+        .keys()
+        .map(|name| name.to_string())
+        .collect::<HashSet<_>>();
+    for message in file.messages.iter_mut() {
+        resolve(message, &defined);
+    }
+    Ok(file)
+}
+
+/// Resolve the type names of the fields, from the innermost scope outwards
+fn resolve(message: &mut Message, defined: &HashSet<String>) {
+    let scope = message.name.clone();
+    for field in message.fields.iter_mut() {
+        let kind = match &mut field.kind {
+            FieldType::Map(_, value) => value.as_mut(),
+            kind => kind,
+        };
+        if let FieldType::Named(name) = kind {
+            *name = resolve_name(name, &scope, defined);
+        }
+    }
+    for nested in message.messages.iter_mut() {
+        resolve(nested, defined);
+    }
+}
+
+fn resolve_name(name: &str, scope: &str, defined: &HashSet<String>) -> String {
+    if let Some(absolute) = name.strip_prefix('.') {
+        return absolute.to_string();
+    }
+    let first = name.split('.').next().unwrap_or(name);
+    let mut scope = Some(scope);
+    while let Some(current) = scope {
+        let candidate = format!("{}.{}", current, first);
+        if defined.contains(&candidate)
+            || defined
+                .iter()
+                .any(|d| d.starts_with(&format!("{}.", candidate)))
+        {
+            return format!("{}.{}", current, name);
+        }
+        scope = current.rsplit_once('.').map(|(outer, _)| outer);
+    }
+    name.to_string()
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T: Into<String>>(&self, message: T) -> ProtoError {
+        let before = &self.input[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+        ProtoError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    /// Skip whitespace and comments
+    fn skip(&mut self) -> Result<(), ProtoError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                match comment.find("*/") {
+                    Some(end) => self.pos += end + 4,
+                    None => return Err(self.error("unterminated comment")),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<char>, ProtoError> {
+        self.skip()?;
+        Ok(self.rest().chars().next())
+    }
+
+    fn eat(&mut self, c: char) -> Result<bool, ProtoError> {
+        if self.peek()? == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ProtoError> {
+        if self.eat(c)? {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", c)))
+        }
+    }
+
+    /// An identifier, dotted and fully qualified names included
+    fn ident(&mut self) -> Result<String, ProtoError> {
+        self.skip()?;
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(rest.len());
+        match rest.chars().next() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.' => {
+                self.pos += end;
+                Ok(rest[..end].to_string())
+            }
+            _ => Err(self.error("expected an identifier")),
+        }
+    }
+
+    /// The next identifier, without consuming it
+    fn peek_ident(&mut self) -> Result<Option<String>, ProtoError> {
+        let pos = self.pos;
+        let ident = self.ident().ok();
+        self.pos = pos;
+        Ok(ident)
+    }
+
+    /// A string literal in single or double quotes, adjacent literals are concatenated
+    fn string(&mut self) -> Result<String, ProtoError> {
+        let mut value = String::new();
+        loop {
+            let quote = match self.peek()? {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ if value.is_empty() => return Err(self.error("expected a string")),
+                _ => return Ok(value),
+            };
+            self.pos += 1;
+            let mut escaped = false;
+            let end = self.rest().find(|c: char| {
+                let end = c == quote && !escaped;
+                escaped = c == '\\' && !escaped;
+                end
+            });
+            match end {
+                Some(end) => {
+                    value.push_str(&self.rest()[..end]);
+                    self.pos += end + 1;
+                }
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// An integer literal, decimal, hexadecimal or octal
+    fn integer(&mut self) -> Result<i64, ProtoError> {
+        self.skip()?;
+        let negative = self.eat('-')?;
+        self.skip()?;
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let literal = &rest[..end];
+        let parsed = if let Some(hex) = literal
+            .strip_prefix("0x")
+            .or_else(|| literal.strip_prefix("0X"))
+        {
+            i64::from_str_radix(hex, 16)
+        } else if literal.len() > 1 && literal.starts_with('0') {
+            i64::from_str_radix(&literal[1..], 8)
+        } else {
+            literal.parse()
+        };
+        match parsed {
+            Ok(value) => {
+                self.pos += end;
+                Ok(if negative { -value } else { value })
+            }
+            Err(_) => Err(self.error("expected an integer")),
+        }
+    }
+
+    fn field_number(&mut self) -> Result<u32, ProtoError> {
+        match self.integer()? {
+            number @ 1..=0x1FFF_FFFF => Ok(number as u32),
+            _ => Err(self.error("field numbers are between 1 and 536870911")),
+        }
+    }
+
+    /// Skip everything up to the end of the statement, blocks and strings included
+    fn skip_statement(&mut self) -> Result<(), ProtoError> {
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                None => return Err(self.error("unexpected end of the file")),
+                Some('"' | '\'') => {
+                    self.string()?;
+                }
+                Some(c @ ('{' | '[' | '(' | '<')) => {
+                    self.pos += c.len_utf8();
+                    depth += 1;
+                }
+                Some(c @ ('}' | ']' | ')' | '>')) => {
+                    self.pos += c.len_utf8();
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 && c == '}' {
+                        // Blocks like services end without a semicolon.
+                        self.eat(';')?;
+                        return Ok(());
+                    }
+                }
+                Some(';') if depth == 0 => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(c) => self.pos += c.len_utf8(),
+            }
+        }
+    }
+
+    /// Skip the options of a field or an enum value, `[deprecated = true]`
+    fn skip_options(&mut self) -> Result<(), ProtoError> {
+        if self.peek()? != Some('[') {
+            return Ok(());
+        }
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                None => return Err(self.error("unterminated options")),
+                Some('"' | '\'') => {
+                    self.string()?;
+                }
+                Some(c) => {
+                    self.pos += c.len_utf8();
+                    match c {
+                        '[' | '{' => depth += 1,
+                        ']' | '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return Ok(());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn file(&mut self) -> Result<ProtoFile, ProtoError> {
+        let mut file = ProtoFile {
+            syntax: Syntax::Proto2,
+            package: None,
+            imports: vec![],
+            messages: vec![],
+            enums: vec![],
+            source: self.input.to_string(),
+        };
+        loop {
+            if self.peek()?.is_none() {
+                return Ok(file);
+            }
+            if self.eat(';')? {
+                continue;
+            }
+            match self.ident()?.as_str() {
+                "syntax" => {
+                    self.expect('=')?;
+                    file.syntax = match self.string()?.as_str() {
+                        "proto2" => Syntax::Proto2,
+                        "proto3" => Syntax::Proto3,
+                        syntax => return Err(self.error(format!("unsupported syntax {}", syntax))),
+                    };
+                    self.expect(';')?;
+                }
+                "edition" => return Err(self.error("editions are not supported")),
+                "package" => {
+                    file.package = Some(self.ident()?);
+                    self.expect(';')?;
+                }
+                "import" => {
+                    if let Some("public" | "weak") = self.peek_ident()?.as_deref() {
+                        self.ident()?;
+                    }
+                    file.imports.push(self.string()?);
+                    self.expect(';')?;
+                }
+                "message" => {
+                    let scope = file.package.clone();
+                    let syntax = file.syntax;
+                    file.messages.push(self.message(scope.as_deref(), syntax)?);
+                }
+                "enum" => {
+                    let scope = file.package.clone();
+                    file.enums.push(self.enumeration(scope.as_deref())?);
+                }
+                "option" | "service" | "extend" => self.skip_statement()?,
+                other => return Err(self.error(format!("unexpected `{}`", other))),
+            }
+        }
+    }
+
+    fn message(&mut self, scope: Option<&str>, syntax: Syntax) -> Result<Message, ProtoError> {
+        let name = full_name(scope, &self.ident()?);
+        self.expect('{')?;
+        let mut message = Message {
+            name,
+            ..Message::default()
+        };
+        loop {
+            if self.eat('}')? {
+                return Ok(message);
+            }
+            if self.eat(';')? {
+                continue;
+            }
+            match self.peek_ident()?.as_deref() {
+                None => return Err(self.error("expected `}` at the end of the message")),
+                Some("message") => {
+                    self.ident()?;
+                    let nested = self.message(Some(&message.name), syntax)?;
+                    message.messages.push(nested);
+                }
+                Some("enum") => {
+                    self.ident()?;
+                    let nested = self.enumeration(Some(&message.name))?;
+                    message.enums.push(nested);
+                }
+                Some("oneof") => {
+                    self.ident()?;
+                    let oneof = self.ident()?;
+                    self.expect('{')?;
+                    while !self.eat('}')? {
+                        if self.eat(';')? {
+                            continue;
+                        }
+                        if self.peek_ident()?.as_deref() == Some("option") {
+                            self.skip_statement()?;
+                            continue;
+                        }
+                        let mut field = self.field(None)?;
+                        field.oneof = Some(oneof.clone());
+                        message.fields.push(field);
+                    }
+                }
+                Some("reserved") => {
+                    self.ident()?;
+                    self.reserved(&mut message)?;
+                }
+                Some("option" | "extensions" | "extend") => self.skip_statement()?,
+                Some("group") => return Err(self.error("groups are not supported")),
+                Some(label @ ("optional" | "required" | "repeated")) => {
+                    let label = match label {
+                        "optional" => Label::Optional,
+                        "required" => Label::Required,
+                        _ => Label::Repeated,
+                    };
+                    self.ident()?;
+                    if self.peek_ident()?.as_deref() == Some("group") {
+                        return Err(self.error("groups are not supported"));
+                    }
+                    message.fields.push(self.field(Some(label))?);
+                }
+                Some(_) => {
+                    let field = self.field(None)?;
+                    if syntax == Syntax::Proto2 && !matches!(field.kind, FieldType::Map(..)) {
+                        return Err(self.error("fields of proto2 need a label"));
+                    }
+                    message.fields.push(field);
+                }
+            }
+        }
+    }
+
+    /// A field after its label, `type name = number [options];`
+    fn field(&mut self, label: Option<Label>) -> Result<Field, ProtoError> {
+        let kind = self.field_type()?;
+        let name = self.ident()?;
+        self.expect('=')?;
+        let number = self.field_number()?;
+        self.skip_options()?;
+        self.expect(';')?;
+        Ok(Field {
+            name,
+            number,
+            label,
+            kind,
+            oneof: None,
+        })
+    }
+
+    fn field_type(&mut self) -> Result<FieldType, ProtoError> {
+        let name = self.ident()?;
+        if name == "map" && self.eat('<')? {
+            let key = match self.field_type()? {
+                FieldType::Scalar(key)
+                    if key != Scalar::Double && key != Scalar::Float && key != Scalar::Bytes =>
+                {
+                    key
+                }
+                _ => return Err(self.error("map keys are integral or string scalars")),
+            };
+            self.expect(',')?;
+            let value = self.field_type()?;
+            self.expect('>')?;
+            return Ok(FieldType::Map(key, Box::new(value)));
+        }
+        Ok(match name.parse() {
+            Ok(scalar) => FieldType::Scalar(scalar),
+            Err(_) => FieldType::Named(name),
+        })
+    }
+
+    /// `reserved 2, 15, 9 to 11, 40 to max;` or `reserved "foo", "bar";`
+    fn reserved(&mut self, message: &mut Message) -> Result<(), ProtoError> {
+        loop {
+            match self.peek()? {
+                Some('"' | '\'') => message.reserved_names.push(self.string()?),
+                Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                    message.reserved_names.push(self.ident()?)
+                }
+                _ => {
+                    let start = self.field_number()?;
+                    let end = if self.peek_ident()?.as_deref() == Some("to") {
+                        self.ident()?;
+                        if self.peek_ident()?.as_deref() == Some("max") {
+                            self.ident()?;
+                            MAX_FIELD_NUMBER
+                        } else {
+                            self.field_number()?
+                        }
+                    } else {
+                        start
+                    };
+                    message.reserved_numbers.push(start..=end);
+                }
+            }
+            if !self.eat(',')? {
+                return self.expect(';');
+            }
+        }
+    }
+
+    fn enumeration(&mut self, scope: Option<&str>) -> Result<Enum, ProtoError> {
+        let name = full_name(scope, &self.ident()?);
+        self.expect('{')?;
+        let mut enumeration = Enum {
+            name,
+            values: vec![],
+        };
+        loop {
+            if self.eat('}')? {
+                return Ok(enumeration);
+            }
+            if self.eat(';')? {
+                continue;
+            }
+            match self.peek_ident()?.as_deref() {
+                None => return Err(self.error("expected `}` at the end of the enum")),
+                Some("option" | "reserved") => self.skip_statement()?,
+                Some(_) => {
+                    let name = self.ident()?;
+                    self.expect('=')?;
+                    let number = self.integer()?;
+                    let number = i32::try_from(number)
+                        .map_err(|_| self.error("enum values are 32 bit integers"))?;
+                    self.skip_options()?;
+                    self.expect(';')?;
+                    enumeration.values.push(EnumValue { name, number });
+                }
+            }
+        }
+    }
+}
+
+fn full_name(scope: Option<&str>, name: &str) -> String {
+    match scope {
+        Some(scope) if !scope.is_empty() => format!("{}.{}", scope, name),
+        _ => name.to_string(),
+    }
+}
@@ -0,0 +1,362 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Machine readable reports of the checks.
+//!
+//! Every check produces [`ReportCase`]s, which are collected in a [`Report`] and
+//! rendered in one of the [`ReportFormat`]s for CI dashboards.
+//!
+//! ```rust,no_run
+//! use apache_avro::Schema;
+//! use degauss::prelude::*;
+//!
+//! let schemas = vec![
+//!     Schema::parse_file("old.avsc").unwrap(),
+//!     Schema::parse_file("new.avsc").unwrap(),
+//! ];
+//! let case = DegaussCheck(DegaussCompatMode::Backward).report(&schemas, &[]);
+//! let report = Report::new("degauss", vec![case]);
+//! println!("{}", report.render(ReportFormat::Junit));
+//! ```
+//!
+
+use crate::compat::{CompatMatrix, DegaussCompatMode};
+use crate::format::Incompatibility;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+use strum_macros::{Display, EnumIter, EnumString, EnumVariantNames};
+
+const TOOL_NAME: &str = env!("CARGO_PKG_NAME");
+const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+const TOOL_URI: &str = env!("CARGO_PKG_HOMEPAGE");
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Possible formats to render a report in
+#[derive(
+    EnumIter,
+    EnumVariantNames,
+    EnumString,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+)]
+pub enum ReportFormat {
+    #[strum(serialize = "json")]
+    Json,
+    #[strum(serialize = "junit")]
+    Junit,
+    #[strum(serialize = "sarif")]
+    Sarif,
+    #[strum(serialize = "markdown")]
+    Markdown,
+}
+
+/// Severity of a finding
+#[derive(
+    EnumVariantNames,
+    EnumString,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    #[strum(serialize = "note")]
+    Note,
+    #[strum(serialize = "warning")]
+    Warning,
+    #[strum(serialize = "error")]
+    Error,
+}
+
+/// A single problem found by a check
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Identifier of the problem, e.g. `missing_default`.
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    /// File the problem is located in, if known.
+    pub file: Option<String>,
+    /// Location of the problem inside of the schema, if known.
+    pub path: Option<String>,
+}
+
+impl Finding {
+    /// Create a finding from a compatibility violation, `sources` are the files of the checked history.
+    pub fn from_incompatibility(incompat: &Incompatibility, sources: &[String]) -> Self {
+        Finding {
+            rule: incompat.kind.to_string(),
+            severity: Severity::Error,
+            message: incompat.to_string(),
+            file: sources.get(incompat.reader_version).cloned(),
+            path: Some(incompat.path.clone()),
+        }
+    }
+}
+
+/// Outcome of a single check, e.g. one compatibility mode over one history
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ReportCase {
+    pub name: String,
+    /// Compatibility mode the case was checked against, if any.
+    #[serde(serialize_with = "display_name")]
+    pub compatibility: Option<DegaussCompatMode>,
+    pub passed: bool,
+    pub findings: Vec<Finding>,
+}
+
+/// Collection of the outcomes of all the checks of a run
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub name: String,
+    pub passed: bool,
+    pub cases: Vec<ReportCase>,
+    /// Pairwise compatibility of the versions, if requested.
+    /// Only JSON and markdown reports can show it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<CompatMatrix>,
+}
+
+/// Reports name the compat modes like the command line does, e.g. `backward_transitive`,
+/// instead of their schema registry names
+fn display_name<S: Serializer>(
+    compatibility: &Option<DegaussCompatMode>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    compatibility
+        .map(|compatibility| compatibility.to_string())
+        .serialize(serializer)
+}
+
+fn xml_escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn markdown_escape(input: &str) -> String {
+    input.replace('|', "\\|").replace('\n', " ")
+}
+
+impl Report {
+    /// Create a report out of the given cases
+    pub fn new<T: Into<String>>(name: T, cases: Vec<ReportCase>) -> Self {
+        Report {
+            name: name.into(),
+            passed: cases.iter().all(|case| case.passed),
+            cases,
+            matrix: None,
+        }
+    }
+
+    /// Add the pairwise compatibility of the versions to the report
+    pub fn with_matrix(mut self, matrix: CompatMatrix) -> Self {
+        self.matrix = Some(matrix);
+        self
+    }
+
+    /// All findings of all the cases
+    pub fn findings(&self) -> impl Iterator<Item = &Finding> {
+        self.cases.iter().flat_map(|case| case.findings.iter())
+    }
+
+    /// Render the report in the given format
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => self.to_json(),
+            ReportFormat::Junit => self.to_junit(),
+            ReportFormat::Sarif => self.to_sarif(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    fn to_junit(&self) -> String {
+        let failures = self.cases.iter().filter(|case| !case.passed).count();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"{name}\" tests=\"{tests}\" failures=\"{failures}\">\n",
+            name = xml_escape(&self.name),
+            tests = self.cases.len(),
+            failures = failures,
+        ));
+        xml.push_str(&format!(
+            "  <testsuite name=\"{name}\" tests=\"{tests}\" failures=\"{failures}\">\n",
+            name = xml_escape(&self.name),
+            tests = self.cases.len(),
+            failures = failures,
+        ));
+        for case in &self.cases {
+            let classname = case
+                .compatibility
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| self.name.clone());
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\"",
+                xml_escape(&case.name),
+                xml_escape(&classname)
+            ));
+            if case.passed {
+                xml.push_str("/>\n");
+                continue;
+            }
+            xml.push_str(">\n");
+            let details = case
+                .findings
+                .iter()
+                .map(|f| f.message.clone())
+                .collect::<Vec<String>>()
+                .join("\n");
+            xml.push_str(&format!(
+                "      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                xml_escape(&format!("{} failed", case.name)),
+                xml_escape(&classname),
+                xml_escape(&details)
+            ));
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    fn to_sarif(&self) -> String {
+        let mut rules = self
+            .findings()
+            .map(|f| f.rule.clone())
+            .collect::<Vec<String>>();
+        rules.sort();
+        rules.dedup();
+
+        let results = self
+            .findings()
+            .map(|f| {
+                let mut result = json!({
+                    "ruleId": f.rule,
+                    "level": f.severity.to_string(),
+                    "message": { "text": f.message },
+                });
+                let mut location = serde_json::Map::new();
+                if let Some(file) = &f.file {
+                    location.insert(
+                        "physicalLocation".to_string(),
+                        json!({ "artifactLocation": { "uri": file } }),
+                    );
+                }
+                if let Some(path) = &f.path {
+                    location.insert(
+                        "logicalLocations".to_string(),
+                        json!([{ "fullyQualifiedName": path }]),
+                    );
+                }
+                if !location.is_empty() {
+                    result["locations"] = json!([location]);
+                }
+                result
+            })
+            .collect::<Vec<serde_json::Value>>();
+
+        let sarif = json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": TOOL_NAME,
+                        "version": TOOL_VERSION,
+                        "informationUri": TOOL_URI,
+                        "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                    }
+                },
+                "results": results,
+            }]
+        });
+        serde_json::to_string_pretty(&sarif).unwrap()
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut md = format!("## {}\n\n", markdown_escape(&self.name));
+        md.push_str("| Check | Compatibility | Status |\n|---|---|---|\n");
+        for case in &self.cases {
+            md.push_str(&format!(
+                "| {} | {} | {} |\n",
+                markdown_escape(&case.name),
+                case.compatibility
+                    .map(|c| c.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                if case.passed { "passed" } else { "failed" }
+            ));
+        }
+        if self.findings().next().is_some() {
+            md.push_str("\n| Check | Severity | Rule | File | Path | Message |\n|---|---|---|---|---|---|\n");
+            for case in &self.cases {
+                for f in &case.findings {
+                    md.push_str(&format!(
+                        "| {} | {} | {} | {} | {} | {} |\n",
+                        markdown_escape(&case.name),
+                        f.severity,
+                        markdown_escape(&f.rule),
+                        markdown_escape(f.file.as_deref().unwrap_or("-")),
+                        markdown_escape(f.path.as_deref().unwrap_or("-")),
+                        markdown_escape(&f.message)
+                    ));
+                }
+            }
+        }
+        if let Some(matrix) = &self.matrix {
+            md.push_str("\n| Reader \\ Writer |");
+            for writer in 0..matrix.len() {
+                md.push_str(&format!(" {} |", writer));
+            }
+            md.push_str(&format!("\n|---|{}\n", "---|".repeat(matrix.len())));
+            for (reader, row) in matrix.readable.iter().enumerate() {
+                md.push_str(&format!("| {} |", reader));
+                for readable in row {
+                    md.push_str(&format!(" {} |", readable));
+                }
+                md.push('\n');
+            }
+        }
+        md
+    }
+}
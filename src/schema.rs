@@ -8,27 +8,576 @@
 //! let schema = Schema::parse_file("path/to/avsc/file").unwrap();
 //! ```
 //!
+//! Schemas which refer to named types defined in other files are parsed with their dependencies.
+//! The definitions are inlined, so the parsed schemas are self contained.
+//!
+//! ```rust,no_run
+//! use degauss::prelude::*;
+//! let dependencies = SchemaDependencies::load(&["path/to/shared"]).unwrap();
+//! let schema = dependencies.parse_file("path/to/avsc/file").unwrap();
+//! ```
+//!
+//! Avro IDL (`.avdl`) and JSON protocol (`.avpr`) files are read by their extension,
+//! or explicitly with [`SchemaDependencies::parse_file_as`], selecting one of the named types of the protocol.
+//!
+//! ```rust,no_run
+//! use degauss::prelude::*;
+//! let dependencies = SchemaDependencies::new();
+//! let schema = dependencies
+//!     .parse_file_as("path/to/avdl/file", Some(SchemaFileFormat::Avdl), Some("com.example.Movie"))
+//!     .unwrap();
+//! ```
+//!
 
 use crate::errors::*;
+use crate::format::DegaussSchema;
+use crate::idl::{self, ImportKind};
+use crate::schema_registry::types::SchemaType;
 use apache_avro::Schema;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use strum_macros::{Display, EnumString, EnumVariantNames};
+
+pub(crate) const PRIMITIVES: [&str; 8] = [
+    "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+];
+
+/// Formats schema files are written in
+#[derive(EnumVariantNames, EnumString, Display, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SchemaFileFormat {
+    /// JSON schema of a single type
+    #[strum(serialize = "avsc")]
+    Avsc,
+    /// Avro IDL protocol
+    #[strum(serialize = "avdl")]
+    Avdl,
+    /// JSON protocol
+    #[strum(serialize = "avpr")]
+    Avpr,
+    /// Protobuf schema, see [`protobuf`](crate::protobuf)
+    #[strum(serialize = "proto")]
+    Proto,
+    /// JSON Schema, see [`json_schema`](crate::json_schema)
+    #[strum(serialize = "json")]
+    Json,
+}
+
+impl SchemaFileFormat {
+    /// Type of the schemas of the format, as schema-registry knows them
+    pub fn schema_type(&self) -> SchemaType {
+        match self {
+            SchemaFileFormat::Proto => SchemaType::Protobuf,
+            SchemaFileFormat::Json => SchemaType::Json,
+            _ => SchemaType::Avro,
+        }
+    }
+
+    /// Format of a file by its extension, unknown extensions are read as `avsc`.
+    /// So are `.json` files, which are as often Avro schemas as JSON Schemas, see [`detect`](Self::detect).
+    pub fn of<P: AsRef<Path>>(path: P) -> Self {
+        match path
+            .as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+        {
+            Some(SchemaFileFormat::Json) | None => SchemaFileFormat::Avsc,
+            Some(format) => format,
+        }
+    }
+
+    /// Format of a file by its extension, `.json` files are JSON Schemas when their contents look like one:
+    /// they have a `$schema`, or a type Avro doesn't have like `object`, `integer` or `number`.
+    pub fn detect<P: AsRef<Path>>(path: P, input: &str) -> Self {
+        let path = path.as_ref();
+        let json = path.extension().and_then(|ext| ext.to_str()) == Some("json");
+        if json && is_json_schema(input) {
+            SchemaFileFormat::Json
+        } else {
+            SchemaFileFormat::of(path)
+        }
+    }
+}
+
+fn is_json_schema(input: &str) -> bool {
+    let schema = match serde_json::from_str::<Value>(input) {
+        Ok(Value::Object(schema)) => schema,
+        _ => return false,
+    };
+    let json_schema_type =
+        |schema_type: &Value| matches!(schema_type.as_str(), Some("object" | "integer" | "number"));
+    schema.contains_key("$schema")
+        || match schema.get("type") {
+            Some(Value::Array(types)) => types.iter().any(json_schema_type),
+            Some(schema_type) => json_schema_type(schema_type),
+            None => false,
+        }
+}
 
 /// Parse a given file and convert it to Schema object
-pub trait FromFile {
+pub trait FromFile: Sized {
     /// Parses a given file path into a a valid Schema object
-    fn parse_file<P: AsRef<Path>>(path: P) -> Result<Schema, DegaussError>;
+    fn parse_file<P: AsRef<Path>>(path: P) -> Result<Self, DegaussError>;
 }
 
-/// Implements the FromFile trait for reading Schema from a given file
-impl FromFile for Schema {
+/// Implements the FromFile trait for reading every schema language from a given file
+impl<S: DegaussSchema> FromFile for S {
     /// Parses a given file into a a valid Schema object
-    fn parse_file<P: AsRef<Path>>(path: P) -> Result<Schema, DegaussError> {
-        let mut file = File::open(path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        let schema = Schema::parse_str(&contents)?;
-        Ok(schema)
+    fn parse_file<P: AsRef<Path>>(path: P) -> Result<Self, DegaussError> {
+        let path = path.as_ref();
+        S::parse_str_at(path, &read_file(path)?)
+    }
+}
+
+/// Schema files, and every `.avsc` file of the directories and their subdirectories, in order of their paths
+pub fn find_schema_files<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<PathBuf>, DegaussError> {
+    let mut files = vec![];
+    for path in paths {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            files.push(path.to_path_buf());
+            continue;
+        }
+        let file_error = |source| DegaussError::FileRead {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut entries = std::fs::read_dir(path)
+            .map_err(file_error)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, _>>()
+            .map_err(file_error)?;
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() {
+                files.extend(find_schema_files(&[entry])?);
+            } else if entry.extension().is_some_and(|ext| ext == "avsc") {
+                files.push(entry);
+            }
+        }
+    }
+    Ok(files)
+}
+
+pub(crate) fn read_file(path: &Path) -> Result<String, DegaussError> {
+    let mut contents = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|source| DegaussError::FileRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    Ok(contents)
+}
+
+fn read_json(path: &Path) -> Result<Value, DegaussError> {
+    parse_json(path, &read_file(path)?)
+}
+
+fn parse_json(path: &Path, input: &str) -> Result<Value, DegaussError> {
+    serde_json::from_str(input).map_err(|e| DegaussError::SchemaFile {
+        path: path.to_path_buf(),
+        source: apache_avro::Error::ParseSchemaJson(e),
+    })
+}
+
+/// Named types of a protocol, inheriting the namespace of the protocol.
+/// Errors are records to `apache_avro`.
+fn protocol_types(protocol: &Value) -> Vec<Value> {
+    let namespace = protocol.get("namespace").and_then(Value::as_str);
+    let types = match protocol.get("types").and_then(Value::as_array) {
+        Some(types) => types,
+        None => return vec![],
+    };
+    types
+        .iter()
+        .filter(|definition| definition.get("name").is_some())
+        .map(|definition| {
+            let mut definition = definition.clone();
+            if let Some(object) = definition.as_object_mut() {
+                if let Some(own) = namespace_of(object, namespace) {
+                    object.insert("namespace".to_string(), Value::String(own));
+                }
+                if object.get("type").and_then(Value::as_str) == Some("error") {
+                    object.insert("type".to_string(), Value::String("record".to_string()));
+                }
+            }
+            definition
+        })
+        .collect()
+}
+
+/// Namespace of a named type definition, `enclosing` is inherited when the definition doesn't have one
+pub(crate) fn namespace_of(
+    definition: &Map<String, Value>,
+    enclosing: Option<&str>,
+) -> Option<String> {
+    let name = definition.get("name").and_then(Value::as_str).unwrap_or("");
+    match name.rfind('.') {
+        Some(dot) => Some(name[..dot].to_string()),
+        None => definition
+            .get("namespace")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| enclosing.map(str::to_string)),
+    }
+}
+
+/// Fully qualified name of a named type definition or reference
+pub(crate) fn fullname(name: &str, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) if !name.contains('.') && !namespace.is_empty() => {
+            format!("{}.{}", namespace, name)
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// State of a walk over a schema which refers to dependencies
+struct Resolution {
+    /// Named types defined so far, later references to them stay as they are.
+    defined: HashSet<String>,
+    /// Dependencies referred to, in order of appearance.
+    references: Vec<String>,
+    /// Whether to replace references with the definitions.
+    expand: bool,
+}
+
+impl Resolution {
+    fn expanding(expand: bool) -> Self {
+        Resolution {
+            defined: HashSet::new(),
+            references: vec![],
+            expand,
+        }
+    }
+}
+
+/// Named types which are defined in their own files, for other schemas to refer to by name.
+/// Mirrors `Schema::parse_list`, but every parsed schema carries the definitions it uses.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaDependencies {
+    /// Definitions by their fully qualified name, with the file they are defined in.
+    types: HashMap<String, (PathBuf, Value)>,
+}
+
+impl SchemaDependencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the dependencies from schema files, and every `.avsc` file of the directories
+    pub fn load<P: AsRef<Path>>(paths: &[P]) -> Result<Self, DegaussError> {
+        let mut dependencies = Self::new();
+        for path in paths {
+            let path = path.as_ref();
+            if path.is_dir() {
+                dependencies.add_dir(path)?;
+            } else {
+                dependencies.add_file(path)?;
+            }
+        }
+        Ok(dependencies)
+    }
+
+    /// Whether there are no dependencies
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Fully qualified names of the named types defined by the dependencies
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.types.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Add the named type defined in the schema file
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), DegaussError> {
+        let path = path.as_ref();
+        self.add(path, &read_file(path)?)
+    }
+
+    /// Add the named types defined in every `.avsc` file of the directory and its subdirectories
+    pub fn add_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<(), DegaussError> {
+        for path in find_schema_files(&[dir])? {
+            self.add_file(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Add the named type defined in the given schema, `source` names it in errors
+    pub fn add<P: AsRef<Path>>(&mut self, source: P, input: &str) -> Result<(), DegaussError> {
+        let source = source.as_ref();
+        let schema_error = |source_error| DegaussError::SchemaFile {
+            path: source.to_path_buf(),
+            source: source_error,
+        };
+        let definition: Value = serde_json::from_str(input)
+            .map_err(|e| schema_error(apache_avro::Error::ParseSchemaJson(e)))?;
+        let name = match definition.as_object() {
+            Some(object) => match object.get("name").and_then(Value::as_str) {
+                Some(name) => fullname(name, namespace_of(object, None).as_deref()),
+                None => return Err(schema_error(apache_avro::Error::GetNameField)),
+            },
+            None => return Err(schema_error(apache_avro::Error::GetNameField)),
+        };
+        match self.types.get(&name) {
+            Some((_, existing)) if *existing == definition => Ok(()),
+            Some((first, _)) => Err(DegaussError::DuplicateType {
+                name,
+                first: first.clone(),
+                second: source.to_path_buf(),
+            }),
+            None => {
+                self.types.insert(name, (source.to_path_buf(), definition));
+                Ok(())
+            }
+        }
+    }
+
+    /// Parse a schema which may refer to the dependencies
+    pub fn parse_str(&self, input: &str) -> Result<Schema, apache_avro::Error> {
+        let schema: Value =
+            serde_json::from_str(input).map_err(apache_avro::Error::ParseSchemaJson)?;
+        let mut resolution = Resolution::expanding(true);
+        let inlined = self.inline(&schema, None, &mut resolution);
+        Schema::parse_str(&inlined.to_string())
+    }
+
+    /// Fully qualified names of the dependencies the schema refers to, without the ones they refer to.
+    /// These are the references of the schema in a schema registry.
+    pub fn references(&self, input: &str) -> Result<Vec<String>, apache_avro::Error> {
+        let schema: Value =
+            serde_json::from_str(input).map_err(apache_avro::Error::ParseSchemaJson)?;
+        let mut resolution = Resolution::expanding(false);
+        self.inline(&schema, None, &mut resolution);
+        Ok(resolution.references)
+    }
+
+    /// Definition of a dependency by its fully qualified name
+    pub fn definition(&self, name: &str) -> Option<&Value> {
+        self.types.get(name).map(|(_, definition)| definition)
+    }
+
+    /// Parse a schema file which may refer to the dependencies, the format is taken from its extension.
+    /// Protocols give their last named type.
+    pub fn parse_file<P: AsRef<Path>>(&self, path: P) -> Result<Schema, DegaussError> {
+        self.parse_file_as(path, None, None)
+    }
+
+    /// Parse every schema file of a history
+    pub fn parse_files<P: AsRef<Path>>(&self, paths: &[P]) -> Result<Vec<Schema>, DegaussError> {
+        paths.iter().map(|path| self.parse_file(path)).collect()
+    }
+
+    /// Parse a schema file in the given format, or the one of its extension.
+    /// `type_name` selects a named type of a protocol by its full or short name, the last one is taken otherwise.
+    pub fn parse_file_as<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: Option<SchemaFileFormat>,
+        type_name: Option<&str>,
+    ) -> Result<Schema, DegaussError> {
+        let path = path.as_ref();
+        self.parse_str_as(path, &read_file(path)?, format, type_name)
+    }
+
+    /// Parse the contents of a schema file, e.g. an older revision of it, as if they were read from `path`.
+    /// Imports of IDL files are read relative to `path`.
+    pub fn parse_str_as<P: AsRef<Path>>(
+        &self,
+        path: P,
+        input: &str,
+        format: Option<SchemaFileFormat>,
+        type_name: Option<&str>,
+    ) -> Result<Schema, DegaussError> {
+        let path = path.as_ref();
+        let (schema, dependencies) = self.resolve_str(path, input, format, type_name)?;
+        dependencies
+            .parse_str(&schema.to_string())
+            .map_err(|source| DegaussError::SchemaFile {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Read the definition of a schema file as written, along with the dependencies it may refer to.
+    /// Protocols add their other named types and the files IDL imports to the dependencies.
+    pub fn resolve_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: Option<SchemaFileFormat>,
+        type_name: Option<&str>,
+    ) -> Result<(Value, SchemaDependencies), DegaussError> {
+        let path = path.as_ref();
+        self.resolve_str(path, &read_file(path)?, format, type_name)
+    }
+
+    /// Resolve the contents of a schema file as if they were read from `path`, like [`Self::resolve_file`]
+    pub fn resolve_str<P: AsRef<Path>>(
+        &self,
+        path: P,
+        input: &str,
+        format: Option<SchemaFileFormat>,
+        type_name: Option<&str>,
+    ) -> Result<(Value, SchemaDependencies), DegaussError> {
+        let path = path.as_ref();
+        let (protocol, dependencies) = match format.unwrap_or_else(|| SchemaFileFormat::of(path)) {
+            SchemaFileFormat::Avsc => return Ok((parse_json(path, input)?, self.clone())),
+            SchemaFileFormat::Avpr => {
+                let protocol = parse_json(path, input)?;
+                let dependencies = self.with_protocol(path, &protocol)?;
+                (protocol, dependencies)
+            }
+            SchemaFileFormat::Avdl => self.with_idl(path, input, &mut vec![])?,
+            format @ (SchemaFileFormat::Proto | SchemaFileFormat::Json) => {
+                return Err(DegaussError::Usage(format!(
+                    "{} is a {} schema, which is only supported by validate and the schema-registry commands",
+                    path.display(),
+                    format.schema_type()
+                )))
+            }
+        };
+        let types = protocol_types(&protocol);
+        let selected = match type_name {
+            Some(type_name) => types.into_iter().find(|definition| {
+                let name = definition["name"].as_str().unwrap_or("");
+                let fullname = fullname(name, definition["namespace"].as_str());
+                fullname == type_name || name == type_name
+            }),
+            None => types.into_iter().last(),
+        };
+        match selected {
+            Some(schema) => Ok((schema, dependencies)),
+            None => Err(DegaussError::ProtocolType {
+                path: path.to_path_buf(),
+                message: match type_name {
+                    Some(type_name) => format!("{} is not defined", type_name),
+                    None => "no named types are defined".to_string(),
+                },
+            }),
+        }
+    }
+
+    /// Dependencies along with the named types of a protocol
+    fn with_protocol(&self, path: &Path, protocol: &Value) -> Result<Self, DegaussError> {
+        let mut dependencies = self.clone();
+        for definition in protocol_types(protocol) {
+            dependencies.add(path, &definition.to_string())?;
+        }
+        Ok(dependencies)
+    }
+
+    /// Parse an IDL file, returns the protocol it declares with the dependencies along with its own and imported types.
+    /// `importing` holds the IDL files being imported, to skip circular imports.
+    fn with_idl(
+        &self,
+        path: &Path,
+        input: &str,
+        importing: &mut Vec<PathBuf>,
+    ) -> Result<(Value, Self), DegaussError> {
+        let parsed = idl::parse(input).map_err(|source| DegaussError::IdlFile {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        importing.push(path.to_path_buf());
+        let mut dependencies = self.clone();
+        for import in parsed.imports {
+            let imported = path.parent().unwrap_or(Path::new("")).join(&import.path);
+            dependencies = match import.kind {
+                ImportKind::Schema => {
+                    dependencies.add_file(&imported)?;
+                    dependencies
+                }
+                ImportKind::Protocol => {
+                    dependencies.with_protocol(&imported, &read_json(&imported)?)?
+                }
+                ImportKind::Idl if importing.contains(&imported) => dependencies,
+                ImportKind::Idl => {
+                    let (protocol, imported_dependencies) =
+                        dependencies.with_idl(&imported, &read_file(&imported)?, importing)?;
+                    imported_dependencies.with_protocol(&imported, &protocol)?
+                }
+            };
+        }
+        importing.pop();
+        let dependencies = dependencies.with_protocol(path, &parsed.protocol)?;
+        Ok((parsed.protocol, dependencies))
+    }
+
+    /// Replace the first reference to every dependency with its definition, when expanding.
+    /// Types defined by the schema itself take precedence over the dependencies.
+    fn inline(
+        &self,
+        schema: &Value,
+        namespace: Option<&str>,
+        resolution: &mut Resolution,
+    ) -> Value {
+        match schema {
+            Value::String(name) if !PRIMITIVES.contains(&name.as_str()) => {
+                let name = fullname(name, namespace);
+                match self.types.get(&name) {
+                    Some((_, definition)) if !resolution.defined.contains(&name) => {
+                        resolution.references.push(name.clone());
+                        if resolution.expand {
+                            self.inline(definition, None, resolution)
+                        } else {
+                            resolution.defined.insert(name);
+                            schema.clone()
+                        }
+                    }
+                    _ => schema.clone(),
+                }
+            }
+            Value::Array(union) => Value::Array(
+                union
+                    .iter()
+                    .map(|variant| self.inline(variant, namespace, resolution))
+                    .collect(),
+            ),
+            Value::Object(object) => {
+                let mut object = object.clone();
+                match object.get("type").and_then(Value::as_str) {
+                    Some("record" | "error" | "enum" | "fixed") => {
+                        let own = namespace_of(&object, namespace);
+                        if let Some(name) = object.get("name").and_then(Value::as_str) {
+                            resolution.defined.insert(fullname(name, own.as_deref()));
+                        }
+                        if let Some(Value::Array(fields)) = object.get_mut("fields") {
+                            for field in fields.iter_mut() {
+                                if let Some(field_type) = field.get("type") {
+                                    field["type"] =
+                                        self.inline(field_type, own.as_deref(), resolution);
+                                }
+                            }
+                        }
+                    }
+                    Some("array") => {
+                        if let Some(items) = object.get("items") {
+                            let items = self.inline(items, namespace, resolution);
+                            object.insert("items".to_string(), items);
+                        }
+                    }
+                    Some("map") => {
+                        if let Some(values) = object.get("values") {
+                            let values = self.inline(values, namespace, resolution);
+                            object.insert("values".to_string(), values);
+                        }
+                    }
+                    _ => {
+                        if let Some(inner) = object.get("type") {
+                            let inner = self.inline(inner, namespace, resolution);
+                            object.insert("type".to_string(), inner);
+                        }
+                    }
+                }
+                Value::Object(object)
+            }
+            _ => schema.clone(),
+        }
     }
 }
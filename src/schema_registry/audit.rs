@@ -0,0 +1,161 @@
+// MIT License
+//
+// Copyright (c) 2021 Theo M. Bulut, Ankur Srivastava
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//!
+//! Audit of the schema histories registered in schema registry.
+//!
+//! Schema registry only checks compatibility when a version is registered, so a history can violate
+//! the compatibility of its subject, e.g. after it was set to `NONE` for a while and back again.
+//! The audit checks every registered version against the ones before it, with the compatibility which
+//! is in effect for the subject now.
+//!
+//! ```rust,no_run
+//! use degauss::prelude::*;
+//!
+//! let client = SchemaRegistryClient::new("http://localhost:8081", Auth::Skip).unwrap();
+//! let report = Report::new("degauss audit", client.audit().unwrap());
+//! println!("{}", report.render(ReportFormat::Json));
+//! ```
+//!
+
+use crate::compat::{DegaussCheck, DegaussCompatMode};
+use crate::errors::DegaussError;
+use crate::format::DegaussSchema;
+use crate::format::Incompatibility;
+use crate::json_schema::JsonSchema;
+use crate::protobuf::ProtoFile;
+use crate::report::{Finding, ReportCase, Severity};
+use crate::schema_registry::types::{SchemaType, SubjectName, SubjectVersionResponse};
+use crate::schema_registry::SchemaRegistryClient;
+use std::path::Path;
+
+impl SchemaRegistryClient {
+    /// Audit every subject of schema-registry, in alphabetical order.
+    /// Subjects whose history can't be read are reported as failed cases instead of stopping the audit.
+    pub fn audit(&self) -> Result<Vec<ReportCase>, DegaussError> {
+        let mut subjects = self.list_subjects()?;
+        subjects.sort();
+        Ok(subjects
+            .into_iter()
+            .map(|subject| {
+                self.audit_subject(&SubjectName::Raw(subject.clone()))
+                    .unwrap_or_else(|error| ReportCase {
+                        name: subject,
+                        compatibility: None,
+                        passed: false,
+                        findings: vec![Finding {
+                            rule: "audit_error".to_string(),
+                            severity: Severity::Error,
+                            message: error.to_string(),
+                            file: None,
+                            path: None,
+                        }],
+                    })
+            })
+            .collect())
+    }
+
+    /// Check every registered version of a given subject against the versions before it,
+    /// with the compatibility of the subject or the global one when the subject doesn't have its own.
+    /// Findings are located by `<subject>/versions/<version>`.
+    pub fn audit_subject(&self, subject: &SubjectName) -> Result<ReportCase, DegaussError> {
+        let name = subject.resolve(None)?;
+        let compatibility = self
+            .get_effective_compatibility(subject)?
+            .compatibility_level;
+        let history = self.get_history(subject)?;
+        let sources = history
+            .iter()
+            .map(|version| format!("{}/versions/{}", name, version.version))
+            .collect::<Vec<_>>();
+        let findings = self
+            .explain_history(&history, &sources, compatibility)?
+            .iter()
+            .map(|incompat| Finding::from_incompatibility(incompat, &sources))
+            .collect::<Vec<_>>();
+        Ok(ReportCase {
+            name,
+            compatibility: Some(compatibility),
+            passed: findings.is_empty(),
+            findings,
+        })
+    }
+
+    fn explain_history(
+        &self,
+        history: &[SubjectVersionResponse],
+        sources: &[String],
+        compatibility: DegaussCompatMode,
+    ) -> Result<Vec<Incompatibility>, DegaussError> {
+        let check = DegaussCheck(compatibility);
+        let schema_type = history
+            .first()
+            .map_or(SchemaType::Avro, SubjectVersionResponse::kind);
+        if let Some((_, source)) = history
+            .iter()
+            .zip(sources)
+            .find(|(version, _)| version.kind() != schema_type)
+        {
+            return Err(DegaussError::Usage(format!(
+                "{} isn't a {} schema like the versions before it",
+                source, schema_type
+            )));
+        }
+        Ok(match schema_type {
+            SchemaType::Avro => check.explain_history(
+                &history
+                    .iter()
+                    .zip(sources)
+                    .map(|(version, source)| {
+                        self.resolve_schema(version).map_err(|error| match error {
+                            DegaussError::Schema(source_error) => DegaussError::SchemaFile {
+                                path: source.into(),
+                                source: source_error,
+                            },
+                            error => error,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            SchemaType::Protobuf => check.explain_history(&parse::<ProtoFile>(history, sources)?),
+            SchemaType::Json => check.explain_history(&parse::<JsonSchema>(history, sources)?),
+            SchemaType::Other(other) => {
+                return Err(DegaussError::Usage(format!(
+                    "{} schemas can't be checked",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+/// Parse the registered versions, errors name the versions
+fn parse<S: DegaussSchema>(
+    history: &[SubjectVersionResponse],
+    sources: &[String],
+) -> Result<Vec<S>, DegaussError> {
+    history
+        .iter()
+        .zip(sources)
+        .map(|(version, source)| S::parse_str_at(Path::new(source), &version.schema))
+        .collect()
+}
@@ -22,6 +22,8 @@
 
 use crate::compat::DegaussCompatMode;
 use crate::errors::DegaussError;
+use crate::format::DegaussSchema;
+use crate::schema::SchemaDependencies;
 use crate::schema_registry::types::*;
 
 use crate::schema_registry::ResponseExt;
@@ -33,8 +35,16 @@ use isahc::{
     HttpClient, Request,
 };
 use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
+/// Schema registry error code of a missing subject
+pub const SUBJECT_NOT_FOUND: i32 = 40401;
+/// Schema registry error code of a schema which isn't registered under the subject
+pub const SCHEMA_NOT_FOUND: i32 = 40403;
+/// Schema registry error code of a subject without its own compatibility
+pub const SUBJECT_LEVEL_COMPATIBILITY_NOT_CONFIGURED: i32 = 40408;
+
 /// Create an instance of SchemaRegistryClient
 ///
 /// ```rust,no_run
@@ -54,13 +64,6 @@ pub struct SchemaRegistryClient {
 }
 
 impl SchemaRegistryClient {
-    fn get_subject_from_topic(&self, topic: &str, subject: SchemaSubjectType) -> String {
-        match subject {
-            SchemaSubjectType::Key => format!("{}-key", topic),
-            SchemaSubjectType::Value => format!("{}-value", topic),
-        }
-    }
-
     fn make_request<T: ?Sized + Serialize, U: serde::de::DeserializeOwned>(
         &self,
         url: &str,
@@ -89,7 +92,8 @@ impl SchemaRegistryClient {
             .httpclient
             .send(request)?
             .check_for_error()?
-            .json::<U>()?;
+            .json::<U>()
+            .map_err(DegaussError::SrResponse)?;
         Ok(resp)
     }
 
@@ -125,40 +129,158 @@ impl SchemaRegistryClient {
         })
     }
 
-    /// Register the given schema to schema-registry
-    pub fn register_schema(
-        self,
-        schema: &Schema,
-        topic: &str,
-        subject_type: SchemaSubjectType,
+    /// Register the given schema to schema-registry.
+    /// Record name strategies fall back to the record name of the schema.
+    pub fn register_schema<S: DegaussSchema>(
+        &self,
+        schema: &S,
+        subject: &SubjectName,
+    ) -> Result<SchemaRegistrationResponse, DegaussError> {
+        let subject = SubjectName::Raw(subject.resolve(Some(schema))?);
+        self.register_payload(&schema.payload(), &subject)
+    }
+
+    /// Register the given schema text with its references to schema-registry.
+    /// Record name strategies need the record name, as the schema isn't parsed.
+    pub fn register_payload(
+        &self,
+        payload: &SchemaPayload,
+        subject: &SubjectName,
     ) -> Result<SchemaRegistrationResponse, DegaussError> {
         let url = format!(
             "{url}/subjects/{subject}/versions",
             url = self.url,
-            subject = self.get_subject_from_topic(topic, subject_type),
+            subject = path_segment(subject)?,
         );
+        self.make_request(&url, isahc::http::Method::POST, Some(payload))
+    }
 
-        let payload = serde_json::json!({
-            "schema": schema.canonical_form()
-        });
-        let resp = self.make_request(&url, isahc::http::Method::POST, Some(&payload))?;
-        Ok(resp)
+    /// Look up the version of a subject the given schema text with its references is registered as.
+    /// Record name strategies need the record name, as the schema isn't parsed.
+    pub fn lookup_schema(
+        &self,
+        payload: &SchemaPayload,
+        subject: &SubjectName,
+    ) -> Result<SubjectVersionResponse, DegaussError> {
+        let url = format!(
+            "{url}/subjects/{subject}",
+            url = self.url,
+            subject = path_segment(subject)?,
+        );
+        self.make_request(&url, isahc::http::Method::POST, Some(payload))
+    }
+
+    /// Build the references of a schema to the named types of the dependencies it refers to.
+    /// Every referenced type is looked up under the subject `subject_of` gives for its fully qualified name,
+    /// after registering it there when `register` is set. Types referred to by the referenced types are handled first.
+    pub fn resolve_references<F: Fn(&str) -> SubjectName>(
+        &self,
+        dependencies: &SchemaDependencies,
+        schema: &str,
+        subject_of: F,
+        register: bool,
+    ) -> Result<Vec<SchemaReference>, DegaussError> {
+        self.reference_all(
+            dependencies,
+            schema,
+            &subject_of,
+            register,
+            &mut HashMap::new(),
+            &mut vec![],
+        )
+    }
+
+    fn reference_all(
+        &self,
+        dependencies: &SchemaDependencies,
+        schema: &str,
+        subject_of: &dyn Fn(&str) -> SubjectName,
+        register: bool,
+        resolved: &mut HashMap<String, SchemaReference>,
+        referring: &mut Vec<String>,
+    ) -> Result<Vec<SchemaReference>, DegaussError> {
+        let mut references = vec![];
+        for name in dependencies.references(schema)? {
+            if let Some(reference) = resolved.get(&name) {
+                references.push(reference.clone());
+                continue;
+            }
+            if referring.contains(&name) {
+                return Err(DegaussError::Usage(format!(
+                    "Named type {} refers to itself through {}, which can't be registered as references",
+                    name,
+                    referring.join(" -> ")
+                )));
+            }
+            let definition = match dependencies.definition(&name) {
+                Some(definition) => definition.to_string(),
+                None => continue,
+            };
+
+            referring.push(name.clone());
+            let payload = SchemaPayload {
+                schema_type: None,
+                references: self.reference_all(
+                    dependencies,
+                    &definition,
+                    subject_of,
+                    register,
+                    resolved,
+                    referring,
+                )?,
+                schema: definition,
+            };
+            referring.pop();
+
+            let subject = subject_of(&name);
+            if register {
+                self.register_payload(&payload, &subject)?;
+            }
+            let registered = self.lookup_schema(&payload, &subject)?;
+            let reference = SchemaReference {
+                name: name.clone(),
+                subject: registered.subject,
+                version: registered.version,
+            };
+            resolved.insert(name, reference.clone());
+            references.push(reference);
+        }
+        Ok(references)
+    }
+
+    /// Parse a registered schema, fetching the versions it references and the ones they reference.
+    pub fn resolve_schema(&self, version: &SubjectVersionResponse) -> Result<Schema, DegaussError> {
+        let mut dependencies = SchemaDependencies::new();
+        let mut pending = version.references.clone();
+        let mut fetched = HashSet::new();
+        while let Some(reference) = pending.pop() {
+            if !fetched.insert((reference.subject.clone(), reference.version)) {
+                continue;
+            }
+            let referenced = self.get_version(
+                &SubjectName::Raw(reference.subject.clone()),
+                SchemaVersion::Number(reference.version),
+            )?;
+            dependencies.add(
+                format!("{}/versions/{}", reference.subject, reference.version),
+                &referenced.schema,
+            )?;
+            pending.extend(referenced.references);
+        }
+        Ok(dependencies.parse_str(&version.schema)?)
     }
 
     /// Set the compatibility of a given subject.
-    /// Subject is evaluated using the topic name and the type of subject.
-    ///
-    /// subject = topic-key or topic-value
+    /// Subject is derived with the naming strategy of the given subject name.
     pub fn set_compatibility(
-        self,
-        topic: &str,
-        subject_type: SchemaSubjectType,
+        &self,
+        subject: &SubjectName,
         compatibility: DegaussCompatMode,
     ) -> Result<SubjectCompatibilitySetResponse, DegaussError> {
         let url = format!(
             "{url}/config/{subject}",
             url = self.url,
-            subject = self.get_subject_from_topic(topic, subject_type),
+            subject = path_segment(subject)?,
         );
 
         let payload = serde_json::json!({
@@ -170,47 +292,222 @@ impl SchemaRegistryClient {
     }
 
     /// Get the compatibility of a given subject.
-    /// Subject is evaluated using the topic name and the type of subject.
-    ///
-    /// subject = topic-key or topic-value
+    /// Subject is derived with the naming strategy of the given subject name.
     pub fn get_compatibility(
-        self,
-        topic: &str,
-        subject_type: SchemaSubjectType,
+        &self,
+        subject: &SubjectName,
     ) -> Result<SubjectCompatibilityGetResponse, DegaussError> {
         let url = format!(
             "{url}/config/{subject}",
             url = self.url,
-            subject = self.get_subject_from_topic(topic, subject_type),
+            subject = path_segment(subject)?,
+        );
+
+        self.get(&url)
+    }
+
+    /// Get the global compatibility of schema-registry, used by subjects without their own.
+    pub fn get_global_compatibility(
+        &self,
+    ) -> Result<SubjectCompatibilityGetResponse, DegaussError> {
+        let url = format!("{url}/config", url = self.url);
+        self.get(&url)
+    }
+
+    /// Get the compatibility which is in effect for a given subject.
+    /// Falls back to the global compatibility when the subject doesn't have its own.
+    pub fn get_effective_compatibility(
+        &self,
+        subject: &SubjectName,
+    ) -> Result<SubjectCompatibilityGetResponse, DegaussError> {
+        let url = format!(
+            "{url}/config/{subject}?defaultToGlobal=true",
+            url = self.url,
+            subject = path_segment(subject)?,
         );
+        match self.get(&url) {
+            Err(DegaussError::SrHttp { error_code, .. })
+                if error_code == SUBJECT_NOT_FOUND
+                    || error_code == SUBJECT_LEVEL_COMPATIBILITY_NOT_CONFIGURED =>
+            {
+                self.get_global_compatibility()
+            }
+            resp => resp,
+        }
+    }
 
+    /// Get every version of a given subject, from oldest to newest.
+    /// Subjects which don't exist have no versions.
+    pub fn get_history(
+        &self,
+        subject: &SubjectName,
+    ) -> Result<Vec<SubjectVersionResponse>, DegaussError> {
+        let versions = match self.list_versions(subject) {
+            Err(DegaussError::SrHttp { error_code, .. }) if error_code == SUBJECT_NOT_FOUND => {
+                return Ok(vec![])
+            }
+            versions => versions?,
+        };
+        versions
+            .into_iter()
+            .map(|version| self.get_version(subject, SchemaVersion::Number(version)))
+            .collect()
+    }
+
+    /// List the ids of the schemas which reference a given version of a subject.
+    /// Subject is derived with the naming strategy of the given subject name.
+    pub fn get_referenced_by(
+        &self,
+        subject: &SubjectName,
+        version: SchemaVersion,
+    ) -> Result<Vec<i32>, DegaussError> {
+        let url = format!(
+            "{url}/subjects/{subject}/versions/{version}/referencedby",
+            url = self.url,
+            subject = path_segment(subject)?,
+            version = version,
+        );
+        self.get(&url)
+    }
+
+    fn get<U: serde::de::DeserializeOwned>(&self, url: &str) -> Result<U, DegaussError> {
         let none: Option<String> = None;
-        let resp = self.make_request(&url, isahc::http::Method::GET, none.as_ref())?;
-        Ok(resp)
+        self.make_request(url, isahc::http::Method::GET, none.as_ref())
+    }
+
+    fn delete<U: serde::de::DeserializeOwned>(&self, url: &str) -> Result<U, DegaussError> {
+        let none: Option<String> = None;
+        self.make_request(url, isahc::http::Method::DELETE, none.as_ref())
+    }
+
+    /// List all the subjects registered in schema-registry
+    pub fn list_subjects(&self) -> Result<Vec<String>, DegaussError> {
+        let url = format!("{url}/subjects", url = self.url);
+        self.get(&url)
+    }
+
+    /// List the versions registered under a given subject.
+    /// Subject is derived with the naming strategy of the given subject name.
+    pub fn list_versions(&self, subject: &SubjectName) -> Result<Vec<i32>, DegaussError> {
+        let url = format!(
+            "{url}/subjects/{subject}/versions",
+            url = self.url,
+            subject = path_segment(subject)?,
+        );
+        self.get(&url)
+    }
+
+    /// Get a specific, or the latest, version of a given subject.
+    /// Subject is derived with the naming strategy of the given subject name.
+    pub fn get_version(
+        &self,
+        subject: &SubjectName,
+        version: SchemaVersion,
+    ) -> Result<SubjectVersionResponse, DegaussError> {
+        let url = format!(
+            "{url}/subjects/{subject}/versions/{version}",
+            url = self.url,
+            subject = path_segment(subject)?,
+            version = version,
+        );
+        self.get(&url)
+    }
+
+    /// Get the schema registered with the given global id
+    pub fn get_schema_by_id(&self, id: i32) -> Result<SchemaByIdResponse, DegaussError> {
+        let url = format!("{url}/schemas/ids/{id}", url = self.url, id = id);
+        self.get(&url)
+    }
+
+    /// List the subjects the schema with the given global id is registered under
+    pub fn get_subjects_by_id(&self, id: i32) -> Result<Vec<String>, DegaussError> {
+        let url = format!("{url}/schemas/ids/{id}/subjects", url = self.url, id = id);
+        self.get(&url)
+    }
+
+    /// List the subject-version pairs the schema with the given global id is registered as
+    pub fn get_versions_by_id(&self, id: i32) -> Result<Vec<SubjectVersionPair>, DegaussError> {
+        let url = format!("{url}/schemas/ids/{id}/versions", url = self.url, id = id);
+        self.get(&url)
+    }
+
+    /// Delete a subject with all of its versions, returns the deleted versions.
+    /// Permanent deletion only works on subjects which are already soft deleted.
+    pub fn delete_subject(
+        &self,
+        subject: &SubjectName,
+        permanent: bool,
+    ) -> Result<Vec<i32>, DegaussError> {
+        let url = format!(
+            "{url}/subjects/{subject}?permanent={permanent}",
+            url = self.url,
+            subject = path_segment(subject)?,
+            permanent = permanent,
+        );
+        self.delete(&url)
+    }
+
+    /// Delete a single version of a subject, returns the deleted version.
+    /// Permanent deletion only works on versions which are already soft deleted.
+    pub fn delete_version(
+        &self,
+        subject: &SubjectName,
+        version: SchemaVersion,
+        permanent: bool,
+    ) -> Result<i32, DegaussError> {
+        let url = format!(
+            "{url}/subjects/{subject}/versions/{version}?permanent={permanent}",
+            url = self.url,
+            subject = path_segment(subject)?,
+            version = version,
+            permanent = permanent,
+        );
+        self.delete(&url)
     }
 
     /// Check the compatibility with given Schema
-    pub fn check_compatibility(
-        self,
-        schema: &Schema,
-        topic: &str,
-        subject_type: SchemaSubjectType,
+    pub fn check_compatibility<S: DegaussSchema>(
+        &self,
+        schema: &S,
+        subject: &SubjectName,
+        verbose: bool,
+    ) -> Result<SchemaCompatibleResponse, DegaussError> {
+        let subject = SubjectName::Raw(subject.resolve(Some(schema))?);
+        self.check_payload_compatibility(&schema.payload(), &subject, verbose)
+    }
+
+    /// Check the compatibility of the given schema text with its references.
+    /// Record name strategies need the record name, as the schema isn't parsed.
+    pub fn check_payload_compatibility(
+        &self,
+        payload: &SchemaPayload,
+        subject: &SubjectName,
         verbose: bool,
     ) -> Result<SchemaCompatibleResponse, DegaussError> {
         let url = format!(
             "{url}/compatibility/subjects/{subject}/versions?verbose={verbose}",
             url = self.url,
-            subject = self.get_subject_from_topic(topic, subject_type),
+            subject = path_segment(subject)?,
             verbose = verbose,
         );
-        let payload = serde_json::json!({
-            "schema": schema.canonical_form()
-        });
-        let resp = self.make_request(&url, isahc::http::Method::POST, Some(&payload))?;
-        Ok(resp)
+        self.make_request(&url, isahc::http::Method::POST, Some(payload))
     }
 }
 
+/// Subject as a segment of a URL path, characters besides the unreserved ones of RFC 3986 are percent-encoded
+fn path_segment(subject: &SubjectName) -> Result<String, DegaussError> {
+    Ok(subject
+        .resolve(None)?
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect())
+}
+
 /// previous schema.
 #[cfg(test)]
 mod tests {
@@ -248,11 +545,9 @@ mod tests {
     #[ignore]
     fn test_register_schema() {
         let client = test_client();
-        let topic = random_chars(10, "test");
+        let subject = SubjectName::topic_name(random_chars(10, "test"), SchemaSubjectType::Value);
         let schema = test_schema();
-        let res = client
-            .register_schema(&schema, &topic, SchemaSubjectType::Value)
-            .unwrap();
+        let res = client.register_schema(&schema, &subject).unwrap();
         assert!(res.id > 0)
     }
 
@@ -260,9 +555,9 @@ mod tests {
     #[ignore]
     fn test_set_schema() {
         let client = test_client();
-        let topic = random_chars(10, "test");
+        let subject = SubjectName::topic_name(random_chars(10, "test"), SchemaSubjectType::Value);
         let res = client
-            .set_compatibility(&topic, SchemaSubjectType::Value, DegaussCompatMode::Forward)
+            .set_compatibility(&subject, DegaussCompatMode::Forward)
             .unwrap();
         assert!(res.compatibility == DegaussCompatMode::Forward.to_string());
     }
@@ -271,21 +566,15 @@ mod tests {
     #[ignore]
     fn test_set_and_get_schema() {
         let client = test_client();
-        let topic = random_chars(10, "test");
+        let subject = SubjectName::topic_name(random_chars(10, "test"), SchemaSubjectType::Value);
 
         let res = client
             .clone()
-            .set_compatibility(
-                &topic,
-                SchemaSubjectType::Value,
-                DegaussCompatMode::Backward,
-            )
+            .set_compatibility(&subject, DegaussCompatMode::Backward)
             .unwrap();
         assert!(res.compatibility == DegaussCompatMode::Backward.to_string());
 
-        let res = client
-            .get_compatibility(&topic, SchemaSubjectType::Value)
-            .unwrap();
+        let res = client.get_compatibility(&subject).unwrap();
 
         let want = DegaussCompatMode::Backward;
         assert!(
@@ -298,13 +587,196 @@ mod tests {
 
     #[test]
     #[ignore]
-    fn test_check_registrycompatibility() {
+    fn test_set_and_get_none_schema() {
         let client = test_client();
-        let topic = "test";
-        let schema = test_schema();
+        let subject = SubjectName::topic_name(random_chars(10, "test"), SchemaSubjectType::Value);
+
         let res = client
-            .check_compatibility(&schema, topic, SchemaSubjectType::Value, true)
+            .clone()
+            .set_compatibility(&subject, DegaussCompatMode::None)
+            .unwrap();
+        assert!(res.compatibility.eq_ignore_ascii_case("none"));
+
+        let res = client.get_compatibility(&subject).unwrap();
+        assert!(res.compatibility_level == DegaussCompatMode::None);
+    }
+
+    #[test]
+    fn test_get_response_with_none_compatibility() {
+        let res: SubjectCompatibilityGetResponse =
+            serde_json::from_str(r#"{"compatibilityLevel": "NONE"}"#).unwrap();
+        assert!(res.compatibility_level == DegaussCompatMode::None);
+
+        let res: SubjectCompatibilityGetResponse =
+            serde_json::from_str(r#"{"compatibilityLevel": "FULL_TRANSITIVE"}"#).unwrap();
+        assert!(res.compatibility_level == DegaussCompatMode::FullTransitive);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_register_list_get_and_delete() {
+        let client = test_client();
+        let subject = SubjectName::topic_name(random_chars(10, "test"), SchemaSubjectType::Value);
+        let schema = test_schema();
+        let registered = client.register_schema(&schema, &subject).unwrap();
+
+        let name = subject.resolve(None).unwrap();
+        assert!(client.list_subjects().unwrap().contains(&name));
+        assert!(client.list_versions(&subject).unwrap() == vec![1]);
+
+        let latest = client.get_version(&subject, SchemaVersion::Latest).unwrap();
+        assert!(latest.id == registered.id);
+        assert!(Schema::parse_str(&latest.schema).unwrap() == schema);
+
+        let by_id = client.get_schema_by_id(registered.id).unwrap();
+        assert!(Schema::parse_str(&by_id.schema).unwrap() == schema);
+        assert!(client
+            .get_subjects_by_id(registered.id)
+            .unwrap()
+            .contains(&name));
+        assert!(client
+            .get_versions_by_id(registered.id)
+            .unwrap()
+            .iter()
+            .any(|sv| sv.subject == name && sv.version == 1));
+
+        let deleted = client.delete_subject(&subject, false).unwrap();
+        assert!(deleted == vec![1]);
+        let deleted = client.delete_subject(&subject, true).unwrap();
+        assert!(deleted == vec![1]);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_get_history_and_effective_compatibility() {
+        let client = test_client();
+        let subject = SubjectName::topic_name(random_chars(10, "test"), SchemaSubjectType::Value);
+        assert!(client.get_history(&subject).unwrap().is_empty());
+
+        client.register_schema(&test_schema(), &subject).unwrap();
+        let history = client.get_history(&subject).unwrap();
+        assert!(history.len() == 1);
+        assert!(history[0].parse_schema().unwrap() == test_schema());
+
+        let global = client.get_global_compatibility().unwrap();
+        let effective = client.get_effective_compatibility(&subject).unwrap();
+        assert!(effective.compatibility_level == global.compatibility_level);
+    }
+
+    #[test]
+    fn test_schema_version_from_str() {
+        assert!("latest".parse::<SchemaVersion>().unwrap() == SchemaVersion::Latest);
+        assert!("3".parse::<SchemaVersion>().unwrap() == SchemaVersion::Number(3));
+        assert!("first".parse::<SchemaVersion>().is_err());
+        assert!(SchemaVersion::Number(3).to_string() == "3");
+    }
+
+    #[test]
+    fn test_resolve_subject_name() {
+        let schema = test_schema();
+        let subject = SubjectName::topic_name("movies", SchemaSubjectType::Key);
+        assert!(subject.resolve(Some(&schema)).unwrap() == "movies-key");
+
+        let subject = SubjectName::RecordName { record_name: None };
+        assert!(subject.resolve(Some(&schema)).unwrap() == "myrecord");
+        assert!(subject.resolve(None).is_err());
+
+        let subject = SubjectName::TopicRecordName {
+            topic: "movies".to_string(),
+            record_name: Some("com.example.Movie".to_string()),
+        };
+        assert!(subject.resolve(Some(&schema)).unwrap() == "movies-com.example.Movie");
+
+        let subject = SubjectName::Raw("movies".to_string());
+        assert!(subject.resolve(Some(&schema)).unwrap() == "movies");
+        assert!(subject.strategy().is_none());
+    }
+
+    #[test]
+    fn test_subjects_are_percent_encoded_in_paths() {
+        let subject = SubjectName::Raw("team/movies value?v=1".to_string());
+        assert_eq!(
+            path_segment(&subject).unwrap(),
+            "team%2Fmovies%20value%3Fv%3D1"
+        );
+        let subject = SubjectName::topic_name("movies", SchemaSubjectType::Value);
+        assert_eq!(path_segment(&subject).unwrap(), "movies-value");
+        assert_eq!(
+            path_segment(&SubjectName::Raw("café".to_string())).unwrap(),
+            "caf%C3%A9"
+        );
+    }
+
+    #[test]
+    fn test_version_response_with_references() {
+        let res: SubjectVersionResponse = serde_json::from_str(
+            r#"{"subject": "users-value", "id": 7, "version": 2, "schema": "{}",
+                "references": [{"name": "com.example.Address", "subject": "com.example.Address", "version": 1}]}"#,
+        )
+        .unwrap();
+        assert!(
+            res.references
+                == vec![SchemaReference {
+                    name: "com.example.Address".to_string(),
+                    subject: "com.example.Address".to_string(),
+                    version: 1,
+                }]
+        );
+
+        let res: SubjectVersionResponse = serde_json::from_str(
+            r#"{"subject": "users-value", "id": 7, "version": 2, "schema": "{}"}"#,
+        )
+        .unwrap();
+        assert!(res.references.is_empty());
+
+        let payload = serde_json::to_value(SchemaPayload::new(&test_schema())).unwrap();
+        assert!(payload.get("references").is_none());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_register_and_resolve_with_references() {
+        let client = test_client();
+        let dependencies = SchemaDependencies::load(&["tests/data/shared"]).unwrap();
+        let prefix = random_chars(10, "test");
+        let schema = std::fs::read_to_string("tests/data/user1.avsc").unwrap();
+        let references = client
+            .resolve_references(
+                &dependencies,
+                &schema,
+                |name| SubjectName::Raw(format!("{}-{}", prefix, name)),
+                true,
+            )
             .unwrap();
+        assert!(references.len() == 1);
+        assert!(references[0].name == "com.example.Address");
+
+        let subject = SubjectName::topic_name(prefix.clone(), SchemaSubjectType::Value);
+        let payload = SchemaPayload {
+            schema,
+            schema_type: None,
+            references,
+        };
+        client.register_payload(&payload, &subject).unwrap();
+
+        let latest = client.get_version(&subject, SchemaVersion::Latest).unwrap();
+        let resolved = client.resolve_schema(&latest).unwrap();
+        assert!(resolved == dependencies.parse_file("tests/data/user1.avsc").unwrap());
+
+        let address = SubjectName::Raw(format!("{}-com.example.Address", prefix));
+        let referenced_by = client
+            .get_referenced_by(&address, SchemaVersion::Number(1))
+            .unwrap();
+        assert!(referenced_by == vec![latest.id]);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_check_registrycompatibility() {
+        let client = test_client();
+        let subject = SubjectName::topic_name("test", SchemaSubjectType::Value);
+        let schema = test_schema();
+        let res = client.check_compatibility(&schema, &subject, true).unwrap();
 
         let want = true;
         assert!(
@@ -38,8 +38,12 @@
 //! // Use your client to interact with schema registry
 //!```
 //!
+mod audit;
 mod client;
-pub use client::SchemaRegistryClient;
+pub use client::{
+    SchemaRegistryClient, SCHEMA_NOT_FOUND, SUBJECT_LEVEL_COMPATIBILITY_NOT_CONFIGURED,
+    SUBJECT_NOT_FOUND,
+};
 mod response_ext;
 pub mod types;
 pub use response_ext::ResponseExt;
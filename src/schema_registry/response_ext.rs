@@ -53,7 +53,9 @@ impl ResponseExt for Response<Body> {
         match self.status().is_success() {
             true => Ok(Response::new(self.into_body())),
             false => {
-                let err_response = self.json::<SchemaRegistryErrResponse>()?;
+                let err_response = self
+                    .json::<SchemaRegistryErrResponse>()
+                    .map_err(DegaussError::SrResponse)?;
                 Err(DegaussError::SrHttp {
                     error_code: err_response.error_code,
                     message: err_response.message,
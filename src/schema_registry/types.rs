@@ -48,7 +48,9 @@ pub struct SchemaRegistryErrResponse {
     Ord,
     Hash,
     Debug,
+    serde::Deserialize,
 )]
+#[serde(rename_all = "lowercase")]
 pub enum SchemaSubjectType {
     #[strum(serialize = "key")]
     Key,
@@ -56,6 +58,113 @@ pub enum SchemaSubjectType {
     Value,
 }
 
+/// Strategy to derive the subject a schema is registered under
+#[derive(
+    EnumIter,
+    EnumVariantNames,
+    EnumString,
+    Display,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Debug,
+)]
+pub enum SubjectNameStrategy {
+    /// `<topic>-key` or `<topic>-value`
+    #[strum(serialize = "topic_name")]
+    TopicName,
+    /// `<fully qualified record name>`
+    #[strum(serialize = "record_name")]
+    RecordName,
+    /// `<topic>-<fully qualified record name>`
+    #[strum(serialize = "topic_record_name")]
+    TopicRecordName,
+}
+
+/// Subject a schema is registered under, either derived with a [`SubjectNameStrategy`] or given as is.
+///
+/// ```rust
+/// use degauss::prelude::*;
+///
+/// let subject = SubjectName::TopicRecordName {
+///     topic: "movies".to_string(),
+///     record_name: Some("com.example.Movie".to_string()),
+/// };
+/// assert_eq!(subject.resolve(None).unwrap(), "movies-com.example.Movie");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SubjectName {
+    TopicName {
+        topic: String,
+        subject_type: SchemaSubjectType,
+    },
+    /// The record name is taken from the schema when not given
+    RecordName {
+        record_name: Option<String>,
+    },
+    /// The record name is taken from the schema when not given
+    TopicRecordName {
+        topic: String,
+        record_name: Option<String>,
+    },
+    Raw(String),
+}
+
+impl SubjectName {
+    /// Subject of the topic name strategy, `<topic>-key` or `<topic>-value`
+    pub fn topic_name<T: Into<String>>(topic: T, subject_type: SchemaSubjectType) -> Self {
+        SubjectName::TopicName {
+            topic: topic.into(),
+            subject_type,
+        }
+    }
+
+    /// Strategy the subject is derived with, none for raw subjects
+    pub fn strategy(&self) -> Option<SubjectNameStrategy> {
+        match self {
+            SubjectName::TopicName { .. } => Some(SubjectNameStrategy::TopicName),
+            SubjectName::RecordName { .. } => Some(SubjectNameStrategy::RecordName),
+            SubjectName::TopicRecordName { .. } => Some(SubjectNameStrategy::TopicRecordName),
+            SubjectName::Raw(_) => None,
+        }
+    }
+
+    /// Resolve the name of the subject.
+    /// Record name strategies fall back to the fully qualified name of the given schema.
+    pub fn resolve(
+        &self,
+        schema: Option<&dyn crate::format::DegaussSchema>,
+    ) -> Result<String, crate::errors::DegaussError> {
+        let record_name = |record_name: &Option<String>| {
+            record_name
+                .clone()
+                .or_else(|| schema.and_then(|schema| schema.record_name()))
+                .ok_or_else(|| {
+                    crate::errors::DegaussError::Usage(format!(
+                        "Subject name strategy {} needs a record name or a schema with a named type",
+                        self.strategy().unwrap()
+                    ))
+                })
+        };
+        Ok(match self {
+            SubjectName::TopicName {
+                topic,
+                subject_type,
+            } => format!("{}-{}", topic, subject_type),
+            SubjectName::RecordName { record_name: name } => record_name(name)?,
+            SubjectName::TopicRecordName {
+                topic,
+                record_name: name,
+            } => format!("{}-{}", topic, record_name(name)?),
+            SubjectName::Raw(subject) => subject.clone(),
+        })
+    }
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct SchemaRegistrationResponse {
     pub id: i32,
@@ -64,6 +173,9 @@ pub struct SchemaRegistrationResponse {
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct SchemaCompatibleResponse {
     pub is_compatible: bool,
+    /// Reasons of the incompatibility, only sent back in verbose mode
+    #[serde(default)]
+    pub messages: Vec<String>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -76,3 +188,190 @@ pub struct SubjectCompatibilityGetResponse {
     #[serde(rename(serialize = "compatibilityLevel", deserialize = "compatibilityLevel"))]
     pub compatibility_level: DegaussCompatMode,
 }
+
+/// Version of a schema under a subject
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SchemaVersion {
+    Latest,
+    Number(i32),
+}
+
+impl std::fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaVersion::Latest => write!(f, "latest"),
+            SchemaVersion::Number(version) => write!(f, "{}", version),
+        }
+    }
+}
+
+impl std::str::FromStr for SchemaVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" | "-1" => Ok(SchemaVersion::Latest),
+            number => number
+                .parse::<i32>()
+                .map(SchemaVersion::Number)
+                .map_err(|_| format!("{} is neither `latest` nor a version number", number)),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SubjectVersionResponse {
+    pub subject: String,
+    pub id: i32,
+    pub version: i32,
+    pub schema: String,
+    #[serde(
+        rename = "schemaType",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub schema_type: Option<String>,
+    /// Subjects and versions the named types of the schema are registered under
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<SchemaReference>,
+}
+
+impl SubjectVersionResponse {
+    /// Parse the registered schema, only works for schemas without references.
+    /// See [`SchemaRegistryClient::resolve_schema`](crate::schema_registry::SchemaRegistryClient::resolve_schema) otherwise.
+    pub fn parse_schema(&self) -> Result<apache_avro::Schema, crate::errors::DegaussError> {
+        Ok(apache_avro::Schema::parse_str(&self.schema)?)
+    }
+
+    /// Type of the registered schema, schema-registry leaves it out for Avro schemas
+    pub fn kind(&self) -> SchemaType {
+        self.schema_type
+            .as_deref()
+            .map_or(SchemaType::Avro, |schema_type| {
+                let Ok(schema_type) = schema_type.parse();
+                schema_type
+            })
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SchemaByIdResponse {
+    pub schema: String,
+    #[serde(
+        rename = "schemaType",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub schema_type: Option<String>,
+    /// Subjects and versions the named types of the schema are registered under
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<SchemaReference>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SubjectVersionPair {
+    pub subject: String,
+    pub version: i32,
+}
+
+/// Reference of a schema to a named type registered under another subject
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SchemaReference {
+    /// Fully qualified name of the referenced type
+    pub name: String,
+    pub subject: String,
+    pub version: i32,
+}
+
+/// Types of schemas schema-registry accepts, it takes schemas as Avro when the type isn't given
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum SchemaType {
+    Avro,
+    Protobuf,
+    Json,
+    /// Type of a custom schema provider of the registry, as it is written.
+    Other(String),
+}
+
+impl std::fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaType::Avro => write!(f, "AVRO"),
+            SchemaType::Protobuf => write!(f, "PROTOBUF"),
+            SchemaType::Json => write!(f, "JSON"),
+            SchemaType::Other(schema_type) => write!(f, "{}", schema_type),
+        }
+    }
+}
+
+impl std::str::FromStr for SchemaType {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "AVRO" => SchemaType::Avro,
+            "PROTOBUF" => SchemaType::Protobuf,
+            "JSON" => SchemaType::Json,
+            other => SchemaType::Other(other.to_string()),
+        })
+    }
+}
+
+impl serde::Serialize for SchemaType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SchemaType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let schema_type = String::deserialize(deserializer)?;
+        // Types the registry knows but degauss doesn't are kept as `Other`, parsing never fails.
+        let Ok(schema_type) = schema_type.parse();
+        Ok(schema_type)
+    }
+}
+
+/// Schema as it is sent to schema-registry, with the references of its named types
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SchemaPayload {
+    pub schema: String,
+    #[serde(
+        rename = "schemaType",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub schema_type: Option<SchemaType>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<SchemaReference>,
+}
+
+impl SchemaPayload {
+    /// Payload of a self contained schema
+    pub fn new(schema: &apache_avro::Schema) -> Self {
+        SchemaPayload {
+            schema: schema.canonical_form(),
+            schema_type: None,
+            references: vec![],
+        }
+    }
+
+    /// Payload of a `.proto` file as it is written
+    pub fn protobuf<T: Into<String>>(schema: T) -> Self {
+        SchemaPayload::typed(schema, SchemaType::Protobuf)
+    }
+
+    /// Payload of a JSON Schema as it is written
+    pub fn json<T: Into<String>>(schema: T) -> Self {
+        SchemaPayload::typed(schema, SchemaType::Json)
+    }
+
+    /// Payload of a self contained schema text of the given type
+    pub fn typed<T: Into<String>>(schema: T, schema_type: SchemaType) -> Self {
+        SchemaPayload {
+            schema: schema.into(),
+            schema_type: Some(schema_type),
+            references: vec![],
+        }
+    }
+}
@@ -1,17 +1,63 @@
+use degauss::errors::DegaussError;
+
 /// Represents the exit status of a command
 pub enum Status {
     Success,
+    /// Any other failure
     Failure,
+    /// Schemas are not compatible, or a check like `diff` found something
+    Incompatible,
+    /// Schema files are missing or can't be parsed
+    InvalidInput,
+    /// Schema registry can't be reached or rejected the request
+    RegistryError,
+    /// Options given to the command can't be used together
+    UsageError,
 }
 
 impl Status {
     /// Convert a given status enum to its i32 representation
     /// Success = 0
-    /// Failure = -1
+    /// Incompatible = 1
+    /// InvalidInput = 2
+    /// RegistryError = 3
+    /// UsageError = 4
+    /// Failure = 5
     pub fn to_i32(&self) -> i32 {
         match self {
-            Status::Failure => -1,
             Status::Success => 0,
+            Status::Incompatible => 1,
+            Status::InvalidInput => 2,
+            Status::RegistryError => 3,
+            Status::UsageError => 4,
+            Status::Failure => 5,
+        }
+    }
+}
+
+impl From<&DegaussError> for Status {
+    fn from(error: &DegaussError) -> Self {
+        match error {
+            DegaussError::IO(_)
+            | DegaussError::Schema(_)
+            | DegaussError::Serde(_)
+            | DegaussError::FileRead { .. }
+            | DegaussError::FileWrite { .. }
+            | DegaussError::SchemaFile { .. }
+            | DegaussError::DataFile { .. }
+            | DegaussError::DuplicateType { .. }
+            | DegaussError::IdlFile { .. }
+            | DegaussError::ProtoFile { .. }
+            | DegaussError::JsonSchemaFile { .. }
+            | DegaussError::ProtocolType { .. }
+            | DegaussError::ConfigFile { .. }
+            | DegaussError::Git { .. } => Status::InvalidInput,
+            DegaussError::HTTPClient(_)
+            | DegaussError::Http(_)
+            | DegaussError::SrResponse(_)
+            | DegaussError::SrHttp { .. } => Status::RegistryError,
+            DegaussError::Usage(_) => Status::UsageError,
+            DegaussError::Custom(_) => Status::Failure,
         }
     }
 }
@@ -24,9 +70,30 @@ mod tests {
     #[test]
     fn test_status() {
         let status = Status::Failure;
-        assert!(status.to_i32() == -1);
+        assert!(status.to_i32() == 5);
 
         let status = Status::Success;
         assert!(status.to_i32() == 0);
     }
+
+    #[test]
+    fn test_status_from_error() {
+        let error = DegaussError::Usage("nothing to compare".to_string());
+        assert!(Status::from(&error).to_i32() == 4);
+
+        let error = DegaussError::SrHttp {
+            error_code: 40401,
+            message: "Subject not found".to_string(),
+        };
+        assert!(Status::from(&error).to_i32() == 3);
+
+        let error = DegaussError::SrResponse(serde_json::from_str::<i32>("<html>").unwrap_err());
+        assert!(Status::from(&error).to_i32() == 3);
+
+        let error = DegaussError::FileRead {
+            path: "missing.avsc".into(),
+            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+        };
+        assert!(Status::from(&error).to_i32() == 2);
+    }
 }
@@ -21,7 +21,13 @@
 // SOFTWARE.
 
 #![allow(dead_code)]
-use crate::compat::DegaussCompatMode;
+use crate::compat::{CompatMatrix, DegaussCompatMode};
+use crate::data::DataVerification;
+use crate::diff::SchemaChange;
+use crate::fingerprint::Fingerprints;
+use crate::format::Incompatibility;
+use crate::fuzz::FuzzOutcome;
+use crate::report::{Finding, ReportCase};
 use comfy_table::Table;
 use std::collections::HashMap;
 
@@ -38,8 +44,242 @@ use std::collections::HashMap;
 pub fn render(payload: &HashMap<DegaussCompatMode, bool>) {
     let mut table = Table::new();
     table.set_header(vec!["Compatibility", "Status"]);
-    for (key, value) in payload.iter() {
+    let mut rows = payload.iter().collect::<Vec<_>>();
+    rows.sort();
+    for (key, value) in rows {
         table.add_row(vec![key.to_string(), value.to_string()]);
     }
     println!("{}", table);
 }
+
+/// Render the compatibility violations, one per row, in tabular format
+///
+///```bash
+/// +--------+--------+-----------------+-------------+-------------+-----------------+
+/// | Reader | Writer | Path            | Reader type | Writer type | Reason          |
+/// +=================================================================================+
+/// | 1      | 0      | /fields/1       | string      | absent      | missing_default |
+/// +--------+--------+-----------------+-------------+-------------+-----------------+
+///```
+///
+pub fn render_incompatibilities(payload: &[Incompatibility]) {
+    let mut table = Table::new();
+    table.set_header(vec![
+        "Reader",
+        "Writer",
+        "Path",
+        "Reader type",
+        "Writer type",
+        "Reason",
+    ]);
+    for incompat in payload.iter() {
+        table.add_row(vec![
+            incompat.reader_version.to_string(),
+            incompat.writer_version.to_string(),
+            incompat.path.clone(),
+            incompat.reader_type.clone(),
+            incompat.writer_type.clone(),
+            incompat.kind.to_string(),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// Render the changes between two schemas, one per row, in tabular format
+///
+///```bash
+/// +------------+-------------+-----+--------+
+/// | Path       | Change      | Old | New    |
+/// +==========================================+
+/// | /fields/f2 | field_added | -   | string |
+/// +------------+-------------+-----+--------+
+///```
+///
+pub fn render_changes(payload: &[SchemaChange]) {
+    let mut table = Table::new();
+    table.set_header(vec!["Path", "Change", "Old", "New"]);
+    for change in payload.iter() {
+        table.add_row(vec![
+            change.path.clone(),
+            change.kind.to_string(),
+            change.old.clone().unwrap_or_else(|| "-".to_string()),
+            change.new.clone().unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// Render the pairwise compatibility of a history, rows are readers and columns are writers
+///
+///```bash
+/// +-----------------+------+-------+
+/// | Reader \ Writer | 0    | 1     |
+/// +================================+
+/// | 0               | true | true  |
+/// |-----------------+------+-------|
+/// | 1               | true | true  |
+/// +-----------------+------+-------+
+///```
+///
+pub fn render_matrix(payload: &CompatMatrix) {
+    let mut table = Table::new();
+    let mut header = vec!["Reader \\ Writer".to_string()];
+    header.extend((0..payload.len()).map(|writer| writer.to_string()));
+    table.set_header(header);
+    for (reader, row) in payload.readable.iter().enumerate() {
+        let mut cells = vec![reader.to_string()];
+        cells.extend(row.iter().map(|readable| readable.to_string()));
+        table.add_row(cells);
+    }
+    println!("{}", table);
+}
+
+/// Render the verification of the data files, one per row, in tabular format
+///
+///```bash
+/// +------------------+---------+--------+----------------------------------------+
+/// | File             | Records | Passed | First failure                          |
+/// +==============================================================================+
+/// | data/movies.avro | 3       | false  | record 3: Missing field in record: "f" |
+/// +------------------+---------+--------+----------------------------------------+
+///```
+///
+pub fn render_data_verifications(payload: &[(String, DataVerification)]) {
+    let mut table = Table::new();
+    table.set_header(vec!["File", "Records", "Passed", "First failure"]);
+    for (source, verification) in payload.iter() {
+        table.add_row(vec![
+            source.clone(),
+            verification.records.to_string(),
+            verification.passed().to_string(),
+            verification
+                .failure
+                .as_ref()
+                .map(|failure| format!("record {}: {}", failure.index, failure.error))
+                .unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// Render the outcome of a fuzzing run and its smallest failing datum, in tabular format
+///
+///```bash
+/// +------------+------+--------+----------+--------+
+/// | Compatible | Seed | Datums | Resolved | Agrees |
+/// +================================================+
+/// | false      | 42   | 3      | 2        | true   |
+/// +------------+------+--------+----------+--------+
+/// +-----------------------------------+----------------------------------+
+/// | Failing datum                     | Error                            |
+/// +======================================================================+
+/// | {"f1":"","f2":"CLUBS"}            | Enum default "CLUBS" is not ...  |
+/// +-----------------------------------+----------------------------------+
+///```
+///
+pub fn render_fuzz(payload: &FuzzOutcome) {
+    let mut table = Table::new();
+    table.set_header(vec!["Compatible", "Seed", "Datums", "Resolved", "Agrees"]);
+    table.add_row(vec![
+        payload.compatible.to_string(),
+        payload.seed.to_string(),
+        payload.datums.to_string(),
+        payload.resolved.to_string(),
+        payload.agrees().to_string(),
+    ]);
+    println!("{}", table);
+    if let Some(failure) = &payload.failure {
+        let mut table = Table::new();
+        table.set_header(vec!["Failing datum", "Error"]);
+        table.add_row(vec![failure.datum_json(), failure.error.clone()]);
+        println!("{}", table);
+    }
+}
+
+/// Render the fingerprints of schemas, one per row, followed by their canonical forms
+///
+///```bash
+/// +-------------+------------------+----------------------------------+------------------------------------------------------------------+
+/// | Schema      | CRC-64-AVRO      | MD5                              | SHA-256                                                          |
+/// +====================================================================================================================================+
+/// | schema.avsc | 8a8f25cce724dd63 | 9b41ef67651c18488a8b08bb67c75699 | f072cbec3bf8841871d4284230c5e983dc211a56837aed862487148f947d1a1f |
+/// +-------------+------------------+----------------------------------+------------------------------------------------------------------+
+/// schema.avsc: "null"
+///```
+///
+pub fn render_fingerprints(payload: &[(String, Fingerprints)]) {
+    let mut table = Table::new();
+    table.set_header(vec!["Schema", "CRC-64-AVRO", "MD5", "SHA-256"]);
+    for (source, fingerprints) in payload.iter() {
+        table.add_row(vec![
+            source.clone(),
+            fingerprints.rabin.clone(),
+            fingerprints.md5.clone(),
+            fingerprints.sha256.clone(),
+        ]);
+    }
+    println!("{}", table);
+    for (source, fingerprints) in payload.iter() {
+        println!("{}: {}", source, fingerprints.canonical_form);
+    }
+}
+
+/// Render the outcome of checks, one per row, in tabular format
+///
+///```bash
+/// +--------------+---------------+--------+----------+
+/// | Check        | Compatibility | Passed | Findings |
+/// +==================================================+
+/// | movies-value | backward      | false  | 1        |
+/// +--------------+---------------+--------+----------+
+///```
+///
+pub fn render_cases(payload: &[ReportCase]) {
+    println!("{}", cases_table(payload));
+}
+
+/// Render the outcomes of checks like [`render_cases`], to stderr so a report on stdout stays parseable
+pub fn summarize_cases(payload: &[ReportCase]) {
+    eprintln!("{}", cases_table(payload));
+}
+
+fn cases_table(payload: &[ReportCase]) -> Table {
+    let mut table = Table::new();
+    table.set_header(vec!["Check", "Compatibility", "Passed", "Findings"]);
+    for case in payload.iter() {
+        table.add_row(vec![
+            case.name.clone(),
+            case.compatibility
+                .map(|compat| compat.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            case.passed.to_string(),
+            case.findings.len().to_string(),
+        ]);
+    }
+    table
+}
+
+/// Render the findings of checks, one per row, in tabular format
+///
+///```bash
+/// +-------------+----------+-------------+-------+--------------------------------------------+
+/// | File        | Severity | Rule        | Path  | Message                                    |
+/// +===========================================================================================+
+/// | movies.avsc | warning  | type_naming | /     | record movie is not named in PascalCase    |
+/// +-------------+----------+-------------+-------+--------------------------------------------+
+///```
+///
+pub fn render_findings(payload: &[Finding]) {
+    let mut table = Table::new();
+    table.set_header(vec!["File", "Severity", "Rule", "Path", "Message"]);
+    for finding in payload.iter() {
+        table.add_row(vec![
+            finding.file.clone().unwrap_or_else(|| "-".to_string()),
+            finding.severity.to_string(),
+            finding.rule.clone(),
+            finding.path.clone().unwrap_or_else(|| "-".to_string()),
+            finding.message.clone(),
+        ]);
+    }
+    println!("{}", table);
+}
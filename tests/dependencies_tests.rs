@@ -0,0 +1,102 @@
+/// Schemas which refer to named types defined in other files.
+#[cfg(test)]
+mod dependencies {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    fn history() -> Vec<Schema> {
+        SchemaDependencies::load(&["tests/data/shared"])
+            .unwrap()
+            .parse_files(&["tests/data/user1.avsc", "tests/data/user2.avsc"])
+            .unwrap()
+    }
+
+    #[test]
+    fn schemas_without_dependencies_fail_to_parse() {
+        assert!(matches!(
+            Schema::parse_file("tests/data/user1.avsc"),
+            Err(DegaussError::SchemaFile { .. })
+        ));
+    }
+
+    #[test]
+    fn dependencies_are_loaded_from_files_and_directories() {
+        let dependencies = SchemaDependencies::load(&["tests/data/shared"]).unwrap();
+        assert_eq!(
+            dependencies.names(),
+            vec!["com.example.Address", "com.example.Money"]
+        );
+
+        let dependencies = SchemaDependencies::load(&["tests/data/shared/address.avsc"]).unwrap();
+        assert_eq!(dependencies.names(), vec!["com.example.Address"]);
+        assert!(dependencies.parse_file("tests/data/user1.avsc").is_ok());
+        assert!(dependencies.parse_file("tests/data/user2.avsc").is_err());
+    }
+
+    #[test]
+    fn parsed_schemas_are_self_contained() {
+        let schemas = history();
+        let canonical = Schema::parse_str(&schemas[1].canonical_form()).unwrap();
+        assert_eq!(canonical.canonical_form(), schemas[1].canonical_form());
+        assert!(schemas[1]
+            .canonical_form()
+            .contains(r#"{"name":"com.example.Money","type":"record""#));
+    }
+
+    #[test]
+    fn dependencies_are_checked_in_every_compat_mode() {
+        let schemas = history();
+        assert!(DegaussCheck(DegaussCompatMode::Forward).validate(&schemas));
+        assert!(!DegaussCheck(DegaussCompatMode::Backward).validate(&schemas));
+
+        let incompatibilities = DegaussCheck(DegaussCompatMode::Full).explain(&schemas);
+        assert_eq!(incompatibilities.len(), 1);
+        assert_eq!(incompatibilities[0].path, "/fields/3");
+        assert_eq!(
+            incompatibilities[0].kind,
+            AvroIncompatibilityKind::MissingDefault.into()
+        );
+    }
+
+    #[test]
+    fn conflicting_definitions_are_rejected() {
+        let mut dependencies = SchemaDependencies::new();
+        dependencies
+            .add(
+                "a.avsc",
+                r#"{"type": "fixed", "name": "com.example.Id", "size": 16}"#,
+            )
+            .unwrap();
+        dependencies
+            .add(
+                "b.avsc",
+                r#"{"type": "fixed", "name": "com.example.Id", "size": 16}"#,
+            )
+            .unwrap();
+        assert!(matches!(
+            dependencies.add(
+                "c.avsc",
+                r#"{"type": "fixed", "name": "Id", "namespace": "com.example", "size": 8}"#
+            ),
+            Err(DegaussError::DuplicateType { .. })
+        ));
+    }
+
+    #[test]
+    fn references_are_the_directly_referred_dependencies() {
+        let dependencies = SchemaDependencies::load(&["tests/data/shared"]).unwrap();
+        let user = std::fs::read_to_string("tests/data/user2.avsc").unwrap();
+        assert_eq!(
+            dependencies.references(&user).unwrap(),
+            vec!["com.example.Address", "com.example.Money"]
+        );
+
+        let money = dependencies.definition("com.example.Money").unwrap();
+        assert!(dependencies
+            .references(&money.to_string())
+            .unwrap()
+            .is_empty());
+        assert!(dependencies.definition("com.example.User").is_none());
+    }
+}
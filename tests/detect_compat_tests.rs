@@ -0,0 +1,87 @@
+/// Detection: evaluate every compatibility mode over a history and find the strongest one which holds.
+#[cfg(test)]
+mod detect_compat {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    #[test]
+    fn every_compat_mode_is_evaluated() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        let matrix = DegaussCheck::tabular_validate_all(&schemas);
+        assert_eq!(matrix.len(), 7);
+        assert!(matrix[&DegaussCompatMode::None]);
+        assert!(matrix[&DegaussCompatMode::Forward]);
+        assert!(!matrix[&DegaussCompatMode::Backward]);
+        assert!(!matrix[&DegaussCompatMode::Full]);
+    }
+
+    #[test]
+    fn adding_fields_with_defaults_is_fully_transitive() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema2.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema8.avsc").unwrap(),
+        ];
+        assert_eq!(
+            DegaussCheck::strongest(&schemas),
+            vec![DegaussCompatMode::FullTransitive]
+        );
+    }
+
+    #[test]
+    fn adding_a_field_wo_default_is_at_most_forward_transitive() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        assert_eq!(
+            DegaussCheck::strongest(&schemas),
+            vec![DegaussCompatMode::ForwardTransitive]
+        );
+    }
+
+    #[test]
+    fn incompatible_type_change_only_satisfies_none() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_str(r#"{"type": "record", "name": "myrecord", "fields": [{"name": "f1", "type": "int"}]}"#)
+                .unwrap(),
+        ];
+        assert_eq!(
+            DegaussCheck::strongest(&schemas),
+            vec![DegaussCompatMode::None]
+        );
+        assert!(DegaussCheck(DegaussCompatMode::None).validate(&schemas));
+    }
+
+    #[test]
+    fn modes_which_dont_imply_each_other_are_both_strongest() {
+        use DegaussCompatMode::*;
+        assert!(FullTransitive.implies(Backward));
+        assert!(Full.implies(Forward));
+        assert!(!Full.implies(BackwardTransitive));
+        assert!(!BackwardTransitive.implies(Forward));
+        assert!(Forward.implies(None));
+
+        let matrix = [
+            (None, true),
+            (Backward, true),
+            (BackwardTransitive, false),
+            (Forward, true),
+            (ForwardTransitive, true),
+            (Full, false),
+            (FullTransitive, false),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        assert_eq!(
+            DegaussCheck::strongest_of(&matrix),
+            vec![Backward, ForwardTransitive]
+        );
+    }
+}
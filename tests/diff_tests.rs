@@ -0,0 +1,94 @@
+/// Diff: semantic changes between an old and a new schema, independent of the JSON layout.
+#[cfg(test)]
+mod diff {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    #[test]
+    fn same_schema_with_different_layout_has_no_changes() {
+        let old = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        let new = Schema::parse_str(
+            r#"{"fields":[{"name":"f1","type":"string"}],"name":"myrecord","type":"record"}"#,
+        )
+        .unwrap();
+        assert!(diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_fields_are_reported() {
+        let old = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        let new = Schema::parse_file("tests/data/schema8.avsc").unwrap();
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes
+            .iter()
+            .all(|c| c.kind == SchemaChangeKind::FieldAdded));
+        assert_eq!(changes[0].path, "/fields/f2");
+
+        let changes = diff(&new, &old);
+        assert_eq!(changes.len(), 2);
+        assert!(changes
+            .iter()
+            .all(|c| c.kind == SchemaChangeKind::FieldRemoved));
+    }
+
+    #[test]
+    fn union_branches_and_docs_are_reported() {
+        let old = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        let new = Schema::parse_file("tests/data/schema6.avsc").unwrap();
+        let changes = diff(&old, &new);
+        let kinds = changes.iter().map(|c| c.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![SchemaChangeKind::DocChanged, SchemaChangeKind::TypeChanged]
+        );
+
+        let old = Schema::parse_file("tests/data/schema6.avsc").unwrap();
+        let new = Schema::parse_file("tests/data/schema7.avsc").unwrap();
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, SchemaChangeKind::UnionBranchAdded);
+        assert_eq!(changes[0].path, "/fields/f1/type/2");
+        assert_eq!(changes[0].new.as_deref(), Some("int"));
+    }
+
+    #[test]
+    fn enum_symbol_changes_are_reported() {
+        let old = Schema::parse_file("tests/data/schema9.avsc").unwrap();
+        let new = Schema::parse_file("tests/data/schema10.avsc").unwrap();
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes
+            .iter()
+            .all(|c| c.kind == SchemaChangeKind::SymbolRemoved));
+    }
+
+    #[test]
+    fn defaults_namespaces_and_fixed_sizes_are_reported() {
+        let old = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "namespace": "a", "fields": [
+                {"name": "f1", "type": "string", "default": "foo"},
+                {"name": "f2", "type": {"type": "fixed", "name": "md5", "size": 16}}
+            ]}"#,
+        )
+        .unwrap();
+        let new = Schema::parse_str(
+            r#"{"type": "record", "name": "r", "namespace": "b", "fields": [
+                {"name": "f1", "type": "string", "default": "bar"},
+                {"name": "f2", "type": {"type": "fixed", "name": "md5", "size": 32}}
+            ]}"#,
+        )
+        .unwrap();
+        let kinds = diff(&old, &new).iter().map(|c| c.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                SchemaChangeKind::NamespaceChanged,
+                SchemaChangeKind::DefaultChanged,
+                SchemaChangeKind::NamespaceChanged,
+                SchemaChangeKind::FixedSizeChanged,
+            ]
+        );
+    }
+}
@@ -0,0 +1,34 @@
+/// Exit statuses of the binary.
+#[cfg(test)]
+mod exit_status {
+
+    use std::process::{Command, Output};
+
+    fn degauss(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_degauss"))
+            .args(args)
+            .output()
+            .unwrap()
+    }
+
+    #[test]
+    fn changes_found_by_diff_exit_with_1() {
+        let output = degauss(&["diff", "tests/data/schema1.avsc", "tests/data/schema2.avsc"]);
+        assert_eq!(output.status.code(), Some(1));
+
+        let output = degauss(&["diff", "tests/data/schema1.avsc", "tests/data/schema1.avsc"]);
+        assert_eq!(output.status.code(), Some(0));
+    }
+
+    #[test]
+    fn exit_status_flag_is_rejected() {
+        let output = degauss(&[
+            "-e",
+            "diff",
+            "tests/data/schema1.avsc",
+            "tests/data/schema1.avsc",
+        ]);
+        assert_eq!(output.status.code(), Some(4));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--exit-status was removed"));
+    }
+}
@@ -0,0 +1,165 @@
+/// Explanations: every violation found between the schemas of a history, with the location where it happened.
+#[cfg(test)]
+mod explain {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    #[test]
+    fn compatible_schemas_have_nothing_to_explain() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema2.avsc").unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::Backward);
+        assert!(dc.explain(&schemas).is_empty());
+    }
+
+    #[test]
+    fn histories_without_a_previous_schema_are_not_valid() {
+        let schemas = vec![Schema::parse_file("tests/data/schema1.avsc").unwrap()];
+        let empty: Vec<Schema> = vec![];
+        assert!(!DegaussCheck(DegaussCompatMode::BackwardTransitive).validate(&empty));
+        assert!(!DegaussCheck(DegaussCompatMode::Backward).validate(&schemas));
+        assert!(DegaussCheck(DegaussCompatMode::BackwardTransitive).validate(&schemas));
+
+        let case = DegaussCheck(DegaussCompatMode::Full).report(&schemas, &[]);
+        assert!(!case.passed);
+        assert_eq!(case.findings[0].rule, "nothing_to_compare");
+    }
+
+    #[test]
+    fn adding_a_field_wo_default_is_explained_as_missing_default() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::Backward);
+        let explanation = dc.explain(&schemas);
+        assert_eq!(explanation.len(), 1);
+        assert_eq!(explanation[0].reader_version, 1);
+        assert_eq!(explanation[0].writer_version, 0);
+        assert_eq!(explanation[0].path, "/fields/1");
+        assert_eq!(
+            explanation[0].kind,
+            AvroIncompatibilityKind::MissingDefault.into()
+        );
+    }
+
+    #[test]
+    fn removing_a_type_from_a_union_is_explained_as_missing_union_branch() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema7.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema6.avsc").unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::Backward);
+        let explanation = dc.explain(&schemas);
+        assert_eq!(explanation.len(), 1);
+        assert_eq!(explanation[0].path, "/fields/0/type");
+        assert_eq!(explanation[0].writer_type, "int");
+        assert_eq!(
+            explanation[0].kind,
+            AvroIncompatibilityKind::MissingUnionBranch.into()
+        );
+    }
+
+    #[test]
+    fn removing_enum_symbols_is_explained_per_symbol() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema9.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema10.avsc").unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::Backward);
+        let explanation = dc.explain(&schemas);
+        assert_eq!(explanation.len(), 2);
+        assert!(explanation.iter().all(|e| e.kind
+            == AvroIncompatibilityKind::EnumSymbolRemoved.into()
+            && e.path == "/fields/1/type/symbols"));
+
+        let dc = DegaussCheck(DegaussCompatMode::Forward);
+        assert!(dc.explain(&schemas).is_empty());
+    }
+
+    #[test]
+    fn changing_a_field_type_is_explained_as_type_mismatch() {
+        let schemas = vec![
+            Schema::parse_str(
+                r#"{"type": "record", "name": "r", "fields": [{"name": "f1", "type": "string"}]}"#,
+            )
+            .unwrap(),
+            Schema::parse_str(
+                r#"{"type": "record", "name": "r", "fields": [{"name": "f1", "type": "long"}]}"#,
+            )
+            .unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::Full);
+        let explanation = dc.explain(&schemas);
+        assert_eq!(explanation.len(), 2);
+        assert!(explanation
+            .iter()
+            .all(|e| e.kind == AvroIncompatibilityKind::TypeMismatch.into()));
+    }
+
+    #[test]
+    fn transitive_explanation_points_to_the_offending_historical_schema() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema2.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::BackwardTransitive);
+        let explanation = dc.explain(&schemas);
+        assert_eq!(explanation.len(), 1);
+        assert_eq!(explanation[0].reader_version, 2);
+        assert_eq!(explanation[0].writer_version, 0);
+    }
+
+    #[test]
+    fn recursive_schemas_are_explained_without_looping() {
+        let schemas = vec![
+            Schema::parse_str(
+                r#"{"type": "record", "name": "node", "fields": [
+                    {"name": "value", "type": "int"},
+                    {"name": "next", "type": ["null", "node"]}
+                ]}"#,
+            )
+            .unwrap(),
+            Schema::parse_str(
+                r#"{"type": "record", "name": "node", "fields": [
+                    {"name": "value", "type": "long"},
+                    {"name": "next", "type": ["null", "node"]}
+                ]}"#,
+            )
+            .unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::Backward);
+        assert!(dc.explain(&schemas).is_empty());
+
+        let dc = DegaussCheck(DegaussCompatMode::Forward);
+        let explanation = dc.explain(&schemas);
+        assert_eq!(explanation.len(), 1);
+        assert_eq!(explanation[0].path, "/fields/0/type");
+    }
+
+    #[test]
+    fn violations_stay_in_the_history_once_newer_versions_are_added() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        let dc = DegaussCheck(DegaussCompatMode::Backward);
+        assert!(dc.explain(&schemas).is_empty());
+
+        let explanation = dc.explain_history(&schemas);
+        assert_eq!(explanation.len(), 1);
+        assert_eq!(
+            (explanation[0].reader_version, explanation[0].writer_version),
+            (1, 0)
+        );
+        assert!(dc.explain_history(&schemas[..1]).is_empty());
+        assert!(DegaussCheck(DegaussCompatMode::None)
+            .explain_history(&schemas)
+            .is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+/// Canonical forms and fingerprints of schemas.
+#[cfg(test)]
+mod fingerprint {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    #[test]
+    fn fingerprints_match_the_specification() {
+        let fingerprints = Fingerprints::new(&Schema::parse_str(r#""null""#).unwrap());
+        assert_eq!(fingerprints.canonical_form, r#""null""#);
+        // 0x63dd24e7cc258f8a in little endian byte order
+        assert_eq!(fingerprints.rabin, "8a8f25cce724dd63");
+        assert_eq!(fingerprints.md5, "9b41ef67651c18488a8b08bb67c75699");
+        assert_eq!(
+            fingerprints.sha256,
+            "f072cbec3bf8841871d4284230c5e983dc211a56837aed862487148f947d1a1f"
+        );
+        assert_eq!(fingerprints.single_object_header(), "c3018a8f25cce724dd63");
+    }
+
+    #[test]
+    fn formatting_and_docs_keep_the_fingerprints() {
+        let compact = Schema::parse_str(
+            r#"{"type":"record","name":"myrecord","fields":[{"name":"f1","type":"string"}]}"#,
+        )
+        .unwrap();
+        let documented = Schema::parse_str(
+            r#"{
+                "doc": "A record",
+                "fields": [{"type": "string", "name": "f1", "doc": "A field", "default": ""}],
+                "name": "myrecord",
+                "type": "record"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(Fingerprints::new(&compact), Fingerprints::new(&documented));
+        assert_eq!(
+            Fingerprints::new(&compact),
+            Fingerprints::new(&Schema::parse_file("tests/data/schema1.avsc").unwrap())
+        );
+    }
+
+    #[test]
+    fn changes_change_the_fingerprints() {
+        let old = Fingerprints::new(&Schema::parse_file("tests/data/schema1.avsc").unwrap());
+        let new = Fingerprints::new(&Schema::parse_file("tests/data/schema2.avsc").unwrap());
+        assert_ne!(old.rabin, new.rabin);
+        assert_ne!(old.md5, new.md5);
+        assert_ne!(old.sha256, new.sha256);
+    }
+
+    #[test]
+    fn logical_types_are_stripped_before_fingerprinting() {
+        let logical = Schema::parse_str(
+            r#"{"type": "record", "name": "item", "namespace": "shop", "fields": [
+                {"name": "price", "type": {"type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 2}},
+                {"name": "day", "type": {"type": "int", "logicalType": "date"}, "order": "descending"},
+                {"name": "took", "type": {"type": {"type": "fixed", "name": "took", "size": 12}, "logicalType": "duration"}}
+            ]}"#,
+        )
+        .unwrap();
+        let plain = Schema::parse_str(
+            r#"{"type": "record", "name": "item", "namespace": "shop", "fields": [
+                {"name": "price", "type": "bytes"},
+                {"name": "day", "type": "int"},
+                {"name": "took", "type": {"type": "fixed", "name": "duration", "size": 12}}
+            ]}"#,
+        )
+        .unwrap();
+        let fingerprints = Fingerprints::new(&logical);
+        assert_eq!(
+            fingerprints.canonical_form,
+            r#"{"name":"shop.item","type":"record","fields":[{"name":"price","type":"bytes"},{"name":"day","type":"int"},{"name":"took","type":{"name":"duration","type":"fixed","size":12}}]}"#
+        );
+        assert_eq!(fingerprints, Fingerprints::new(&plain));
+        assert_eq!(
+            degauss::fingerprint::parsing_canonical_form(&logical).unwrap(),
+            fingerprints.canonical_form
+        );
+        // The canonical form sent to schema registry keeps them.
+        assert!(logical.canonical_form().contains("logicalType"));
+    }
+}
@@ -0,0 +1,97 @@
+/// Formatting schema files into a stable layout.
+#[cfg(test)]
+mod fmt {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    const NESTED: &str = r#"{"fields": [
+        {"type": {"symbols": ["A", "B"], "type": "enum", "name": "Kind", "namespace": "com.example.kinds"}, "name": "kind"},
+        {"default": null, "name": "next", "type": ["null", "com.example.kinds.Kind"]},
+        {"name": "self", "type": {"type": "array", "items": "com.example.Node"}, "order": "ignore", "x-owner": "team"}
+    ], "namespace": "com.example", "name": "Node", "type": "record", "doc": "A node"}"#;
+
+    fn format(input: &str, names: Option<NameStyle>) -> String {
+        format_schema(input, &FormatConfig { names, indent: 2 }).unwrap()
+    }
+
+    #[test]
+    fn attributes_are_written_in_a_stable_order() {
+        let formatted = format(NESTED, None);
+        let expected = r#"{
+  "type": "record",
+  "name": "Node",
+  "namespace": "com.example",
+  "doc": "A node",
+  "fields": [
+    {
+      "name": "kind",
+      "type": {
+        "type": "enum",
+        "name": "Kind",
+        "namespace": "com.example.kinds",
+        "symbols": ["A", "B"]
+      }
+    },
+    {
+      "name": "next",
+      "type": ["null", "com.example.kinds.Kind"],
+      "default": null
+    },
+    {
+      "name": "self",
+      "type": {
+        "type": "array",
+        "items": "com.example.Node"
+      },
+      "order": "ignore",
+      "x-owner": "team"
+    }
+  ]
+}
+"#;
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn formatting_is_idempotent_and_keeps_the_schema() {
+        for names in [None, Some(NameStyle::Full), Some(NameStyle::Minimal)] {
+            let formatted = format(NESTED, names);
+            assert_eq!(format(&formatted, names), formatted);
+            assert_eq!(
+                Schema::parse_str(&formatted).unwrap().canonical_form(),
+                Schema::parse_str(NESTED).unwrap().canonical_form()
+            );
+        }
+    }
+
+    #[test]
+    fn names_are_qualified_or_minimized() {
+        let full = format(NESTED, Some(NameStyle::Full));
+        assert!(full.contains(r#""name": "com.example.Node""#));
+        assert!(full.contains(r#""name": "com.example.kinds.Kind""#));
+        assert!(!full.contains(r#""namespace""#));
+
+        let minimal = format(NESTED, Some(NameStyle::Minimal));
+        assert!(minimal.contains(r#""name": "Kind""#));
+        assert!(minimal.contains(r#""namespace": "com.example.kinds""#));
+        // Kind is in another namespace than the record referring to it
+        assert!(minimal.contains(r#"["null", "com.example.kinds.Kind"]"#));
+        assert!(minimal.contains(r#""items": "Node""#));
+    }
+
+    #[test]
+    fn repository_schemas_are_formatted() {
+        for path in find_schema_files(&["tests/data"]).unwrap() {
+            let input = std::fs::read_to_string(&path).unwrap();
+            let formatted = format_schema(&input, &FormatConfig::default()).unwrap();
+            assert_eq!(
+                format_schema(&formatted, &FormatConfig::default()).unwrap(),
+                formatted,
+                "{}",
+                path.display()
+            );
+        }
+        assert!(format_schema("{\"type\": ", &FormatConfig::default()).is_err());
+    }
+}
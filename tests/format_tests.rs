@@ -0,0 +1,101 @@
+/// Schema languages behind the DegaussSchema trait.
+#[cfg(test)]
+mod format {
+
+    use apache_avro::Schema;
+    use degauss::json_schema::JsonSchema;
+    use degauss::prelude::*;
+    use degauss::protobuf::ProtoFile;
+    use std::path::Path;
+
+    fn reparsed<S: DegaussSchema>(schema: &S) -> S {
+        S::parse_str_at(Path::new("canonical"), &schema.canonical_form()).unwrap()
+    }
+
+    #[test]
+    fn canonical_forms_parse_back_into_themselves() {
+        let avro = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        assert_eq!(reparsed(&avro).canonical_form(), avro.canonical_form());
+
+        for version in ["movies_v1", "movies_v2", "accounts_v2"] {
+            let proto =
+                ProtoFile::parse_file(format!("tests/data/proto/{}.proto", version)).unwrap();
+            assert_eq!(reparsed(&proto).canonical_form(), proto.canonical_form());
+        }
+
+        let json = JsonSchema::parse_file("tests/data/json_schema/movie_v2.json").unwrap();
+        assert_eq!(reparsed(&json).canonical_form(), json.canonical_form());
+        assert_eq!(reparsed(&json), json);
+        let list = JsonSchema::parse_str_at(
+            Path::new("list.json"),
+            r##"{"type": "object", "properties": {"next": {"$ref": "#"}}}"##,
+        )
+        .unwrap();
+        assert_eq!(reparsed(&list), list);
+    }
+
+    #[test]
+    fn layout_and_comments_keep_the_fingerprints() {
+        let compact = ProtoFile::parse_str_at(
+            Path::new("compact.proto"),
+            r#"syntax = "proto3"; package p; message B { int64 id = 1; } message A { B b = 2; string name = 1; }"#,
+        )
+        .unwrap();
+        let commented = ProtoFile::parse_str_at(
+            Path::new("commented.proto"),
+            r#"
+            syntax = "proto3";
+            package p;
+
+            // The referenced message
+            message A {
+                string name = 1;
+                B b = 2;
+            }
+
+            message B {
+                int64 id = 1;
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(compact.fingerprints(), commented.fingerprints());
+        assert_eq!(
+            Fingerprints::new(&compact).canonical_form,
+            compact.canonical_form()
+        );
+    }
+
+    #[test]
+    fn payloads_carry_the_schema_type() {
+        let avro = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        assert_eq!(avro.schema_type(), SchemaType::Avro);
+        assert_eq!(avro.payload().schema_type, None);
+
+        let proto = ProtoFile::parse_file("tests/data/proto/movies_v1.proto").unwrap();
+        assert_eq!(proto.payload().schema_type, Some(SchemaType::Protobuf));
+        let json = JsonSchema::parse_file("tests/data/json_schema/movie_v1.json").unwrap();
+        assert_eq!(json.payload().schema_type, Some(SchemaType::Json));
+
+        let other = SchemaType::Other("COLUMNS".to_string());
+        assert_eq!(serde_json::to_string(&other).unwrap(), r#""COLUMNS""#);
+        assert_eq!(
+            serde_json::from_str::<SchemaType>(r#""COLUMNS""#).unwrap(),
+            other
+        );
+        assert_eq!(
+            serde_json::from_str::<SchemaType>(r#""PROTOBUF""#).unwrap(),
+            SchemaType::Protobuf
+        );
+    }
+
+    #[test]
+    fn record_names_come_from_the_schema() {
+        let strategy = SubjectName::RecordName { record_name: None };
+        let avro = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        assert_eq!(strategy.resolve(Some(&avro)).unwrap(), "myrecord");
+        let json = JsonSchema::parse_file("tests/data/json_schema/movie_v1.json").unwrap();
+        assert_eq!(strategy.resolve(Some(&json)).unwrap(), "com.example.Movie");
+        assert!(strategy.resolve(None).is_err());
+    }
+}
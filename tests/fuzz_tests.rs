@@ -0,0 +1,129 @@
+/// Fuzzing: random datums of the writer read with the reader, compared with the static check.
+#[cfg(test)]
+mod fuzz {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const EVERYTHING: &str = r#"{
+        "type": "record",
+        "name": "node",
+        "namespace": "degauss",
+        "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "score", "type": ["null", "int", "float", "string"]},
+            {"name": "tags", "type": {"type": "map", "values": {"type": "array", "items": "bytes"}}},
+            {"name": "kind", "type": {"type": "enum", "name": "kind", "symbols": ["A", "B", "C"]}},
+            {"name": "hash", "type": {"type": "fixed", "name": "md5", "size": 16}},
+            {"name": "price", "type": {"type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 2}},
+            {"name": "amount", "type": {"type": {"type": "fixed", "name": "amount", "size": 8}, "logicalType": "decimal", "precision": 18}},
+            {"name": "uuid", "type": {"type": "string", "logicalType": "uuid"}},
+            {"name": "day", "type": {"type": "int", "logicalType": "date"}},
+            {"name": "at", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+            {"name": "took", "type": {"type": {"type": "fixed", "name": "took", "size": 12}, "logicalType": "duration"}},
+            {"name": "next", "type": ["null", "node"]},
+            {"name": "children", "type": {"type": "array", "items": "node"}}
+        ]
+    }"#;
+
+    #[test]
+    fn generated_datums_conform_to_the_schema() {
+        let schema = Schema::parse_str(EVERYTHING).unwrap();
+        let config = FuzzConfig {
+            max_depth: 3,
+            max_len: 3,
+            ..FuzzConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..200 {
+            let datum = generate(&schema, &mut rng, &config);
+            assert!(datum.validate(&schema));
+            apache_avro::to_avro_datum(&schema, datum).unwrap();
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_datums() {
+        let schema = Schema::parse_str(EVERYTHING).unwrap();
+        let config = FuzzConfig {
+            max_depth: 2,
+            ..FuzzConfig::default()
+        };
+        let first = generate(&schema, &mut StdRng::seed_from_u64(7), &config);
+        let second = generate(&schema, &mut StdRng::seed_from_u64(7), &config);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn compatible_schemas_read_every_datum() {
+        let writer = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        let reader = Schema::parse_file("tests/data/schema2.avsc").unwrap();
+        let outcome = fuzz(&writer, &reader, &FuzzConfig::default()).unwrap();
+        assert!(outcome.compatible);
+        assert!(outcome.agrees());
+        assert_eq!(outcome.resolved, 1000);
+        assert!(outcome.report("schema2.avsc").passed);
+    }
+
+    #[test]
+    fn self_compatibility_of_every_type_is_confirmed() {
+        // apache-avro can't resolve unions with recursive references, leave `next` out.
+        let everything = EVERYTHING.replace(r#"{"name": "next", "type": ["null", "node"]},"#, "");
+        let schema = Schema::parse_str(&everything).unwrap();
+        let config = FuzzConfig {
+            iterations: 200,
+            max_depth: 3,
+            max_len: 3,
+            ..FuzzConfig::default()
+        };
+        let outcome = fuzz(&schema, &schema, &config).unwrap();
+        assert!(outcome.agrees(), "{:?}", outcome.failure);
+    }
+
+    #[test]
+    fn incompatibility_is_confirmed_with_a_minimized_datum() {
+        let writer = Schema::parse_file("tests/data/schema9.avsc").unwrap();
+        let reader = Schema::parse_file("tests/data/schema10.avsc").unwrap();
+        let outcome = fuzz(&writer, &reader, &FuzzConfig::default()).unwrap();
+        assert!(!outcome.compatible);
+        assert!(outcome.agrees());
+
+        let failure = outcome.failure.unwrap();
+        let datum = serde_json::Value::try_from(failure.datum).unwrap();
+        assert_eq!(datum["f1"], "");
+        assert!(datum["f2"] == "DIAMONDS" || datum["f2"] == "CLUBS");
+    }
+
+    #[test]
+    fn minimized_datums_keep_failing_the_same_way() {
+        // apache-avro panics reading unions with recursive references, even of identical schemas.
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "node", "fields": [
+                {"name": "u", "type": {"type": "string", "logicalType": "uuid"}},
+                {"name": "next", "type": ["null", "node"]}
+            ]}"#,
+        )
+        .unwrap();
+        let config = FuzzConfig {
+            seed: 1,
+            ..FuzzConfig::default()
+        };
+        let outcome = fuzz(&schema, &schema, &config).unwrap();
+        assert!(!outcome.agrees());
+
+        let case = outcome.report("node.avsc");
+        assert_eq!(case.compatibility, Some(DegaussCompatMode::Backward));
+        assert!(!case.passed);
+
+        let failure = outcome.failure.unwrap();
+        assert!(
+            failure.error.starts_with("reader panicked"),
+            "{}",
+            failure.error
+        );
+        let datum = serde_json::Value::try_from(failure.datum).unwrap();
+        assert_eq!(datum["u"].as_str().unwrap().len(), 36);
+    }
+}
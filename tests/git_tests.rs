@@ -0,0 +1,152 @@
+/// Schema histories read from the revisions of a file in git.
+#[cfg(test)]
+mod git {
+
+    use degauss::git;
+    use degauss::prelude::*;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// Repository with three commits of `schemas/movie.avsc`, schema1 to schema2 to schema3
+    fn repository(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("degauss-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("schemas")).unwrap();
+        run(&root, &["init", "-q"]);
+        for version in ["schema1", "schema2", "schema3"] {
+            std::fs::copy(
+                format!("tests/data/{}.avsc", version),
+                root.join("schemas/movie.avsc"),
+            )
+            .unwrap();
+            commit(&root, version);
+        }
+        root
+    }
+
+    fn commit(root: &Path, message: &str) {
+        run(root, &["add", "-A", "."]);
+        run(
+            root,
+            &[
+                "-c",
+                "user.name=degauss",
+                "-c",
+                "user.email=degauss@example.com",
+                "commit",
+                "-q",
+                "-m",
+                message,
+            ],
+        );
+    }
+
+    fn run(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn revisions_are_read_in_commit_order() {
+        let root = repository("history");
+        let path = root.join("schemas/movie.avsc");
+        let history = git::history(&path, None).unwrap();
+        assert_eq!(history.len(), 3);
+        for (revision, version) in history.iter().zip(["schema1", "schema2", "schema3"]) {
+            let expected = std::fs::read_to_string(format!("tests/data/{}.avsc", version)).unwrap();
+            assert_eq!(revision.contents, expected);
+        }
+        let head = run(&root, &["rev-parse", "HEAD"]);
+        assert_eq!(history[2].commit, head.trim());
+        assert_eq!(
+            history[2].source("movie.avsc"),
+            format!("movie.avsc@{}", &head[..7])
+        );
+
+        let since = git::history(&path, Some("HEAD~1")).unwrap();
+        assert_eq!(since.len(), 2);
+        assert_eq!(since[0], history[1]);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn working_copy_is_checked_against_a_revision() {
+        let root = repository("against");
+        let path = root.join("schemas/movie.avsc");
+        std::fs::copy("tests/data/schema1.avsc", &path).unwrap();
+
+        let dependencies = SchemaDependencies::new();
+        let revision = git::revision(&path, "HEAD~2").unwrap();
+        let schemas = vec![
+            dependencies
+                .parse_str_as(&path, &revision.contents, None, None)
+                .unwrap(),
+            dependencies.parse_file(&path).unwrap(),
+        ];
+        assert!(DegaussCheck(DegaussCompatMode::Full).validate(&schemas));
+
+        assert!(matches!(
+            git::revision(&path, "nope"),
+            Err(DegaussError::Git { .. })
+        ));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn deleted_revisions_are_skipped() {
+        let root = repository("deleted");
+        let path = root.join("schemas/movie.avsc");
+        std::fs::remove_file(&path).unwrap();
+        commit(&root, "delete");
+        std::fs::copy("tests/data/schema2.avsc", &path).unwrap();
+        commit(&root, "recreate");
+
+        let history = git::history(&path, None).unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(
+            history[3].contents,
+            std::fs::read_to_string("tests/data/schema2.avsc").unwrap()
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn non_transitive_modes_only_get_the_newest_schemas() {
+        let root = repository("modes");
+        let path = root.join("schemas/movie.avsc");
+        let commits = |compatibility| {
+            git::check_history(&path, None, compatibility)
+                .unwrap()
+                .iter()
+                .map(|revision| revision.as_ref().map(|revision| revision.commit.clone()))
+                .collect::<Vec<_>>()
+        };
+        let history = git::history(&path, None).unwrap();
+
+        // The working copy is the same as the newest revision.
+        let transitive = commits(Some(DegaussCompatMode::BackwardTransitive));
+        assert_eq!(transitive.len(), 3);
+        assert!(transitive.iter().all(Option::is_some));
+        assert_eq!(
+            commits(Some(DegaussCompatMode::Backward)),
+            vec![
+                Some(history[1].commit.clone()),
+                Some(history[2].commit.clone())
+            ]
+        );
+
+        std::fs::copy("tests/data/schema1.avsc", &path).unwrap();
+        assert_eq!(commits(None).len(), 4);
+        assert_eq!(
+            commits(Some(DegaussCompatMode::Full)),
+            vec![Some(history[2].commit.clone()), None]
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
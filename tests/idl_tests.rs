@@ -0,0 +1,110 @@
+/// Schemas written in Avro IDL and JSON protocols.
+#[cfg(test)]
+mod idl {
+
+    use apache_avro::Schema;
+    use degauss::idl;
+    use degauss::prelude::*;
+
+    fn parse(path: &str, type_name: &str) -> Schema {
+        SchemaDependencies::new()
+            .parse_file_as(path, None, Some(type_name))
+            .unwrap()
+    }
+
+    #[test]
+    fn idl_is_parsed_into_a_protocol() {
+        let idl =
+            idl::parse(&std::fs::read_to_string("tests/data/idl/users.avdl").unwrap()).unwrap();
+        assert_eq!(idl.imports.len(), 2);
+        assert_eq!(idl.imports[0].kind, idl::ImportKind::Schema);
+        assert_eq!(idl.imports[1].path, "money.avdl");
+
+        let protocol = &idl.protocol;
+        assert_eq!(protocol["protocol"], "Users");
+        assert_eq!(protocol["namespace"], "com.example");
+        assert_eq!(protocol["doc"], "Users and their accounts");
+
+        let kind = &protocol["types"][0];
+        assert_eq!(kind["doc"], "Kinds of accounts");
+        assert_eq!(kind["default"], "PERSONAL");
+        assert_eq!(protocol["types"][1]["size"], 16);
+
+        let fields = &protocol["types"][2]["fields"];
+        assert_eq!(fields[0]["doc"], "Display name");
+        assert_eq!(fields[3]["type"], serde_json::json!(["null", "Address"]));
+        assert_eq!(fields[5]["type"]["logicalType"], "timestamp-micros");
+        assert_eq!(fields[9]["order"], "ignore");
+
+        let get = &protocol["messages"]["get"];
+        assert_eq!(get["response"], "User");
+        assert_eq!(get["errors"], serde_json::json!(["Failure"]));
+        assert_eq!(protocol["messages"]["touch"]["one-way"], true);
+    }
+
+    #[test]
+    fn syntax_errors_point_at_their_position() {
+        let error = idl::parse("protocol P {\n  record R {\n    string\n  }\n}").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 3));
+
+        assert!(matches!(
+            SchemaDependencies::new().parse_file_as(
+                "tests/data/schema1.avsc",
+                Some(SchemaFileFormat::Avdl),
+                None
+            ),
+            Err(DegaussError::IdlFile { .. })
+        ));
+    }
+
+    #[test]
+    fn types_are_selected_with_their_imports() {
+        let user = parse("tests/data/idl/users.avdl", "com.example.User");
+        match &user {
+            Schema::Record { name, fields, .. } => {
+                assert_eq!(name.fullname(None), "com.example.User");
+                assert_eq!(fields.len(), 10);
+            }
+            _ => panic!("User is not a record"),
+        }
+        assert_eq!(
+            parse("tests/data/idl/users.avdl", "User").canonical_form(),
+            user.canonical_form()
+        );
+
+        let last = SchemaDependencies::new()
+            .parse_file("tests/data/idl/users.avdl")
+            .unwrap();
+        assert!(last.canonical_form().contains("com.example.Failure"));
+
+        assert!(matches!(
+            SchemaDependencies::new().parse_file_as(
+                "tests/data/idl/users.avdl",
+                None,
+                Some("Nope")
+            ),
+            Err(DegaussError::ProtocolType { .. })
+        ));
+    }
+
+    #[test]
+    fn idl_and_json_protocols_agree() {
+        let from_idl = parse("tests/data/idl/accounts.avdl", "Account");
+        let from_json = parse("tests/data/idl/accounts.avpr", "Account");
+        assert_eq!(from_idl.canonical_form(), from_json.canonical_form());
+    }
+
+    #[test]
+    fn protocol_types_are_checked_for_compatibility() {
+        let history = SchemaDependencies::new()
+            .parse_files(&[
+                "tests/data/idl/accounts.avdl",
+                "tests/data/idl/accounts_v2.avdl",
+                "tests/data/idl/accounts_v3.avdl",
+            ])
+            .unwrap();
+        assert!(DegaussCheck(DegaussCompatMode::Full).validate(&history[..2]));
+        assert!(DegaussCheck(DegaussCompatMode::Forward).validate(&history[1..]));
+        assert!(!DegaussCheck(DegaussCompatMode::Backward).validate(&history[1..]));
+    }
+}
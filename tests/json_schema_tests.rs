@@ -0,0 +1,187 @@
+/// JSON Schemas and their compatibility.
+#[cfg(test)]
+mod json_schema {
+
+    use apache_avro::Schema;
+    use degauss::json_schema::{
+        self, AdditionalProperties, JsonSchema, JsonSchemaIncompatibilityKind, JsonType,
+    };
+    use degauss::prelude::*;
+
+    fn history(versions: &[&str]) -> Vec<JsonSchema> {
+        versions
+            .iter()
+            .map(|version| {
+                json_schema::parse_file(format!("tests/data/json_schema/{}.json", version)).unwrap()
+            })
+            .collect()
+    }
+
+    fn kinds(
+        incompatibilities: &[Incompatibility],
+    ) -> Vec<(JsonSchemaIncompatibilityKind, String)> {
+        incompatibilities
+            .iter()
+            .map(|incompat| match incompat.kind {
+                IncompatibilityKind::JsonSchema(kind) => (kind, incompat.path.clone()),
+                kind => panic!("{} isn't a JSON Schema kind", kind),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn schemas_are_parsed_with_their_local_references() {
+        let movie = &history(&["movie_v1"])[0];
+        assert_eq!(movie.title.as_deref(), Some("com.example.Movie"));
+        assert_eq!(movie.to_string(), "object");
+        assert_eq!(movie.additional_properties, AdditionalProperties::Closed);
+        assert_eq!(movie.required.iter().collect::<Vec<_>>(), vec!["title"]);
+        assert!(movie.properties["year"].may_be(JsonType::Integer));
+        assert_eq!(movie.properties["genre"].values.as_ref().unwrap().len(), 2);
+        assert_eq!(
+            movie.properties["tags"].items.as_ref().unwrap().to_string(),
+            "string"
+        );
+
+        let director = &movie.properties["director"];
+        assert!(director.required.contains("name"));
+        assert_eq!(
+            director.properties["mentor"].reference.as_deref(),
+            Some("#/definitions/Person")
+        );
+    }
+
+    #[test]
+    fn invalid_schemas_point_at_the_keyword() {
+        let error = json_schema::parse(r#"{"properties": {"a/b": {"type": "text"}}}"#).unwrap_err();
+        assert_eq!(error.pointer, "/properties/a~1b/type");
+
+        let error = json_schema::parse(r##"{"$ref": "#/definitions/Nope"}"##).unwrap_err();
+        assert_eq!(error.pointer, "/$ref");
+
+        assert!(matches!(
+            json_schema::parse_file("tests/data/schema1.avsc"),
+            Err(DegaussError::JsonSchemaFile { .. })
+        ));
+    }
+
+    #[test]
+    fn widening_types_and_adding_enum_values_is_backward_compatible() {
+        let schemas = history(&["movie_v1", "movie_v2"]);
+        assert!(DegaussCheck(DegaussCompatMode::Backward).validate(&schemas));
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Forward).explain(&schemas)),
+            vec![
+                (
+                    JsonSchemaIncompatibilityKind::EnumSymbolRemoved,
+                    "/properties/genre/enum".to_string()
+                ),
+                (
+                    JsonSchemaIncompatibilityKind::TypeMismatch,
+                    "/properties/year".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn content_models_and_required_properties_are_compared() {
+        let schemas = history(&["movie_v2", "movie_v3"]);
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Backward).explain(&schemas)),
+            vec![
+                (
+                    JsonSchemaIncompatibilityKind::TypeMismatch,
+                    "/properties/tags/items".to_string()
+                ),
+                (
+                    JsonSchemaIncompatibilityKind::RequiredPropertyAdded,
+                    "/properties/rating".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Forward).explain(&schemas)),
+            vec![
+                (
+                    JsonSchemaIncompatibilityKind::PropertyRemoved,
+                    "/properties/rating".to_string()
+                ),
+                (
+                    JsonSchemaIncompatibilityKind::TypeMismatch,
+                    "/properties/tags/items".to_string()
+                ),
+                (
+                    JsonSchemaIncompatibilityKind::ContentModelClosed,
+                    "/additionalProperties".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn properties_added_to_open_content_models_are_constrained() {
+        let old = json_schema::parse(r#"{"type": "object", "properties": {"a": {}}}"#).unwrap();
+        let new = json_schema::parse(
+            r#"{"type": "object", "properties": {"a": {}, "b": {"type": "string"}, "c": true}}"#,
+        )
+        .unwrap();
+        let schemas = [old, new];
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Backward).explain(&schemas)),
+            vec![(
+                JsonSchemaIncompatibilityKind::PropertyAddedToOpenContentModel,
+                "/properties/b".to_string()
+            )]
+        );
+        assert!(DegaussCheck(DegaussCompatMode::Forward).validate(&schemas));
+        assert!(!DegaussCheck::pairwise(&schemas).readable[1][0]);
+        assert!(
+            DegaussCheck::tabular_validate_all(&schemas)[&DegaussCompatMode::ForwardTransitive]
+        );
+    }
+
+    #[test]
+    fn json_schema_payloads_carry_their_schema_type() {
+        let text = std::fs::read_to_string("tests/data/json_schema/movie_v1.json").unwrap();
+        let payload = serde_json::to_value(SchemaPayload::json(text.as_str())).unwrap();
+        assert_eq!(payload["schemaType"], "JSON");
+
+        let schema = json_schema::parse(&text).unwrap();
+        assert_eq!(schema.payload(), SchemaPayload::json(text.as_str()));
+        assert!(schema.payload().schema.contains("$schema"));
+        assert_eq!(
+            JsonSchema::default().payload(),
+            SchemaPayload::json(JsonSchema::default().canonical_form())
+        );
+        assert_eq!(
+            SchemaFileFormat::detect("tests/data/json_schema/movie_v1.json", &text).schema_type(),
+            SchemaType::Json
+        );
+    }
+
+    #[test]
+    fn avro_schemas_in_json_files_stay_avro() {
+        let path = "tests/data/schema1.json";
+        let text = std::fs::read_to_string(path).unwrap();
+        assert_eq!(SchemaFileFormat::of(path), SchemaFileFormat::Avsc);
+        assert_eq!(
+            SchemaFileFormat::detect(path, &text),
+            SchemaFileFormat::Avsc
+        );
+        let schema = SchemaDependencies::new().parse_file(path).unwrap();
+        assert_eq!(
+            schema,
+            Schema::parse_file("tests/data/schema1.avsc").unwrap()
+        );
+
+        assert_eq!(
+            SchemaFileFormat::detect("types.json", r#"{"type": ["integer", "null"]}"#),
+            SchemaFileFormat::Json
+        );
+        assert_eq!(
+            SchemaFileFormat::detect("types.avsc", r#"{"type": "object"}"#),
+            SchemaFileFormat::Avsc
+        );
+    }
+}
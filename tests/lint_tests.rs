@@ -0,0 +1,111 @@
+/// Linting schemas against conventions.
+#[cfg(test)]
+mod lint {
+
+    use degauss::prelude::*;
+
+    fn read(path: &str) -> serde_json::Value {
+        SchemaDependencies::new()
+            .resolve_file(path, None, None)
+            .unwrap()
+            .0
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<(String, Option<String>)> {
+        findings
+            .iter()
+            .map(|finding| (finding.rule.clone(), finding.path.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn conventional_schema_has_no_findings() {
+        let linter = Linter::new(LintConfig::default());
+        assert_eq!(linter.lint(&read("tests/data/lint/account.avsc")), vec![]);
+    }
+
+    #[test]
+    fn every_rule_finds_its_violations() {
+        let linter = Linter::new(LintConfig::default());
+        let findings = linter.lint(&read("tests/data/lint/payment.avsc"));
+        let found = |rule: &str, path: &str| (rule.to_string(), Some(path.to_string()));
+        assert_eq!(
+            rules(&findings),
+            vec![
+                found("type_naming", "/"),
+                found("namespace", "/"),
+                found("field_naming", "/fields/paymentId"),
+                found("float_money", "/fields/total_price"),
+                found("nullable_default", "/fields/note"),
+                found("field_doc", "/fields/status"),
+                found("namespace", "/fields/status"),
+                found("enum_default", "/fields/status"),
+            ]
+        );
+        let money = &findings[3];
+        assert_eq!(money.severity, Severity::Error);
+        assert!(money.message.contains("double"));
+    }
+
+    #[test]
+    fn rules_are_configured_from_a_file() {
+        let config = LintConfig::from_file("tests/data/lint/degauss.toml").unwrap();
+        assert_eq!(config.severity(LintRule::FieldDoc), None);
+        assert_eq!(config.severity(LintRule::Namespace), Some(Severity::Error));
+        assert_eq!(
+            config.severity(LintRule::RecordDoc),
+            Some(Severity::Warning)
+        );
+
+        let findings = Linter::new(config).lint(&read("tests/data/lint/payment.avsc"));
+        assert!(findings.iter().all(|finding| finding.rule != "field_doc"));
+        let money = findings
+            .iter()
+            .find(|finding| finding.rule == "float_money")
+            .unwrap();
+        assert_eq!(money.severity, Severity::Warning);
+
+        let config = LintConfig {
+            money_words: Some(vec!["fee".to_string()]),
+            ..LintConfig::default()
+        };
+        let findings = Linter::new(config).lint(&read("tests/data/lint/payment.avsc"));
+        assert!(findings.iter().all(|finding| finding.rule != "float_money"));
+    }
+
+    #[test]
+    fn unknown_rules_are_rejected() {
+        assert!(toml::from_str::<LintConfig>("[rules.nope]\nenabled = false").is_err());
+        assert!(matches!(
+            LintConfig::from_file("tests/data/schema1.avsc"),
+            Err(DegaussError::ConfigFile { .. })
+        ));
+    }
+
+    #[test]
+    fn report_fails_on_errors_only() {
+        let linter = Linter::new(LintConfig::default());
+        let case = linter.report(
+            &read("tests/data/lint/payment.avsc"),
+            "tests/data/lint/payment.avsc",
+        );
+        assert!(!case.passed);
+        assert!(case
+            .findings
+            .iter()
+            .all(|finding| finding.file.as_deref() == Some("tests/data/lint/payment.avsc")));
+
+        let mut config = LintConfig::default();
+        config.rules.insert(
+            LintRule::FloatMoney,
+            RuleConfig {
+                enabled: true,
+                severity: Some(Severity::Note),
+            },
+        );
+        let case = Linter::new(config).report(&read("tests/data/lint/payment.avsc"), "payment");
+        assert!(case.passed);
+        let report = Report::new("degauss lint", vec![case]);
+        assert!(report.render(ReportFormat::Sarif).contains("enum_default"));
+    }
+}
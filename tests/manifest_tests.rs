@@ -0,0 +1,122 @@
+/// Manifests of the subjects of a project and checking all of them.
+#[cfg(test)]
+mod manifest {
+
+    use degauss::prelude::*;
+    use std::path::PathBuf;
+
+    fn manifest() -> Manifest {
+        Manifest::from_file("tests/data/manifest/degauss.toml").unwrap()
+    }
+
+    #[test]
+    fn subjects_are_read_with_their_histories() {
+        let manifest = manifest();
+        assert_eq!(manifest.root, PathBuf::from("tests/data/manifest"));
+        assert_eq!(manifest.subjects.len(), 3);
+
+        let movies = &manifest.subjects[1];
+        assert_eq!(movies.subject().unwrap(), "movies-key");
+        assert_eq!(movies.compatibility, DegaussCompatMode::BackwardTransitive);
+        assert_eq!(
+            movies.history(&manifest.root).unwrap(),
+            vec![
+                PathBuf::from("tests/data/manifest/movies/movie_v1.avsc"),
+                PathBuf::from("tests/data/manifest/movies/movie_v2.avsc"),
+                PathBuf::from("tests/data/manifest/movies/movie_v10.avsc"),
+            ]
+        );
+        assert_eq!(manifest.subjects[0].subject().unwrap(), "users-value");
+        assert_eq!(manifest.subjects[2].subject().unwrap(), "myrecord");
+    }
+
+    #[test]
+    fn every_subject_is_checked() {
+        let cases = manifest().check().unwrap();
+        let outcomes = cases
+            .iter()
+            .map(|case| (case.name.as_str(), case.passed))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("users-value", true),
+                ("movies-key", true),
+                ("myrecord", false)
+            ]
+        );
+        assert_eq!(cases[2].compatibility, Some(DegaussCompatMode::Backward));
+        assert_eq!(
+            cases[2].findings[0].file.as_deref(),
+            Some("tests/data/manifest/../schema3.avsc")
+        );
+
+        let report = Report::new("degauss check-all", cases);
+        assert!(!report.passed);
+    }
+
+    #[test]
+    fn protobuf_and_json_schema_subjects_are_checked() {
+        let entry = |toml: &str| -> SubjectEntry { toml::from_str(toml).unwrap() };
+        let manifest = Manifest {
+            root: PathBuf::from("tests/data"),
+            subjects: vec![
+                entry(
+                    "subject = \"movies\"\ncompatibility = \"full_transitive\"\ndir = \"proto\"\nversions = \"movies_v[12].proto\"",
+                ),
+                entry(
+                    "subject = \"movie\"\ncompatibility = \"backward\"\nschemas = [\"json_schema/movie_v2.json\", \"json_schema/movie_v3.json\"]",
+                ),
+            ],
+            ..Manifest::default()
+        };
+        let cases = manifest.check().unwrap();
+        assert_eq!(
+            cases
+                .iter()
+                .map(|case| (case.name.as_str(), case.passed))
+                .collect::<Vec<_>>(),
+            vec![("movies", true), ("movie", false)]
+        );
+        assert_eq!(cases[1].findings[0].rule, "type_mismatch");
+
+        let mixed = entry(
+            "subject = \"mixed\"\ncompatibility = \"full\"\nschemas = [\"schema1.avsc\", \"proto/movies_v1.proto\"]",
+        );
+        let history = mixed.history("tests/data").unwrap();
+        assert!(matches!(
+            mixed.schema_type(&history),
+            Err(DegaussError::Usage(message)) if message.contains("mixed")
+        ));
+    }
+
+    #[test]
+    fn invalid_subjects_are_rejected() {
+        let entry = |toml: &str| -> SubjectEntry { toml::from_str(toml).unwrap() };
+
+        let nameless = entry("compatibility = \"full\"\nschemas = [\"a.avsc\"]");
+        assert!(matches!(nameless.subject(), Err(DegaussError::Usage(_))));
+
+        let both = entry(
+            "topic = \"t\"\ncompatibility = \"full\"\nschemas = [\"a.avsc\"]\ndir = \"d\"\nversions = \"*.avsc\"",
+        );
+        assert!(matches!(both.history("."), Err(DegaussError::Usage(_))));
+
+        let empty = entry(
+            "topic = \"t\"\ncompatibility = \"full\"\ndir = \"tests/data/manifest/movies\"\nversions = \"*.avdl\"",
+        );
+        assert!(matches!(empty.history("."), Err(DegaussError::Usage(_))));
+
+        assert!(toml::from_str::<Manifest>(
+            "[[subjects]]\ntopic = \"t\"\ncompatibility = \"sideways\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn versions_are_ordered_by_their_numbers() {
+        let mut names = vec!["v10.avsc", "v2.avsc", "v1.avsc", "v1_1.avsc"];
+        names.sort_by(|a, b| version_order(a, b));
+        assert_eq!(names, vec!["v1.avsc", "v1_1.avsc", "v2.avsc", "v10.avsc"]);
+    }
+}
@@ -0,0 +1,35 @@
+/// Pairwise compatibility: for every pair of versions in a history, can the reader version read data written by the
+/// writer version.
+#[cfg(test)]
+mod pairwise_compat {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    #[test]
+    fn matrix_has_a_row_and_a_column_per_version() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema2.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema8.avsc").unwrap(),
+        ];
+        let matrix = DegaussCheck::pairwise(&schemas);
+        assert_eq!(matrix.len(), 3);
+        assert!(matrix.readable.iter().all(|row| row.len() == 3));
+        assert!(matrix.readable.iter().flatten().all(|readable| *readable));
+    }
+
+    #[test]
+    fn matrix_shows_where_a_transitive_chain_broke() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema2.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        let matrix = DegaussCheck::pairwise(&schemas);
+        assert!(matrix.can_read(2, 1));
+        assert!(!matrix.can_read(2, 0));
+        assert!(matrix.can_read(0, 2));
+        assert!((0..3).all(|version| matrix.can_read(version, version)));
+    }
+}
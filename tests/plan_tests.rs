@@ -0,0 +1,187 @@
+/// Plans bringing the subjects of a manifest to schema registry.
+#[cfg(test)]
+mod plan {
+
+    use apache_avro::Schema;
+    use degauss::json_schema::JsonSchema;
+    use degauss::prelude::*;
+    use degauss::protobuf::ProtoFile;
+    use std::path::PathBuf;
+
+    fn files(names: &[&str]) -> (Vec<PathBuf>, Vec<Schema>) {
+        let files = names
+            .iter()
+            .map(|name| PathBuf::from(format!("tests/data/{}.avsc", name)))
+            .collect::<Vec<_>>();
+        let schemas = files
+            .iter()
+            .map(|file| Schema::parse_file(file).unwrap())
+            .collect();
+        (files, schemas)
+    }
+
+    fn rules(plan: &SubjectPlan) -> Vec<(String, Severity)> {
+        plan.case()
+            .findings
+            .iter()
+            .map(|finding| (finding.rule.clone(), finding.severity))
+            .collect()
+    }
+
+    #[test]
+    fn new_subjects_are_configured_and_registered() {
+        let (files, schemas) = files(&["schema1", "schema2"]);
+        let plan = SubjectPlan::new(
+            "myrecord".to_string(),
+            DegaussCompatMode::Backward,
+            &files,
+            schemas,
+            &RegisteredSubject::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            plan.actions,
+            vec![
+                Action::SetCompatibility {
+                    from: None,
+                    to: DegaussCompatMode::Backward
+                },
+                Action::Register {
+                    file: files[1].clone()
+                },
+            ]
+        );
+        assert!(plan.passed());
+        assert_eq!(
+            plan.actions[0].to_string(),
+            "set compatibility to backward, instead of the global one"
+        );
+    }
+
+    #[test]
+    fn schemas_to_register_are_checked_against_the_registered_versions() {
+        let (files, schemas) = files(&["schema1", "schema3"]);
+        let registered = RegisteredSubject {
+            compatibility: Some(DegaussCompatMode::Backward),
+            versions: vec![(1, schemas[0].clone())],
+            file_versions: vec![Some(1), None],
+        };
+        let plan = SubjectPlan::new(
+            "myrecord".to_string(),
+            DegaussCompatMode::Backward,
+            &files,
+            schemas,
+            &registered,
+        )
+        .unwrap();
+        assert!(!plan.passed());
+        assert_eq!(
+            rules(&plan),
+            vec![
+                ("register".to_string(), Severity::Note),
+                ("missing_default".to_string(), Severity::Error),
+            ]
+        );
+        assert_eq!(
+            plan.findings[0].file.as_deref(),
+            Some("tests/data/schema3.avsc")
+        );
+    }
+
+    #[test]
+    fn versions_registered_out_of_band_are_drift() {
+        let (files, schemas) = files(&["schema1", "schema2"]);
+        let registered = RegisteredSubject {
+            compatibility: Some(DegaussCompatMode::Full),
+            versions: vec![
+                (1, schemas[0].clone()),
+                (2, schemas[1].clone()),
+                (3, schemas[1].clone()),
+            ],
+            file_versions: vec![Some(1), Some(2)],
+        };
+        let plan = SubjectPlan::new(
+            "myrecord".to_string(),
+            DegaussCompatMode::Forward,
+            &files,
+            schemas,
+            &registered,
+        )
+        .unwrap();
+        assert!(plan.passed());
+        assert_eq!(
+            rules(&plan),
+            vec![
+                ("set_compatibility".to_string(), Severity::Note),
+                ("out_of_band_version".to_string(), Severity::Warning),
+            ]
+        );
+        assert_eq!(
+            plan.findings[0].file.as_deref(),
+            Some("myrecord/versions/3")
+        );
+
+        let plan = Plan {
+            subjects: vec![plan],
+            unmanaged: vec!["legacy-value".to_string()],
+        };
+        assert!(!plan.is_empty());
+        let cases = plan.cases();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[1].name, "legacy-value");
+        assert_eq!(cases[1].findings[0].rule, "unmanaged_subject");
+        assert!(Report::new("degauss schema-registry plan", cases).passed);
+    }
+
+    #[test]
+    fn protobuf_schemas_are_checked_against_the_registered_versions() {
+        let files = ["movies_v1", "movies_v2", "movies_v3"]
+            .iter()
+            .map(|name| PathBuf::from(format!("tests/data/proto/{}.proto", name)))
+            .collect::<Vec<_>>();
+        let protos = files
+            .iter()
+            .map(|file| ProtoFile::parse_file(file).unwrap())
+            .collect::<Vec<_>>();
+        let registered = RegisteredSubject {
+            compatibility: Some(DegaussCompatMode::Backward),
+            versions: vec![(1, protos[0].clone()), (2, protos[1].clone())],
+            file_versions: vec![Some(1), Some(2), None],
+        };
+        let plan = SubjectPlan::new(
+            "movies".to_string(),
+            DegaussCompatMode::Backward,
+            &files,
+            protos,
+            &registered,
+        )
+        .unwrap();
+        assert!(!plan.passed());
+        assert_eq!(
+            plan.actions,
+            vec![Action::Register {
+                file: files[2].clone()
+            }]
+        );
+        assert_eq!(rules(&plan)[1].1, Severity::Error);
+        let text = std::fs::read_to_string(&files[2]).unwrap();
+        assert_eq!(plan.payload, SchemaPayload::protobuf(text));
+    }
+
+    #[test]
+    fn json_schemas_are_registered_as_written() {
+        let files = vec![PathBuf::from("tests/data/json_schema/movie_v1.json")];
+        let schemas = vec![JsonSchema::parse_file(&files[0]).unwrap()];
+        let plan = SubjectPlan::new(
+            "movie".to_string(),
+            DegaussCompatMode::Backward,
+            &files,
+            schemas,
+            &RegisteredSubject::default(),
+        )
+        .unwrap();
+        let text = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(text.contains("$schema"));
+        assert_eq!(plan.payload, SchemaPayload::json(text));
+    }
+}
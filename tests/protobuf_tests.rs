@@ -0,0 +1,204 @@
+/// Protobuf schemas and their compatibility.
+#[cfg(test)]
+mod protobuf {
+
+    use degauss::prelude::*;
+    use degauss::protobuf::{
+        self, FieldType, Label, ProtoFile, ProtobufIncompatibilityKind, Scalar, Syntax,
+    };
+
+    fn parse(path: &str) -> ProtoFile {
+        protobuf::parse(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn history(versions: &[&str]) -> Vec<ProtoFile> {
+        versions
+            .iter()
+            .map(|version| parse(&format!("tests/data/proto/{}.proto", version)))
+            .collect()
+    }
+
+    fn kinds(incompatibilities: &[Incompatibility]) -> Vec<(ProtobufIncompatibilityKind, String)> {
+        incompatibilities
+            .iter()
+            .map(|incompat| match incompat.kind {
+                IncompatibilityKind::Protobuf(kind) => (kind, incompat.path.clone()),
+                kind => panic!("{} isn't a Protobuf kind", kind),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn proto_files_are_parsed() {
+        let file = parse("tests/data/proto/movies_v1.proto");
+        assert_eq!(file.syntax, Syntax::Proto3);
+        assert_eq!(file.package.as_deref(), Some("com.example.movies"));
+        assert_eq!(file.imports, vec!["google/protobuf/timestamp.proto"]);
+
+        let movie = &file.messages[0];
+        assert_eq!(movie.name, "com.example.movies.Movie");
+        assert_eq!(movie.fields.len(), 9);
+        assert_eq!(
+            movie.fields[2].kind,
+            FieldType::Named("com.example.movies.Genre".to_string())
+        );
+        assert_eq!(movie.fields[3].label, Some(Label::Repeated));
+        assert_eq!(
+            movie.fields[4].kind,
+            FieldType::Map(
+                Scalar::String,
+                Box::new(FieldType::Named(
+                    "com.example.movies.Movie.Rating".to_string()
+                ))
+            )
+        );
+        assert_eq!(
+            movie.fields[5].kind,
+            FieldType::Named("google.protobuf.Timestamp".to_string())
+        );
+        assert_eq!(movie.fields[8].oneof.as_deref(), Some("source"));
+        assert_eq!(file.enums[0].values[2].name, "COMEDY");
+
+        let v2 = parse("tests/data/proto/movies_v2.proto");
+        assert!(v2.messages[0].is_reserved(7));
+        assert_eq!(v2.messages[0].reserved_names, vec!["director"]);
+    }
+
+    #[test]
+    fn syntax_errors_point_at_their_position() {
+        let error =
+            protobuf::parse("syntax = \"proto3\";\nmessage M {\n  string = 1;\n}").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 10));
+        assert!(protobuf::parse("syntax = \"proto2\";\nmessage M { string a = 1; }").is_err());
+    }
+
+    #[test]
+    fn wire_compatible_changes_are_accepted() {
+        let files = history(&["movies_v1", "movies_v2"]);
+        assert_eq!(
+            DegaussCheck(DegaussCompatMode::FullTransitive).explain(&files),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn reused_numbers_renamed_values_and_oneof_moves_are_found() {
+        let files = history(&["movies_v1", "movies_v2", "movies_v3"]);
+        let backward = DegaussCheck(DegaussCompatMode::Backward).explain(&files);
+        assert_eq!(
+            kinds(&backward),
+            vec![
+                (
+                    ProtobufIncompatibilityKind::EnumValueChanged,
+                    "/Genre/values/HORROR".to_string()
+                ),
+                (
+                    ProtobufIncompatibilityKind::FieldNumberReused,
+                    "/Movie/fields/director_cut".to_string()
+                ),
+                (
+                    ProtobufIncompatibilityKind::FieldMovedToOneof,
+                    "/Movie/fields/country".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            (backward[0].reader_version, backward[0].writer_version),
+            (2, 1)
+        );
+
+        let transitive = DegaussCheck(DegaussCompatMode::BackwardTransitive).explain(&files);
+        let reused = transitive
+            .iter()
+            .find(|incompat| {
+                incompat.writer_version == 0
+                    && incompat.kind == ProtobufIncompatibilityKind::FieldNumberReused.into()
+            })
+            .unwrap();
+        assert_eq!(reused.reader_type, "bool");
+        assert_eq!(reused.writer_type, "string");
+    }
+
+    #[test]
+    fn required_fields_and_closed_enums_are_checked() {
+        let files = history(&["accounts_v1", "accounts_v2"]);
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Backward).explain(&files)),
+            vec![
+                (
+                    ProtobufIncompatibilityKind::RequiredFieldAdded,
+                    "/Account/fields/owner".to_string()
+                ),
+                (
+                    ProtobufIncompatibilityKind::EnumSymbolRemoved,
+                    "/Account.Kind/values/BUSINESS".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Forward).explain(&files)),
+            vec![]
+        );
+        let reversed = [files[1].clone(), files[0].clone()];
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Forward).explain(&reversed)),
+            vec![
+                (
+                    ProtobufIncompatibilityKind::RequiredFieldRemoved,
+                    "/Account/fields/owner".to_string()
+                ),
+                (
+                    ProtobufIncompatibilityKind::EnumSymbolRemoved,
+                    "/Account.Kind/values/BUSINESS".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn messages_and_packages_are_compared_by_name() {
+        let old = protobuf::parse(
+            "syntax = \"proto3\"; package a; message M { int32 x = 1; } message N {}",
+        )
+        .unwrap();
+        let new =
+            protobuf::parse("syntax = \"proto3\"; package b; message M { sint32 x = 1; }").unwrap();
+        let files = [old, new];
+        assert_eq!(
+            kinds(&DegaussCheck(DegaussCompatMode::Backward).explain(&files)),
+            vec![
+                (ProtobufIncompatibilityKind::PackageChanged, "/".to_string()),
+                (
+                    ProtobufIncompatibilityKind::TypeMismatch,
+                    "/M/fields/x".to_string()
+                ),
+                (
+                    ProtobufIncompatibilityKind::MessageRemoved,
+                    "/N".to_string()
+                ),
+            ]
+        );
+        assert!(!DegaussCheck::pairwise(&files).readable[1][0]);
+        assert!(DegaussCheck::pairwise(&files).readable[1][1]);
+    }
+
+    #[test]
+    fn protobuf_payloads_carry_their_schema_type() {
+        let text = std::fs::read_to_string("tests/data/proto/movies_v1.proto").unwrap();
+        let payload = serde_json::to_value(SchemaPayload::protobuf(text.as_str())).unwrap();
+        assert_eq!(payload["schemaType"], "PROTOBUF");
+        assert_eq!(payload["schema"], text.as_str());
+
+        let payload = parse("tests/data/proto/movies_v1.proto").payload();
+        assert_eq!(payload, SchemaPayload::protobuf(text.as_str()));
+        assert!(payload.schema.contains("service Movies"));
+
+        let schema = apache_avro::Schema::parse_str(r#""string""#).unwrap();
+        let payload = serde_json::to_value(SchemaPayload::new(&schema)).unwrap();
+        assert!(payload.get("schemaType").is_none());
+        assert_eq!(
+            SchemaFileFormat::of("tests/data/proto/movies_v1.proto").schema_type(),
+            SchemaType::Protobuf
+        );
+    }
+}
@@ -0,0 +1,101 @@
+/// Checking a schema with schema registry and locally, against a registry served from the test.
+#[cfg(test)]
+mod registry_check {
+
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::process::Command;
+
+    const SUBJECT_NOT_FOUND: &str = r#"{"error_code": 40401, "message": "Subject not found."}"#;
+
+    /// Answer a request the way schema registry does for a subject without any versions
+    fn respond(stream: TcpStream) {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        reader
+            .by_ref()
+            .take(content_length)
+            .read_to_end(&mut vec![])
+            .unwrap();
+
+        let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let (status, body) = match path {
+            "/config" => ("200 OK", r#"{"compatibilityLevel": "BACKWARD"}"#),
+            _ => ("404 Not Found", SUBJECT_NOT_FOUND),
+        };
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+        .unwrap();
+    }
+
+    fn registry() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                respond(stream.unwrap());
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn first_version_of_a_subject_passes_the_local_check() {
+        let output = Command::new(env!("CARGO_BIN_EXE_degauss"))
+            .args([
+                "schema-registry",
+                "--schema-registry-url",
+                &registry(),
+                "compatibility",
+                "check",
+                "--subject-type",
+                "value",
+                "--topic",
+                "movies",
+                "--schema-path",
+                "tests/data/schema1.avsc",
+                "--local",
+                "--output",
+                "json",
+            ])
+            .env_remove("DEGAUSS_SCHEMA_REGISTRY_USER")
+            .env_remove("DEGAUSS_SCHEMA_REGISTRY_PASS")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(
+            output.status.code(),
+            Some(0),
+            "{}{}",
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(report["passed"], true);
+        assert_eq!(report["cases"][0]["name"], "movies-value-local");
+        assert_eq!(report["cases"][0]["findings"], serde_json::json!([]));
+        assert_eq!(report["cases"][2]["name"], "movies-value-agreement");
+        assert_eq!(report["cases"][2]["passed"], true);
+    }
+}
@@ -0,0 +1,122 @@
+/// Reports: check results rendered in machine readable formats for CI.
+#[cfg(test)]
+mod report {
+
+    use apache_avro::Schema;
+    use degauss::prelude::*;
+
+    fn failing_report() -> Report {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        let sources = vec![
+            "tests/data/schema1.avsc".to_string(),
+            "tests/data/schema3.avsc".to_string(),
+        ];
+        let case = DegaussCheck(DegaussCompatMode::Backward).report(&schemas, &sources);
+        Report::new("degauss", vec![case])
+    }
+
+    #[test]
+    fn report_case_carries_the_findings_of_the_check() {
+        let report = failing_report();
+        assert!(!report.passed);
+        assert_eq!(report.cases.len(), 1);
+        assert_eq!(
+            report.cases[0].compatibility,
+            Some(DegaussCompatMode::Backward)
+        );
+        let finding = &report.cases[0].findings[0];
+        assert_eq!(finding.rule, "missing_default");
+        assert_eq!(finding.file.as_deref(), Some("tests/data/schema3.avsc"));
+        assert_eq!(finding.path.as_deref(), Some("/fields/1"));
+    }
+
+    #[test]
+    fn json_report_is_parseable() {
+        let json: serde_json::Value =
+            serde_json::from_str(&failing_report().render(ReportFormat::Json)).unwrap();
+        assert_eq!(json["passed"], false);
+        assert_eq!(json["cases"][0]["compatibility"], "backward");
+    }
+
+    #[test]
+    fn junit_report_has_a_failure_per_failing_case() {
+        let xml = failing_report().render(ReportFormat::Junit);
+        assert!(xml.contains(r#"<testsuites name="degauss" tests="1" failures="1">"#));
+        assert!(xml.contains("<failure "));
+        assert!(xml.contains("&apos;f2&apos;"));
+    }
+
+    #[test]
+    fn sarif_report_has_a_result_per_finding() {
+        let sarif: serde_json::Value =
+            serde_json::from_str(&failing_report().render(ReportFormat::Sarif)).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "missing_default");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "tests/data/schema3.avsc"
+        );
+    }
+
+    #[test]
+    fn markdown_report_lists_cases_and_findings() {
+        let md = failing_report().render(ReportFormat::Markdown);
+        assert!(md.contains("| backward | backward | failed |"));
+        assert!(md.contains("| missing_default |"));
+    }
+
+    #[test]
+    fn passing_report_has_no_findings() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema2.avsc").unwrap(),
+        ];
+        let case = DegaussCheck(DegaussCompatMode::Backward).report(&schemas, &[]);
+        let report = Report::new("degauss", vec![case]);
+        assert!(report.passed);
+        assert!(report
+            .render(ReportFormat::Junit)
+            .contains(r#"<testcase name="backward" classname="backward"/>"#));
+    }
+
+    #[test]
+    fn reports_name_the_modes_like_the_command_line() {
+        let json: serde_json::Value =
+            serde_json::from_str(&failing_report().render(ReportFormat::Json)).unwrap();
+        assert_eq!(json["cases"][0]["compatibility"], "backward");
+
+        // Schema registry payloads keep the upper case names.
+        let mode = DegaussCompatMode::BackwardTransitive;
+        let wire = serde_json::to_string(&mode).unwrap();
+        assert_eq!(wire, r#""BACKWARD_TRANSITIVE""#);
+        assert_eq!(
+            serde_json::from_str::<DegaussCompatMode>(&wire).unwrap(),
+            mode
+        );
+    }
+
+    #[test]
+    fn matrix_is_part_of_json_and_markdown_reports() {
+        let schemas = vec![
+            Schema::parse_file("tests/data/schema1.avsc").unwrap(),
+            Schema::parse_file("tests/data/schema3.avsc").unwrap(),
+        ];
+        let report = failing_report().with_matrix(DegaussCheck::pairwise(&schemas));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&report.render(ReportFormat::Json)).unwrap();
+        assert_eq!(json["matrix"]["readable"][1][0], false);
+        assert!(report
+            .render(ReportFormat::Markdown)
+            .contains("| 1 | false | true |"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&failing_report().render(ReportFormat::Json)).unwrap();
+        assert!(json.get("matrix").is_none());
+    }
+}
@@ -0,0 +1,87 @@
+/// Data verification: existing object container files read with a newer reader schema.
+#[cfg(test)]
+mod verify_data {
+
+    use apache_avro::types::{Record, Value};
+    use apache_avro::{Schema, Writer};
+    use degauss::prelude::*;
+
+    const SUITS: [&str; 4] = ["SPADES", "HEARTS", "DIAMONDS", "CLUBS"];
+
+    /// Object container of the given records, written with `schema`
+    fn container(schema: &Schema, records: Vec<Vec<(&str, Value)>>) -> Vec<u8> {
+        let mut writer = Writer::new(schema, Vec::new());
+        for fields in records {
+            let mut record = Record::new(schema).unwrap();
+            for (name, value) in fields {
+                record.put(name, value);
+            }
+            writer.append(record).unwrap();
+        }
+        writer.into_inner().unwrap()
+    }
+
+    fn card(suit: &str) -> Vec<(&'static str, Value)> {
+        let symbol = SUITS.iter().position(|s| *s == suit).unwrap();
+        vec![
+            ("f1", Value::String("card".to_string())),
+            ("f2", Value::Enum(symbol as u32, suit.to_string())),
+        ]
+    }
+
+    fn cards(suits: &[&str]) -> Vec<Vec<(&'static str, Value)>> {
+        suits.iter().map(|suit| card(suit)).collect()
+    }
+
+    #[test]
+    fn data_written_with_an_older_schema_is_read() {
+        let writer = Schema::parse_file("tests/data/schema1.avsc").unwrap();
+        let reader = Schema::parse_file("tests/data/schema2.avsc").unwrap();
+        let records = vec![
+            vec![("f1", Value::String("a".to_string()))],
+            vec![("f1", Value::String("b".to_string()))],
+        ];
+        let data = container(&writer, records);
+        let verification = verify_data(&reader, &data[..]).unwrap();
+        assert!(verification.passed());
+        assert_eq!(verification.records, 2);
+        assert_eq!(verification.writer_schema, writer);
+    }
+
+    #[test]
+    fn first_failing_record_is_reported() {
+        let writer = Schema::parse_file("tests/data/schema9.avsc").unwrap();
+        let reader = Schema::parse_file("tests/data/schema10.avsc").unwrap();
+        let data = container(&writer, cards(&["SPADES", "HEARTS", "CLUBS", "SPADES"]));
+        let verification = verify_data(&reader, &data[..]).unwrap();
+        assert!(!verification.passed());
+        assert_eq!(verification.records, 2);
+        assert_eq!(verification.failure.as_ref().unwrap().index, 2);
+
+        let case = verification.report("suits.avro");
+        assert!(!case.passed);
+        assert_eq!(case.findings[0].rule, "unreadable_record");
+        assert_eq!(case.findings[0].file.as_deref(), Some("suits.avro"));
+    }
+
+    #[test]
+    fn data_which_only_uses_readable_symbols_passes() {
+        let writer = Schema::parse_file("tests/data/schema9.avsc").unwrap();
+        let reader = Schema::parse_file("tests/data/schema10.avsc").unwrap();
+        let data = container(&writer, cards(&["SPADES", "HEARTS"]));
+        assert!(verify_data(&reader, &data[..]).unwrap().passed());
+    }
+
+    #[test]
+    fn missing_data_file_is_an_error() {
+        let reader = Schema::parse_file("tests/data/schema2.avsc").unwrap();
+        assert!(matches!(
+            verify_data_file(&reader, "tests/data/missing.avro"),
+            Err(DegaussError::FileRead { .. })
+        ));
+        assert!(matches!(
+            verify_data_file(&reader, "tests/data/schema2.avsc"),
+            Err(DegaussError::DataFile { .. })
+        ));
+    }
+}